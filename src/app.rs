@@ -1,21 +1,93 @@
 use imgui::*;
+use std::io::Write;
+use std::path::Path;
+use crate::cms::Cms;
+use crate::der::Tag;
+use crate::detect;
+use crate::document::{Document, Kind};
 use crate::error::{Error, Result};
-use crate::{hex, oid, oid_names, der::{self, Tag}};
+use crate::hash_calculator::HashCalculator;
+use crate::node::Node;
+use crate::notation::Notation;
+use crate::notation_window::NotationWindow;
+use crate::oid_browser::OidBrowser;
+use crate::path_validator::PathValidator;
+use crate::schema_window::{self, SchemaWindow};
+use crate::cards::{self, Action};
+use crate::signature::{self, PublicKey};
+use crate::{config, hex, json, name, oid, oid_names, value, x509};
 
 pub struct App {
     signal_stop: bool,
-    next_id: usize,
-    tree: Option<Tree>,
+    documents: Vec<Document>,
+    /// Documents opened while drawing the others, added after the frame.
+    opened: Vec<Document>,
+    /// Incremented every time a document is opened or closed.
+    generation: usize,
+    errors: Vec<String>,
+    selection: Option<Selection>,
+    /// Open the parents of the selected node and scroll to it on the next frame.
+    navigate: bool,
+    /// Document whose tab must be selected on the next frame.
+    focus_document: Option<usize>,
+    oid_report: oid_names::LoadReport,
+    oid_browser: OidBrowser,
+    hash_calculator: HashCalculator,
+    path_validator: PathValidator,
+    schema_window: SchemaWindow,
+    notation_window: NotationWindow,
+    name_style: name::Style,
+    /// Show the private key components instead of hiding them.
+    reveal_secrets: bool,
+    /// Document and structure the password prompt is opened for.
+    password_prompt: Option<(usize, usize)>,
+    open_password_prompt: bool,
+    password: String,
+    password_error: Option<String>,
+    /// Document and structure the detached content is chosen for.
+    content_prompt: Option<(usize, usize)>,
+    open_content_prompt: bool,
+    /// Document and structure to verify once the frame is drawn, with the
+    /// document holding the detached content.
+    verification: Option<(usize, usize, Option<usize>)>,
+    /// Every loaded certificate, that signatures can be verified with.
+    certificates: Vec<(String, Selection)>,
+    /// Signed element to verify once the frame is drawn, and the certificate
+    /// to verify it with, or None for its own key.
+    signature_check: Option<(Selection, Option<Selection>)>,
+    /// Every loaded CRL, that certificates can be looked up in.
+    crls: Vec<(String, Selection)>,
+    /// Certificate to look up once the frame is drawn, and the CRL.
+    revocation_check: Option<(Selection, Selection)>,
+    /// Document the JSON export prompt is opened for.
+    export_prompt: Option<usize>,
+    open_export_prompt: bool,
+    export_path: String,
+    export_error: Option<String>,
+    /// Title and text of the result shown in a popup.
+    message: Option<(&'static str, String)>,
+    open_message: bool,
 }
 
-pub struct Tree {
-    pub expanded: bool,
-    pub subtrees: Vec<Tree>,
+/// Identifies a node by its document and the index of every node on the way
+/// to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selection {
+    pub document: usize,
+    pub path: Vec<usize>,
 }
 
-impl Tree {
-    pub fn new() -> Self {
-        return Self { expanded: true, subtrees: Vec::new() };
+impl Selection {
+    /// The same node once the document at `closed` is removed, or None if it
+    /// was in that document.
+    pub fn after_close(mut self, closed: usize) -> Option<Self> {
+        if self.document == closed {
+            return None;
+        }
+        if closed < self.document {
+            self.document -= 1;
+        }
+        return Some(self);
     }
 }
 
@@ -23,8 +95,38 @@ impl App {
     pub fn new() -> Self {
         return Self {
             signal_stop: false,
-            next_id: 0,
-            tree: Some(Tree::new()),
+            documents: Vec::new(),
+            opened: Vec::new(),
+            generation: 0,
+            errors: Vec::new(),
+            selection: None,
+            navigate: false,
+            focus_document: None,
+            oid_report: oid_names::reload(),
+            oid_browser: OidBrowser::new(),
+            hash_calculator: HashCalculator::new(),
+            path_validator: PathValidator::new(),
+            schema_window: SchemaWindow::new(),
+            notation_window: NotationWindow::new(),
+            name_style: name::Style::default(),
+            reveal_secrets: false,
+            password_prompt: None,
+            open_password_prompt: false,
+            password: String::new(),
+            password_error: None,
+            content_prompt: None,
+            open_content_prompt: false,
+            verification: None,
+            certificates: Vec::new(),
+            signature_check: None,
+            crls: Vec::new(),
+            revocation_check: None,
+            export_prompt: None,
+            open_export_prompt: false,
+            export_path: String::new(),
+            export_error: None,
+            message: None,
+            open_message: false,
         };
     }
 
@@ -32,10 +134,42 @@ impl App {
         return self.signal_stop;
     }
 
-    fn push_next_id<'a>(&mut self, ui: &'a Ui) -> IdStackToken<'a> {
-        let token = ui.push_id_usize(self.next_id);
-        self.next_id += 1;
-        return token;
+    pub fn open_file(&mut self, path: &Path) {
+        if path.extension().is_some_and(|extension| extension == "asn1" || extension == "asn") {
+            self.schema_window.load(path);
+            return;
+        }
+        match Document::open(path) {
+            Ok(document) => self.add_document(document),
+            Err(err) => self.errors.push(format!("Failed to open '{}': {}", path.display(), err)),
+        }
+    }
+
+    pub fn open_sample(&mut self) {
+        let bytes = include_bytes!("../tests/no-password.pfx");
+        self.add_document(Document::from_bytes("no-password.pfx".to_owned(), bytes.to_vec()));
+    }
+
+    fn add_document(&mut self, document: Document) {
+        self.documents.push(document);
+        self.focus_document = Some(self.documents.len() - 1);
+        self.generation += 1;
+    }
+
+    fn close_document(&mut self, idx: usize) {
+        self.documents.remove(idx);
+        self.generation += 1;
+        self.password_prompt = None;
+        self.content_prompt = None;
+        self.export_prompt = None;
+        self.selection = self.selection.take().and_then(|selection| selection.after_close(idx));
+        self.path_validator.close_document(idx);
+    }
+
+    fn navigate_to(&mut self, selection: Selection) {
+        self.focus_document = Some(selection.document);
+        self.selection = Some(selection);
+        self.navigate = true;
     }
 
     pub fn update(&mut self, ui: &Ui) {
@@ -56,272 +190,731 @@ impl App {
 
         window.build(|| {
             if let Some(_) = ui.begin_menu_bar() {
-                // self.draw_menu(ui);
+                self.draw_menu(ui);
             }
 
             self.draw_main_content(ui);
         });
-    }
 
-    pub fn draw_boolean(&mut self, ui: &Ui, content: &[u8]) {
-        match content {
-            [0] => ui.text("false"),
-            [255] => ui.text("false"),
-            _ => ui.text("Invalid boolean"),
+        if let Some(selection) = self.oid_browser.draw(ui, &self.documents, self.generation) {
+            self.navigate_to(selection);
+        }
+        self.hash_calculator.draw(ui, &self.documents, self.selection.as_ref(), self.generation);
+        if let Some(selection) = self.path_validator.draw(ui, &self.documents, self.generation) {
+            self.navigate_to(selection);
         }
+        match self.schema_window.draw(ui, &self.documents, self.selection.as_ref(), self.generation) {
+            Some(schema_window::Request::Apply(selection, type_name)) => self.apply_schema(selection, type_name),
+            Some(schema_window::Request::Show(selection)) => self.navigate_to(selection),
+            None => {}
+        }
+        self.notation_window.draw(ui, &self.documents, self.generation, self.reveal_secrets);
+    }
+
+    /// Name the selected element and its descendants after a type of the
+    /// loaded ASN.1 modules.
+    fn apply_schema(&mut self, selection: Selection, type_name: String) {
+        let document = match self.documents.get_mut(selection.document) {
+            Some(document) => document,
+            None => return,
+        };
+        let bytes = std::mem::take(&mut document.bytes);
+        let mismatches = match document.node_mut(&selection.path) {
+            Some(node) => self.schema_window.schema.apply(&type_name, node, &bytes),
+            None => Vec::new(),
+        };
+        document.bytes = bytes;
+        self.schema_window.report(selection, type_name, mismatches, self.generation);
     }
 
-    fn draw_tree_helper<'a>(ui: &'a Ui, label: &str) -> Option<TreeNodeToken<'a>> {
-        if let Some(token) = ui.tree_node_config(label).opened(true, Condition::Once).push() {
-            if ui.is_item_hovered() && ui.is_mouse_clicked(MouseButton::Right) {
-                ui.open_popup("toto");
+    fn draw_menu(&mut self, ui: &Ui) {
+        if let Some(_) = ui.begin_menu("OIDs") {
+            if ui.menu_item("Reload OID files") {
+                self.oid_report = oid_names::reload();
+                self.oid_browser.invalidate();
             }
 
-            if let Some(_) = ui.begin_popup("toto") {
-                if ui.menu_item_config("Copy Bytes").build() {
-                    // ui.set_clipboard_text(full_path.as_ref());
-                }
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    match config::config_dir() {
+                        Some(dir) => ui.text(format!(
+                            "Looks for '{}' and '{}' in '{}'",
+                            oid_names::DUMPASN1_FILE_NAME,
+                            oid_names::TSV_FILE_NAME,
+                            dir.display(),
+                        )),
+                        None => ui.text("No config directory available"),
+                    }
+                    ui.text(format!("{} user OIDs loaded", self.oid_report.loaded));
+                    for file in self.oid_report.files.iter() {
+                        ui.text(format!("Loaded '{}'", file));
+                    }
+                    for err in self.oid_report.errors.iter() {
+                        ui.text_colored([1.0, 0.0, 0.0, 1.0], err);
+                    }
+                });
+            }
+        }
 
-                if ui.menu_item_config("View bytes").build() {
+        if let Some(_) = ui.begin_menu("View") {
+            ui.menu_item_config("DN in OpenSSL order").build_with_ref(&mut self.name_style.openssl_order);
+            ui.menu_item_config("Split multi-valued RDNs").build_with_ref(&mut self.name_style.split_multi_valued);
+            ui.menu_item_config("Reveal private key components").build_with_ref(&mut self.reveal_secrets);
+        }
+
+        if let Some(_) = ui.begin_menu_with_enabled("Export", !self.documents.is_empty()) {
+            let mut exported = None;
+            for (idx, document) in self.documents.iter().enumerate() {
+                let _id = ui.push_id_usize(idx);
+                if ui.menu_item(format!("'{}' as JSON", document.name)) {
+                    exported = Some(idx);
                 }
             }
+            if let Some(idx) = exported {
+                // Suggest the file of the document with a .json extension.
+                self.export_path = match self.documents[idx].path.as_ref() {
+                    Some(path) => format!("{}.json", path.display()),
+                    None => String::new(),
+                };
+                self.export_prompt = Some(idx);
+                self.open_export_prompt = true;
+                self.export_error = None;
+            }
+        }
 
-            return Some(token);
-        } else {
-            return None;
+        if let Some(_) = ui.begin_menu("Tools") {
+            ui.menu_item_config("OID browser").build_with_ref(&mut self.oid_browser.opened);
+            ui.menu_item_config("Hash calculator").build_with_ref(&mut self.hash_calculator.opened);
+            ui.menu_item_config("Certificate path").build_with_ref(&mut self.path_validator.opened);
+            ui.menu_item_config("ASN.1 schemas").build_with_ref(&mut self.schema_window.opened);
+            ui.menu_item_config("Show as").build_with_ref(&mut self.notation_window.opened);
         }
     }
 
-    pub fn draw_sequence(&mut self, ui: &Ui, tree: &mut Tree, content: &[u8]) -> Result<()> {
-        let label = format!("{:?} - len: {}", Tag::Sequence, content.len());
-        if let Some(_) = Self::draw_tree_helper(ui, &label) {
-            self.draw_tree(ui, tree, content)?;
-        }
-        return Ok(());
+    /// Save the tree of the document as JSON to `path`, refusing to replace an
+    /// existing file.
+    fn export_json(&self, idx: usize, path: &Path) -> std::io::Result<()> {
+        let document = &self.documents[idx];
+        let mut file = std::fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+        return file.write_all(json::document(document, self.reveal_secrets).pretty().as_bytes());
     }
 
-    pub fn draw_set(&mut self, ui: &Ui, tree: &mut Tree, content: &[u8]) -> Result<()> {
-        let label = format!("{:?} - len: {}", Tag::Set, content.len());
-        if let Some(_) = Self::draw_tree_helper(ui, &label) {
-            self.draw_tree(ui, tree, content)?;
+    fn draw_node_menu(&mut self, ui: &Ui, document: usize, node: &Node, bytes: &[u8], path: &[usize]) {
+        if ui.is_item_hovered() && ui.is_mouse_clicked(MouseButton::Right) {
+            ui.open_popup("node-menu");
         }
-        return Ok(());
-    }
 
-    pub fn draw_oid(&mut self, ui: &Ui, content: &[u8]) {
-        if let Ok(oid) = oid::stringify(&content) {
-            if let Some(oid_name) = oid_names::find(&oid) {
-                ui.text(&format!("{:?} - {} {}", Tag::OID, oid, oid_name));
-            } else {
-                ui.text(&format!("{:?} - {}", Tag::OID, oid));
+        if let Some(_) = ui.begin_popup("node-menu") {
+            // The bytes of private key components are only copied once revealed.
+            let copyable = self.reveal_secrets || !node.contains_secret();
+            if ui.menu_item_config("Copy Bytes").enabled(copyable).build() {
+                ui.set_clipboard_text(hex::hexlify(node.raw(bytes)));
+            }
+
+            if ui.menu_item_config("Copy Content Bytes").enabled(copyable).build() {
+                ui.set_clipboard_text(hex::hexlify(node.content(bytes)));
+            }
+
+            if ui.menu_item_config("Copy as JSON").build() {
+                ui.set_clipboard_text(json::node(node, bytes, self.reveal_secrets).pretty());
+            }
+
+            if let Some(_) = ui.begin_menu("Show as") {
+                for notation in Notation::ALL {
+                    if ui.menu_item_config(notation.name()).build() {
+                        let selection = Selection { document, path: path.to_vec() };
+                        self.notation_window.show(selection, notation, self.generation);
+                    }
+                }
+            }
+
+            if ui.menu_item_config("Hash...").build() {
+                self.selection = Some(Selection { document, path: path.to_vec() });
+                self.hash_calculator.opened = true;
             }
 
-            if let Some(_) = ui.begin_popup("copy-oid") {
-                if ui.menu_item_config("Copy OID as dot notation").build() {
-                    ui.set_clipboard_text(oid);
+            if signature::is_signed(node) {
+                ui.separator();
+                if x509::Fields::new(node).is_ok() && ui.menu_item_config("Build chain").build() {
+                    self.path_validator.show(Selection { document, path: path.to_vec() });
                 }
-                if ui.menu_item_config("Copy OID as bytes").build() {
-                    ui.set_clipboard_text(format!("{:02X?}", content));
+                if x509::Fields::new(node).is_ok() && !self.crls.is_empty() {
+                    if let Some(_) = ui.begin_menu("Is this certificate revoked?") {
+                        for (idx, (label, crl)) in self.crls.iter().enumerate() {
+                            let _id = ui.push_id_usize(idx);
+                            if ui.menu_item_config(label).build() {
+                                self.revocation_check = Some((Selection { document, path: path.to_vec() }, crl.clone()));
+                            }
+                        }
+                    }
+                }
+                if let Some(_) = ui.begin_menu("Verify signature with") {
+                    let selection = Selection { document, path: path.to_vec() };
+                    if signature::own_key(node).is_some() && ui.menu_item_config("Self").build() {
+                        self.signature_check = Some((selection.clone(), None));
+                    }
+                    for (idx, (label, certificate)) in self.certificates.iter().enumerate() {
+                        let _id = ui.push_id_usize(idx);
+                        if *certificate != selection && ui.menu_item_config(label).build() {
+                            self.signature_check = Some((selection.clone(), Some(certificate.clone())));
+                        }
+                    }
                 }
             }
-        } else {
-            ui.text(&format!("{:?} - len: {}", Tag::OID, content.len()));
-        }
 
-        if ui.is_item_hovered() && ui.is_mouse_clicked(MouseButton::Right) {
-            ui.open_popup("copy-oid");
+            if let Some(rdns) = Self::distinguished_name(node, bytes) {
+                ui.separator();
+                if ui.menu_item_config("Copy DN").build() {
+                    ui.set_clipboard_text(name::to_string(&rdns, self.name_style));
+                }
+            }
+
+            if node.tag == Tag::OID {
+                if let Ok(oid) = oid::stringify(node.content(bytes)) {
+                    ui.separator();
+                    if ui.menu_item_config("Copy OID as dot notation").build() {
+                        ui.set_clipboard_text(&oid);
+                    }
+                    if ui.menu_item_config("Copy OID as bytes").build() {
+                        ui.set_clipboard_text(format!("{:02X?}", node.content(bytes)));
+                    }
+                    if ui.menu_item_config("Open in OID browser").build() {
+                        self.oid_browser.show(&oid);
+                    }
+                }
+            }
         }
     }
 
-    pub fn draw_octet_string(&mut self, ui: &Ui, tree: &mut Tree, content: &[u8]) -> Result<()> {
-        let label = format!("{:?} - {}", Tag::OctetString, hex::hexlify(content));
-        if let Some(_) = Self::draw_tree_helper(ui, &label) {
-            self.draw_tree(ui, tree, content)?;
+    /// Decode the node as a Name, if it looks like a non-empty one.
+    fn distinguished_name(node: &Node, bytes: &[u8]) -> Option<Vec<name::Rdn>> {
+        if node.tag != Tag::SEQUENCE || node.children.is_empty() {
+            return None;
         }
-        return Ok(());
+        return name::parse(node, bytes).ok();
     }
 
-    pub fn draw_context_specific_constructed(&mut self, ui: &Ui, tree: &mut Tree, tag: Tag, content: &[u8]) -> Result<()> {
-        let label = format!("{:?} - len: {}", tag, content.len());
-        if let Some(_) = Self::draw_tree_helper(ui, &label) {
-            self.draw_tree(ui, tree, content)?;
+    fn node_label(node: &Node, bytes: &[u8], name_style: name::Style, reveal_secrets: bool) -> String {
+        let mut label = match (node.field.as_ref(), node.type_name.as_ref()) {
+            (Some(field), Some(type_name)) => format!("{} ({}): ", field, type_name),
+            (Some(field), None) => format!("{}: ", field),
+            (None, Some(type_name)) => format!("{}: ", type_name),
+            (None, None) => String::new(),
+        };
+
+        if node.tag.constructed {
+            label.push_str(&format!("{:?} - len: {}", node.tag, node.len));
+            if let Some(rdns) = Self::distinguished_name(node, bytes) {
+                label.push_str(&format!(" - {}", name::to_string(&rdns, name_style)));
+            }
+        } else if node.secret && !reveal_secrets {
+            label.push_str(&format!("{:?} - len: {}, hidden", node.tag, node.len));
+        } else {
+            match value::describe(node.tag, node.content(bytes)) {
+                Ok(value) if value.is_empty() => label.push_str(&format!("{:?}", node.tag)),
+                Ok(value) => label.push_str(&format!("{:?} - {}", node.tag, value)),
+                Err(err) => label.push_str(&format!("{:?} - len: {}, err: {}", node.tag, node.len, err)),
+            }
+        }
+
+        if let Some(note) = node.note.as_ref() {
+            label.push_str(&format!(" ({})", note));
         }
-        return Ok(());
+
+        if node.decrypted {
+            label.push_str(" [decrypted]");
+        }
+
+        return label;
     }
 
-    pub fn draw_printable_string(&mut self, ui: &Ui, content: &[u8]) -> Result<()> {
-        let value = std::str::from_utf8(content).map_err(|_| Error("Invalid UTF8 string"))?;
-        ui.text(format!("{:?} - '{}'", Tag::Utf8String, value));
-        return Ok(());
+    fn draw_node(&mut self, ui: &Ui, document: usize, node: &Node, bytes: &[u8], path: &mut Vec<usize>) {
+        let label = Self::node_label(node, bytes, self.name_style, self.reveal_secrets);
+
+        let (selected, on_path) = match self.selection.as_ref() {
+            Some(selection) if selection.document == document => (
+                selection.path == *path,
+                path.len() < selection.path.len() && selection.path.starts_with(path),
+            ),
+            _ => (false, false),
+        };
+
+        let mut tree_node = ui
+            .tree_node_config(&label)
+            .opened(true, Condition::Once)
+            .leaf(node.children.is_empty() && node.error.is_none())
+            .open_on_arrow(true)
+            .selected(selected);
+        if self.navigate && on_path {
+            tree_node = tree_node.opened(true, Condition::Always);
+        }
+        let token = tree_node.push();
+
+        if ui.is_item_clicked() && !ui.is_item_toggled_open() {
+            self.selection = Some(Selection { document, path: path.clone() });
+        }
+
+        if selected && self.navigate {
+            ui.set_scroll_here_y();
+            self.navigate = false;
+        }
+
+        if node.tag == Tag::OID && ui.is_item_hovered() {
+            if let Some(entry) = oid::stringify(node.content(bytes)).ok().and_then(|oid| oid_names::find(&oid)) {
+                ui.tooltip_text(format!("{} ({})", entry.description, entry.reference));
+            }
+        }
+
+        self.draw_node_menu(ui, document, node, bytes, path);
+
+        if let Some(failures) = self.path_validator.failures(&Selection { document, path: path.clone() }) {
+            if !failures.is_empty() {
+                ui.same_line();
+                ui.text_colored([1.0, 0.0, 0.0, 1.0], format!("path validation: {}", failures.join("; ")));
+            }
+        }
+
+        if let Some(_) = token {
+            self.draw_nodes(ui, document, &node.children, bytes, path);
+            if let Some((offset, err)) = node.error.as_ref() {
+                let remaining_bytes = node.content_offset() + node.len - offset;
+                ui.text_colored([1.0, 0.0, 0.0, 1.0], format!("{} bytes remaining, err: {}", remaining_bytes, err));
+            }
+        }
     }
 
-    pub fn draw_bmp_string(&mut self, ui: &Ui, content: &[u8]) -> Result<()> {
-        let codepoints = content
-            .chunks_exact(2)
-            .map(|bytes| ((bytes[0] as u16) << 8) | bytes[1] as u16)
-            .collect::<Vec<u16>>();
-        let value = String::from_utf16(codepoints.as_slice()).unwrap();
-        ui.text(format!("{:?} - '{}'", Tag::BMPString, value));
-        return Ok(());
+    fn draw_nodes(&mut self, ui: &Ui, document: usize, nodes: &[Node], bytes: &[u8], path: &mut Vec<usize>) {
+        for (idx, node) in nodes.iter().enumerate() {
+            let _id = ui.push_id_usize(idx);
+            path.push(idx);
+            self.draw_node(ui, document, node, bytes, path);
+            path.pop();
+        }
     }
 
-    pub fn draw_utf8_string(&mut self, ui: &Ui, content: &[u8]) -> Result<()> {
-        let mut builder = String::with_capacity(content.len());
-        for &byte in content {
-            match byte {
-                b'A'..=b'Z' => builder.push(byte as char),
-                b'a'..=b'z' => builder.push(byte as char),
-                b'0'..=b'9' => builder.push(byte as char),
-                b' ' | b'\'' | b'(' | b')' | b'+' | b',' | b'-' | b'.' | b'/' | b':' | b'=' | b'?' => builder.push(byte as char),
-                _ => return Err(Error("Invalid PrintableString")),
+    fn draw_document(&mut self, ui: &Ui, idx: usize, document: &mut Document) {
+        ui.child_window("document").build(|| {
+            for (structure_idx, structure) in document.structures.iter_mut().enumerate() {
+                match cards::draw(ui, structure_idx, structure, self.reveal_secrets) {
+                    Some(Action::ShowInTree) => {
+                        self.navigate_to(Selection { document: idx, path: structure.path.clone() });
+                    }
+                    Some(Action::Decrypt) => {
+                        self.password_prompt = Some((idx, structure_idx));
+                        self.open_password_prompt = true;
+                        self.password.clear();
+                        self.password_error = None;
+                    }
+                    Some(Action::ToggleSecrets) => self.reveal_secrets = !self.reveal_secrets,
+                    Some(Action::Verify) => match &structure.kind {
+                        Kind::Cms(Cms { detached: true, .. }) | Kind::TimeStampReq(_) | Kind::TstInfo(_) => {
+                            self.content_prompt = Some((idx, structure_idx));
+                            self.open_content_prompt = true;
+                        }
+                        _ => self.verification = Some((idx, structure_idx, None)),
+                    },
+                    Some(Action::OpenCertificate(certificate_idx)) => {
+                        let certificates = match &structure.kind {
+                            Kind::Cms(cms) => cms.certificates.as_slice(),
+                            Kind::OcspResponse(response) => response.certificates.as_slice(),
+                            _ => &[],
+                        };
+                        if let Some(certificate) = certificates.get(certificate_idx) {
+                            let name = format!("{} [certificate {}]", document.name, certificate_idx + 1);
+                            self.opened.push(Document::from_bytes(name, certificate.der.clone()));
+                        }
+                    }
+                    None => {}
+                }
             }
+
+            self.draw_nodes(ui, idx, &document.nodes, &document.bytes, &mut Vec::new());
+            if let Some((offset, err)) = document.error.as_ref() {
+                let remaining_bytes = document.bytes.len() - offset;
+                ui.text_colored([1.0, 0.0, 0.0, 1.0], format!("{} bytes remaining, err: {}", remaining_bytes, err));
+            }
+        });
+    }
+
+    /// Let the user name the document after another shape from the context
+    /// menu of its tab, the last item drawn.
+    fn draw_shape_menu(&self, ui: &Ui, document: &mut Document) {
+        if document.nodes.is_empty() {
+            return;
+        }
+        if ui.is_item_hovered() && ui.is_mouse_clicked(MouseButton::Right) {
+            ui.open_popup("shape-menu");
         }
 
-        ui.text(format!("{:?} - '{}'", Tag::PrintableString, builder));
-        return Ok(());
+        let detection = &document.detection;
+        let mut chosen = None;
+        if let Some(_) = ui.begin_popup("shape-menu") {
+            if 0 < detection.mismatches {
+                ui.text_colored([1.0, 0.5, 0.0, 1.0], format!("{} element(s) don't match", detection.mismatches));
+                ui.separator();
+            }
+            if ui.menu_item_config("Not detected").selected(detection.shape.is_none()).build() {
+                chosen = Some(None);
+            }
+            for (shape, score) in detection.scores.iter() {
+                let label = format!("{} (score {})", detect::SHAPES[*shape].name, score);
+                if ui.menu_item_config(label).selected(detection.shape == Some(*shape)).build() {
+                    chosen = Some(Some(*shape));
+                }
+            }
+            // The shapes whose tags don't fit weren't scored.
+            let unscored: Vec<usize> = (0..detect::SHAPES.len())
+                .filter(|shape| !detection.scores.iter().any(|(scored, _)| scored == shape))
+                .collect();
+            if let Some(_) = ui.begin_menu_with_enabled("Other types", !unscored.is_empty()) {
+                for shape in unscored {
+                    let selected = detection.shape == Some(shape);
+                    if ui.menu_item_config(detect::SHAPES[shape].name).selected(selected).build() {
+                        chosen = Some(Some(shape));
+                    }
+                }
+            }
+        }
+        if let Some(shape) = chosen {
+            document.set_shape(shape);
+        }
     }
 
-    pub fn draw_time(&mut self, ui: &Ui, tag: Tag, content: &[u8]) -> Result<()> {
-        use chrono::{offset::LocalResult, TimeZone, Utc};
+    pub fn draw_main_content(&mut self, ui: &Ui) {
+        if !self.errors.is_empty() {
+            for err in self.errors.iter() {
+                ui.text_colored([1.0, 0.0, 0.0, 1.0], err);
+            }
+            if ui.small_button("Dismiss") {
+                self.errors.clear();
+            }
+        }
 
-        fn read_digit(inner: &mut der::Reader) -> Result<u32> {
-            let byte = inner.read_byte()?;
-            return match byte {
-                b'0'..=b'9' => Ok(u32::from(byte - b'0')),
-                _ => Err(Error("Invalid digit found")),
-            };
+        if self.documents.is_empty() {
+            ui.text("Drop a DER file on the window to open it.");
+            return;
         }
 
-        fn read_two_digits(inner: &mut der::Reader, min: u32, max: u32) -> Result<u32> {
-            let hi = read_digit(inner)?;
-            let lo = read_digit(inner)?;
-            let value = (hi * 10) + lo;
-            if value < min || value > max {
-                return Err(Error("Digit outside expected range"));
+        self.certificates.clear();
+        self.crls.clear();
+        for (idx, document) in self.documents.iter().enumerate() {
+            for structure in document.structures.iter() {
+                let selection = Selection { document: idx, path: structure.path.clone() };
+                match &structure.kind {
+                    Kind::Certificate(certificate) => {
+                        self.certificates.push((format!("{} ({})", certificate.subject, document.name), selection));
+                    }
+                    Kind::Crl(crl) => self.crls.push((format!("{} ({})", crl.issuer, document.name), selection)),
+                    _ => {}
+                }
             }
-            return Ok(value);
         }
 
-        fn get_days_in_month(year: i32, month: u32) -> Result<u32> {
-            let next_month = if month == 12 {
-                chrono::NaiveDate::from_ymd_opt(year + 1, 1, 1)
-            } else {
-                chrono::NaiveDate::from_ymd_opt(year, month + 1, 1)
-            }.ok_or(Error("Can't initialize a 'chrono::NativeDate'"))?;
+        let mut documents = std::mem::take(&mut self.documents);
+        let mut closed = None;
+        if let Some(_) = ui.tab_bar("documents") {
+            for (idx, document) in documents.iter_mut().enumerate() {
+                let flags = if self.focus_document == Some(idx) {
+                    TabItemFlags::SET_SELECTED
+                } else {
+                    TabItemFlags::empty()
+                };
+
+                let mut opened = true;
+                // The ID ignores the shape, which can change from the context
+                // menu of the tab.
+                let label = match (document.detection.name(), document.detection.mismatches) {
+                    (Some(shape), 0) => format!("{} ({})###{}", document.name, shape, idx),
+                    (Some(shape), _) => format!("{} ({}, with mismatches)###{}", document.name, shape, idx),
+                    (None, _) => format!("{}###{}", document.name, idx),
+                };
+                let tab = ui.tab_item_with_flags(label, Some(&mut opened), flags);
+                {
+                    let _id = ui.push_id_usize(idx);
+                    self.draw_shape_menu(ui, document);
+                }
+                if let Some(_) = tab {
+                    self.draw_document(ui, idx, document);
+                }
 
-            let duration_since = chrono::NaiveDate::from_ymd_opt(year, month, 1)
-                .ok_or(Error("Invalid year or month"))?;
-            let number_of_days = next_month
-                .signed_duration_since(duration_since)
-                .num_days();
-            assert!(number_of_days <= (u32::MAX as i64));
-            return Ok(number_of_days as u32);
+                if !opened {
+                    closed = Some(idx);
+                }
+            }
+        }
+        self.focus_document = None;
+        self.documents = documents;
+        for document in std::mem::take(&mut self.opened) {
+            self.add_document(document);
+        }
+        if let Some((document, structure, content)) = self.verification.take() {
+            self.verify(document, structure, content);
+        }
+        if let Some((subject, issuer)) = self.signature_check.take() {
+            self.check_signature(&subject, issuer.as_ref());
+        }
+        if let Some((certificate, crl)) = self.revocation_check.take() {
+            self.check_revocation(&certificate, &crl);
         }
 
-        let mut reader = der::Reader::new(content);
+        if let Some(idx) = closed {
+            self.close_document(idx);
+        }
 
-        let (year_hi, year_lo) = if tag == Tag::UTCTime {
-            let lo = read_two_digits(&mut reader, 0, 99)?;
-            let hi = if lo >= 50 { 19 } else { 20 };
-            (hi, lo)
-        } else {
-            let hi = read_two_digits(&mut reader, 0, 99)?;
-            let lo = read_two_digits(&mut reader, 0, 99)?;
-            (hi, lo)
+        self.draw_password_prompt(ui);
+        self.draw_export_prompt(ui);
+        self.draw_content_prompt(ui);
+        self.draw_message(ui);
+    }
+
+    fn draw_password_prompt(&mut self, ui: &Ui) {
+        if self.open_password_prompt {
+            ui.open_popup("Password");
+            self.open_password_prompt = false;
+        }
+
+        let (document, structure) = match self.password_prompt {
+            Some(prompt) => prompt,
+            None => return,
+        };
+
+        if let Some(_) = ui.modal_popup_config("Password").always_auto_resize(true).begin_popup() {
+            let name = self.documents.get(document).map(|document| document.name.as_str()).unwrap_or_default();
+            ui.text(format!("Password for '{}'", name));
+            if ui.is_window_appearing() {
+                ui.set_keyboard_focus_here();
+            }
+            let mut submit = ui
+                .input_text("##password", &mut self.password)
+                .password(true)
+                .enter_returns_true(true)
+                .build();
+            if let Some(err) = self.password_error.as_ref() {
+                ui.text_colored([1.0, 0.0, 0.0, 1.0], err);
+            }
+
+            submit |= ui.button("Decrypt");
+            ui.same_line();
+            if ui.button("Cancel") {
+                self.password_prompt = None;
+                ui.close_current_popup();
+            }
+
+            if submit {
+                let result = match self.documents.get_mut(document) {
+                    Some(document) => document.decrypt(structure, &self.password),
+                    None => Ok(()),
+                };
+                self.generation += 1;
+                match result {
+                    Ok(()) => {
+                        self.password.clear();
+                        self.password_prompt = None;
+                        ui.close_current_popup();
+                    }
+                    Err(err) => self.password_error = Some(err.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Let the user choose the file the JSON export is saved to.
+    fn draw_export_prompt(&mut self, ui: &Ui) {
+        if self.open_export_prompt {
+            ui.open_popup("JSON export");
+            self.open_export_prompt = false;
+        }
+
+        let document = match self.export_prompt {
+            Some(document) => document,
+            None => return,
         };
 
-        let year = {
-            let y = (year_hi * 100) + year_lo;
-            assert!(y <= (i32::MAX as u32));
-            y as i32
+        if let Some(_) = ui.modal_popup_config("JSON export").always_auto_resize(true).begin_popup() {
+            let name = self.documents.get(document).map(|document| document.name.as_str()).unwrap_or_default();
+            ui.text(format!("Save '{}' as JSON to", name));
+            if ui.is_window_appearing() {
+                ui.set_keyboard_focus_here();
+            }
+            let mut submit = ui
+                .input_text("##export-path", &mut self.export_path)
+                .hint("path of the JSON file")
+                .enter_returns_true(true)
+                .build();
+            if let Some(err) = self.export_error.as_ref() {
+                ui.text_colored([1.0, 0.0, 0.0, 1.0], err);
+            }
+
+            submit |= ui.button("Save");
+            ui.same_line();
+            if ui.button("Cancel") {
+                self.export_prompt = None;
+                ui.close_current_popup();
+            }
+
+            if submit && !self.export_path.is_empty() && document < self.documents.len() {
+                let path = Path::new(&self.export_path);
+                match self.export_json(document, path) {
+                    Ok(()) => {
+                        self.message = Some(("JSON export", format!("Saved '{}'", path.display())));
+                        self.open_message = true;
+                        self.export_prompt = None;
+                        ui.close_current_popup();
+                    }
+                    Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                        self.export_error = Some(format!("'{}' already exists", path.display()));
+                    }
+                    Err(err) => self.export_error = Some(format!("Failed to save '{}': {}", path.display(), err)),
+                }
+            }
+        }
+    }
+
+    fn verify(&mut self, document: usize, structure: usize, content: Option<usize>) {
+        let detached = content.and_then(|content| self.documents.get(content)).map(|content| content.file().to_vec());
+        let certificates: Vec<Vec<u8>> = self
+            .certificates
+            .iter()
+            .filter_map(|(_, selection)| {
+                let document = self.documents.get(selection.document)?;
+                return Some(document.node(&selection.path)?.raw(&document.bytes).to_vec());
+            })
+            .collect();
+        let result = match self.documents.get_mut(document) {
+            Some(document) => document.verify(structure, detached.as_deref(), &certificates),
+            None => Ok(()),
         };
+        if let Err(err) = result {
+            self.errors.push(format!("Failed to verify: {}", err));
+        }
+    }
 
-        let month = read_two_digits(&mut reader, 1, 12)?;
-        let days_in_month = get_days_in_month(year, month)?;
-        let day_of_month = read_two_digits(&mut reader, 1, days_in_month)?;
-        let hours = read_two_digits(&mut reader, 0, 23)?;
-        let minutes = read_two_digits(&mut reader, 0, 59)?;
-        let seconds = read_two_digits(&mut reader, 0, 59)?;
-
-        if reader.read_byte()? != b'Z' {
-            return Err(Error("Invalid or unsupported timezone"));
-        }
-
-        let time_as_utc = match Utc.with_ymd_and_hms(year, month, day_of_month, hours, minutes, seconds) {
-            LocalResult::None => Err(Error("Can't initialize a Utc time")),
-            LocalResult::Single(dt) => Ok(dt),
-            LocalResult::Ambiguous(_dt1, _dt2) => {
-                /*
-                log::error!(
-                    "Ambiguous time created with year={}, month={}, day_of_month={}, hours={}, minutes={}, seconds={}, result in dt1={}, dt2={}",
-                    year,
-                    month,
-                    day_of_month,
-                    hours,
-                    minutes,
-                    seconds,
-                    dt1,
-                    dt2,
-                );
-                */
-                return Err(Error("Ambiguous time"));
-            }
-        }?;
-
-        ui.text(format!("{:?} - {}", tag, time_as_utc));
-        return Ok(());
+    /// Verify the signature of a Certificate, CRL or CSR with the key of
+    /// `issuer`, or with its own key.
+    fn check_signature(&mut self, subject: &Selection, issuer: Option<&Selection>) {
+        let signer_name = match issuer {
+            Some(issuer) => self
+                .certificates
+                .iter()
+                .find(|(_, certificate)| certificate == issuer)
+                .map(|(label, _)| label.clone())
+                .unwrap_or_default(),
+            None => "its own key".to_owned(),
+        };
+        let message = match self.signed_by(subject, issuer.unwrap_or(subject)) {
+            Ok(()) => format!("The signature is valid, verified with {}.", signer_name),
+            Err(err) => format!("The signature doesn't verify with {}: {}", signer_name, err),
+        };
+        self.message = Some(("Signature", message));
+        self.open_message = true;
     }
 
-    pub fn draw_integer(&mut self, ui: &Ui, content: &[u8]) {
-        ui.text(format!("{:?} - {}", Tag::Integer, content.len()));
+    fn signed_by(&self, subject: &Selection, signer: &Selection) -> Result<()> {
+        let document = self.documents.get(subject.document).ok_or(Error("Missing document"))?;
+        let node = document.node(&subject.path).ok_or(Error("Missing element"))?;
+        let signer_document = self.documents.get(signer.document).ok_or(Error("Missing document"))?;
+        let spki = signer_document
+            .node(&signer.path)
+            .and_then(signature::own_key)
+            .ok_or(Error("The signer doesn't hold a public key"))?;
+        let key = PublicKey::parse(spki, &signer_document.bytes)?;
+        return signature::verify_signed(node, &document.bytes, &key);
     }
 
-    pub fn draw_tree(&mut self, ui: &Ui, tree: &mut Tree, content: &[u8]) -> Result<()> {
-        let mut idx = 0;
-        let mut reader = der::Reader::new(content);
-
-        while !reader.at_end() {
-            if tree.subtrees.len() <= idx {
-                tree.subtrees.push(Tree::new());
-            }
-
-            let tree = &mut tree.subtrees[idx];
-
-            let remaining_bytes = reader.len();
-            match der::read_tag_and_get_value(&mut reader) {
-                Ok((Tag::Boolean, sub_content)) => self.draw_boolean(ui, sub_content),
-                Ok((Tag::Integer, sub_content)) => self.draw_integer(ui, sub_content),
-                Ok((Tag::OctetString, sub_content)) => self.draw_octet_string(ui, tree, sub_content)?,
-                Ok((Tag::OID, sub_content)) => self.draw_oid(ui, sub_content),
-                Ok((Tag::Utf8String, sub_content)) => self.draw_utf8_string(ui, sub_content)?,
-                Ok((Tag::Sequence, sub_content)) => self.draw_sequence(ui, tree, sub_content)?,
-                Ok((Tag::Set, sub_content)) => self.draw_set(ui, tree, sub_content)?,
-                Ok((Tag::PrintableString, sub_content)) => self.draw_printable_string(ui, sub_content)?,
-                Ok((Tag::BMPString, sub_content)) => self.draw_bmp_string(ui, sub_content)?,
-                Ok((Tag::UTCTime, sub_content)) => self.draw_time(ui, Tag::UTCTime, sub_content)?,
-                Ok((Tag::GeneralizedTime, sub_content)) => self.draw_time(ui, Tag::GeneralizedTime, sub_content)?,
-                Ok((tag, sub_content)) if tag == Tag::ContextSpecificConstructed0 => self.draw_context_specific_constructed(ui, tree, tag, sub_content)?,
-                Ok((tag, sub_content)) if tag == Tag::ContextSpecificConstructed1 => self.draw_context_specific_constructed(ui, tree, tag, sub_content)?,
-                Ok((tag, sub_content)) if tag == Tag::ContextSpecificConstructed2 => self.draw_context_specific_constructed(ui, tree, tag, sub_content)?,
-                Ok((tag, sub_content)) if tag == Tag::ContextSpecificConstructed3 => self.draw_context_specific_constructed(ui, tree, tag, sub_content)?,
-                Ok((tag, sub_content)) => ui.text(&format!("{:?} - len: {}", tag, sub_content.len())),
-                Err(err) => {
-                    ui.text(&format!("{} bytes remaining, err: {}", remaining_bytes, err));
-                    break;
-                },
-            }
-
-            idx += 1;
-        }
-
-        return Ok(());
+    /// Look the certificate up in the CRL.
+    fn check_revocation(&mut self, certificate: &Selection, crl: &Selection) {
+        let message = match self.revocation(certificate, crl) {
+            Ok(message) => message,
+            Err(err) => err.to_string(),
+        };
+        self.message = Some(("Revocation", message));
+        self.open_message = true;
     }
 
-    pub fn draw_main_content(&mut self, ui: &Ui) {
-        let bytes = include_bytes!("../tests/no-password.pfx");
+    fn revocation(&self, certificate: &Selection, crl: &Selection) -> Result<String> {
+        let document = self.documents.get(certificate.document).ok_or(Error("Missing document"))?;
+        let node = document.node(&certificate.path).ok_or(Error("Missing element"))?;
+        let fields = x509::Fields::new(node)?;
+        let crl = self
+            .documents
+            .get(crl.document)
+            .and_then(|document| document.structures.iter().find(|structure| structure.path == crl.path))
+            .ok_or(Error("Missing CRL"))?;
+        let crl = match &crl.kind {
+            Kind::Crl(crl) => crl,
+            _ => return Err(Error("Missing CRL")),
+        };
 
-        let mut tree = self.tree.take().unwrap();
-        self.draw_tree(ui, &mut tree, bytes.as_ref()).unwrap();
-        self.tree = Some(tree);
+        let updated = format!("CRL issued at {}", crl.this_update);
+        return Ok(match crl.find(fields.issuer, fields.serial, &document.bytes)? {
+            Some(entry) => match entry.reason {
+                Some(reason) => format!("Revoked on {} ({}), according to the {}.", entry.date, reason, updated),
+                None => format!("Revoked on {}, according to the {}.", entry.date, updated),
+            },
+            None => format!("Not revoked, according to the {}.", updated),
+        });
+    }
+
+    fn draw_message(&mut self, ui: &Ui) {
+        let (title, message) = match self.message.as_ref() {
+            Some(message) => message,
+            None => return,
+        };
+        if self.open_message {
+            ui.open_popup(title);
+            self.open_message = false;
+        }
+
+        if let Some(_) = ui.modal_popup_config(title).always_auto_resize(true).begin_popup() {
+            ui.text(message);
+            if ui.button("Close") {
+                self.message = None;
+                ui.close_current_popup();
+            }
+        }
+    }
+
+    /// Let the user choose the document holding the content of a detached
+    /// signature.
+    fn draw_content_prompt(&mut self, ui: &Ui) {
+        if self.open_content_prompt {
+            ui.open_popup("Detached content");
+            self.open_content_prompt = false;
+        }
+
+        let (document, structure) = match self.content_prompt {
+            Some(prompt) => prompt,
+            None => return,
+        };
+
+        let signed = self
+            .documents
+            .get(document)
+            .and_then(|document| document.structures.get(structure))
+            .is_some_and(|structure| matches!(structure.kind, Kind::Cms(_)));
+        if let Some(_) = ui.modal_popup_config("Detached content").always_auto_resize(true).begin_popup() {
+            if signed {
+                ui.text("The signed content isn't part of the structure.");
+            } else {
+                ui.text("The timestamp only holds the hash of the time-stamped data.");
+            }
+            ui.text("Open the content file, then choose it:");
+            for (idx, content) in self.documents.iter().enumerate() {
+                if idx == document {
+                    continue;
+                }
+                let _id = ui.push_id_usize(idx);
+                if ui.selectable(&content.name) {
+                    self.verification = Some((document, structure, Some(idx)));
+                    self.content_prompt = None;
+                    ui.close_current_popup();
+                }
+            }
+
+            if ui.button("Cancel") {
+                self.content_prompt = None;
+                ui.close_current_popup();
+            }
+        }
     }
 }
@@ -0,0 +1,507 @@
+use imgui::*;
+use chrono::Utc;
+use crate::cms::{Cms, EmbeddedCertificate};
+use crate::crl::Crl;
+use crate::csr::Csr;
+use crate::document::{Kind, Structure};
+use crate::hex;
+use crate::key::Key;
+use crate::ocsp::{CertId, OcspRequest, OcspResponse};
+use crate::pkcs12::Pfx;
+use crate::spki::Spki;
+use crate::tsp::{MessageImprint, TimeStampReq, TimeStampResp, TstInfo};
+use crate::x509::Certificate;
+
+/// Draw one row of a card, with the value copied to the clipboard when clicked.
+fn row(ui: &Ui, key: &str, value: &str) {
+    ui.table_next_row();
+    ui.table_next_column();
+    ui.text(key);
+    ui.table_next_column();
+    let _id = ui.push_id(key);
+    if ui.selectable(value) {
+        ui.set_clipboard_text(value);
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Click to copy");
+    }
+}
+
+fn draw_certificate(ui: &Ui, certificate: &Certificate) {
+    let now = Utc::now();
+    let status = if now < certificate.not_before {
+        "not yet valid"
+    } else if certificate.not_after < now {
+        "expired"
+    } else {
+        "valid"
+    };
+
+    row(ui, "Subject", &certificate.subject);
+    row(ui, "Issuer", &certificate.issuer);
+    row(ui, "Serial", &hex::colon_separated(&certificate.serial));
+    row(ui, "Version", &format!("v{}", certificate.version));
+    row(ui, "Not before", &certificate.not_before.to_string());
+    row(ui, "Not after", &format!("{} ({})", certificate.not_after, status));
+    row(ui, "Public key", &certificate.public_key);
+    row(ui, "Signature", &certificate.signature_algorithm);
+    row(ui, "SHA-256 fingerprint", &hex::colon_separated(&certificate.sha256));
+    row(ui, "SHA-1 fingerprint", &hex::colon_separated(&certificate.sha1));
+}
+
+fn draw_crl(ui: &Ui, crl: &Crl) {
+    let status = match crl.next_update {
+        Some(next_update) if next_update < Utc::now() => " (outdated)",
+        _ => "",
+    };
+
+    row(ui, "Issuer", &crl.issuer);
+    row(ui, "Version", &format!("v{}", crl.version));
+    row(ui, "This update", &crl.this_update.to_string());
+    match crl.next_update {
+        Some(next_update) => row(ui, "Next update", &format!("{}{}", next_update, status)),
+        None => row(ui, "Next update", "none"),
+    }
+    if let Some(number) = crl.number.as_ref() {
+        row(ui, "CRL number", number);
+    }
+    row(ui, "Signature", &crl.signature_algorithm);
+    if !crl.extensions.is_empty() {
+        row(ui, "Extensions", &crl.extensions.join("; "));
+    }
+    row(ui, "Revoked", &crl.entries.len().to_string());
+}
+
+fn draw_csr(ui: &Ui, csr: &Csr) {
+    row(ui, "Subject", &csr.subject);
+    row(ui, "Version", &format!("v{}", csr.version));
+    row(ui, "Public key", &csr.public_key);
+    row(ui, "Signature", &csr.signature_algorithm);
+    match csr.signature.as_ref() {
+        Ok(()) => row(ui, "Self-signature", "valid"),
+        Err(err) => row(ui, "Self-signature", &format!("invalid: {}", err)),
+    }
+    if let Some(password) = csr.challenge_password.as_ref() {
+        row(ui, "Challenge password", password);
+    }
+    if !csr.extensions.is_empty() {
+        row(ui, "Requested extensions", &csr.extensions.join("; "));
+    }
+    for (idx, attribute) in csr.attributes.iter().enumerate() {
+        let _id = ui.push_id_usize(idx);
+        row(ui, "Attribute", attribute);
+    }
+}
+
+/// Draw the revoked certificates in a table sorted by the column the user
+/// clicked, and filtered by the search text.
+fn draw_revoked(ui: &Ui, crl: &mut Crl) {
+    if crl.entries.is_empty() {
+        return;
+    }
+
+    if ui.input_text("Search", &mut crl.search).hint("serial number or reason").build() {
+        crl.filter();
+    }
+
+    let columns = [
+        TableColumnSetup::new("Serial number"),
+        TableColumnSetup::new("Revocation date"),
+        TableColumnSetup::new("Reason"),
+    ];
+    let flags = TableFlags::BORDERS | TableFlags::ROW_BG | TableFlags::RESIZABLE | TableFlags::SORTABLE | TableFlags::SCROLL_Y;
+    let height = ui.text_line_height_with_spacing() * (crl.entries.len().min(15) + 2) as f32;
+    if let Some(_) = ui.begin_table_header_with_sizing("revoked", columns, flags, [0.0, height], 0.0) {
+        if let Some(specs) = ui.table_sort_specs_mut() {
+            let mut sorted = false;
+            let (entries, order) = (&crl.entries, &mut crl.order);
+            specs.conditional_sort(|specs| {
+                let spec = match specs.iter().next() {
+                    Some(spec) => spec,
+                    None => return,
+                };
+                order.sort_by(|a, b| {
+                    let (a, b) = (&entries[*a], &entries[*b]);
+                    return match spec.column_idx() {
+                        0 => (a.serial.len(), &a.serial).cmp(&(b.serial.len(), &b.serial)),
+                        1 => a.date.cmp(&b.date),
+                        _ => a.reason.cmp(&b.reason),
+                    };
+                });
+                if spec.sort_direction() == Some(TableSortDirection::Descending) {
+                    order.reverse();
+                }
+                sorted = true;
+            });
+            if sorted {
+                crl.filter();
+            }
+        }
+
+        let clipper = ListClipper::new(crl.shown.len() as i32).begin(ui);
+        for row in clipper.iter() {
+            let entry = &crl.entries[crl.shown[row as usize]];
+            ui.table_next_row();
+            ui.table_next_column();
+            let serial = hex::colon_separated(&entry.serial);
+            let _id = ui.push_id_usize(row as usize);
+            if ui.selectable(&serial) {
+                ui.set_clipboard_text(&serial);
+            }
+            ui.table_next_column();
+            ui.text(entry.date.to_string());
+            ui.table_next_column();
+            ui.text(entry.reason.unwrap_or(""));
+        }
+    }
+}
+
+fn draw_pfx(ui: &Ui, pfx: &Pfx) {
+    for (idx, bag) in pfx.bags.iter().enumerate() {
+        let mut description = bag.friendly_name.clone().map(|name| format!("'{}'", name)).unwrap_or_default();
+        if let Some(local_key_id) = bag.local_key_id.as_ref() {
+            if !description.is_empty() {
+                description.push_str(", ");
+            }
+            description.push_str(&format!("localKeyID {}", hex::colon_separated(local_key_id)));
+        }
+        let _id = ui.push_id_usize(idx);
+        row(ui, &bag.kind, &description);
+    }
+
+    if pfx.encrypted != 0 {
+        row(ui, "Encrypted", &format!("{} contents and keys, {} decrypted", pfx.encrypted, pfx.decrypted));
+    }
+    if pfx.enveloped != 0 {
+        row(ui, "Enveloped", &format!("{} contents, public-key encrypted", pfx.enveloped));
+    }
+
+    match pfx.mac.as_ref() {
+        Some(mac) => {
+            let status = if pfx.mac_verified { "verified" } else { "not verified" };
+            row(ui, "MAC", &format!("{}, {} iterations, {}", mac.algorithm, mac.iterations, status));
+            row(ui, "MAC salt", &hex::colon_separated(&mac.salt));
+        }
+        None => row(ui, "MAC", "none"),
+    }
+}
+
+fn draw_key(ui: &Ui, key: &Key) {
+    row(ui, "Format", key.format.name());
+    if let Some(description) = key.description.as_ref() {
+        row(ui, "Key", description);
+    }
+    if let Some(encryption) = key.encryption.as_ref() {
+        let status = if key.decrypted { "decrypted" } else { "not decrypted" };
+        row(ui, "Encryption", &format!("{}, {}", encryption, status));
+    }
+}
+
+fn draw_public_key(ui: &Ui, spki: &Spki) {
+    for (key, value) in spki.details.iter() {
+        row(ui, key, value);
+    }
+    row(ui, "SHA-256 pin", &spki.sha256_pin);
+    row(ui, "Key ID (method 1)", &hex::colon_separated(&spki.key_id));
+    row(ui, "Key ID (method 2)", &hex::colon_separated(&spki.short_key_id));
+    if let Some(thumbprint) = spki.jwk_thumbprint.as_ref() {
+        row(ui, "JWK thumbprint", thumbprint);
+    }
+}
+
+/// Draw a CMS summary, returning the index of the embedded certificate the
+/// user asked to open.
+fn draw_cms(ui: &Ui, cms: &Cms) -> Option<usize> {
+    row(ui, "Content type", &cms.content_type);
+    if let Some(inner_content_type) = cms.inner_content_type.as_ref() {
+        let detached = if cms.detached { " (detached)" } else { "" };
+        row(ui, "Content", &format!("{}{}", inner_content_type, detached));
+    }
+
+    for (idx, signer) in cms.signers.iter().enumerate() {
+        let _id = ui.push_id_usize(idx);
+        let mut description = format!("{}, {} with {}", signer.identifier, signer.digest_algorithm, signer.signature_algorithm);
+        if let Some(signing_time) = signer.signing_time {
+            description.push_str(&format!(", signed at {}", signing_time));
+        }
+        row(ui, "Signer", &description);
+        match signer.verification.as_ref() {
+            Some(Ok(())) => row(ui, "Signature", "valid"),
+            Some(Err(err)) => row(ui, "Signature", &format!("invalid: {}", err)),
+            None => {}
+        }
+    }
+
+    for (idx, recipient) in cms.recipients.iter().enumerate() {
+        let _id = ui.push_id_usize(idx);
+        row(ui, "Recipient", recipient);
+    }
+
+    if cms.crls != 0 {
+        row(ui, "CRLs", &cms.crls.to_string());
+    }
+
+    return draw_embedded(ui, &cms.certificates);
+}
+
+/// Draw a row per embedded certificate, returning the index of the one the
+/// user asked to open.
+fn draw_embedded(ui: &Ui, certificates: &[EmbeddedCertificate]) -> Option<usize> {
+    let mut open = None;
+    for (idx, certificate) in certificates.iter().enumerate() {
+        let _id = ui.push_id_usize(idx);
+        ui.table_next_row();
+        ui.table_next_column();
+        if ui.small_button("Open") {
+            open = Some(idx);
+        }
+        ui.table_next_column();
+        ui.text(&certificate.subject);
+    }
+    return open;
+}
+
+fn draw_cert_id(ui: &Ui, cert_id: &CertId) {
+    row(ui, "Serial", &hex::colon_separated(&cert_id.serial));
+    row(ui, "Issuer name hash", &format!("{} {}", cert_id.hash_algorithm, hex::colon_separated(&cert_id.issuer_name_hash)));
+    row(ui, "Issuer key hash", &format!("{} {}", cert_id.hash_algorithm, hex::colon_separated(&cert_id.issuer_key_hash)));
+    if let Some(certificate) = cert_id.certificate.as_ref() {
+        row(ui, "Certificate", certificate);
+    }
+}
+
+fn draw_ocsp_request(ui: &Ui, request: &OcspRequest) {
+    if let Some(requestor) = request.requestor.as_ref() {
+        row(ui, "Requestor", requestor);
+    }
+    for (idx, cert_id) in request.requests.iter().enumerate() {
+        let _id = ui.push_id_usize(idx);
+        draw_cert_id(ui, cert_id);
+    }
+    if let Some(nonce) = request.nonce.as_ref() {
+        row(ui, "Nonce", &hex::colon_separated(nonce));
+    }
+    row(ui, "Signed", if request.signed { "yes" } else { "no" });
+}
+
+/// Draw an OCSP response summary, returning the index of the embedded
+/// certificate the user asked to open.
+fn draw_ocsp_response(ui: &Ui, response: &OcspResponse) -> Option<usize> {
+    row(ui, "Status", response.status);
+    if let Some(response_type) = response.response_type.as_ref() {
+        row(ui, "Response type", response_type);
+    }
+    if let Some(responder) = response.responder.as_ref() {
+        row(ui, "Responder", responder);
+    }
+    if let Some(produced_at) = response.produced_at {
+        row(ui, "Produced at", &produced_at.to_string());
+    }
+    for (idx, single) in response.responses.iter().enumerate() {
+        let _id = ui.push_id_usize(idx);
+        draw_cert_id(ui, &single.cert_id);
+        row(ui, "Certificate status", &single.status);
+        row(ui, "This update", &single.this_update.to_string());
+        if let Some(next_update) = single.next_update {
+            row(ui, "Next update", &next_update.to_string());
+        }
+    }
+    if let Some(nonce) = response.nonce.as_ref() {
+        row(ui, "Nonce", &hex::colon_separated(nonce));
+    }
+    if let Some(signature_algorithm) = response.signature_algorithm.as_ref() {
+        row(ui, "Signature", signature_algorithm);
+    }
+    match response.verification.as_ref() {
+        Some(Ok(signer)) => row(ui, "Verification", &format!("valid, signed by {}", signer)),
+        Some(Err(err)) => row(ui, "Verification", &format!("invalid: {}", err)),
+        None => {}
+    }
+    return draw_embedded(ui, &response.certificates);
+}
+
+fn draw_message_imprint(ui: &Ui, message_imprint: &MessageImprint) {
+    let hash = hex::colon_separated(&message_imprint.hashed_message);
+    row(ui, "Message imprint", &format!("{} {}", message_imprint.hash_algorithm, hash));
+    match message_imprint.check.as_ref() {
+        Some(Ok(())) => row(ui, "Check", "matches the chosen file"),
+        Some(Err(err)) => row(ui, "Check", &err.to_string()),
+        None => {}
+    }
+}
+
+fn draw_extension_names(ui: &Ui, extensions: &[String]) {
+    if !extensions.is_empty() {
+        row(ui, "Extensions", &extensions.join("; "));
+    }
+}
+
+fn draw_time_stamp_req(ui: &Ui, request: &TimeStampReq) {
+    row(ui, "Version", &format!("v{}", request.version));
+    draw_message_imprint(ui, &request.message_imprint);
+    row(ui, "Policy", request.policy.as_deref().unwrap_or("any"));
+    if let Some(nonce) = request.nonce.as_ref() {
+        row(ui, "Nonce", nonce);
+    }
+    row(ui, "Certificate requested", if request.cert_req { "yes" } else { "no" });
+    draw_extension_names(ui, &request.extensions);
+}
+
+fn draw_time_stamp_resp(ui: &Ui, response: &TimeStampResp) {
+    row(ui, "Status", response.status);
+    for (idx, text) in response.status_string.iter().enumerate() {
+        let _id = ui.push_id_usize(idx);
+        row(ui, "Status text", text);
+    }
+    if !response.failure_info.is_empty() {
+        row(ui, "Failure", &response.failure_info.join(", "));
+    }
+    row(ui, "Token", if response.token { "present" } else { "absent" });
+}
+
+fn draw_tst_info(ui: &Ui, info: &TstInfo) {
+    row(ui, "Version", &format!("v{}", info.version));
+    row(ui, "Policy", &info.policy);
+    draw_message_imprint(ui, &info.message_imprint);
+    row(ui, "Serial", &hex::colon_separated(&info.serial));
+    row(ui, "Time", &info.gen_time.to_string());
+    if let Some(accuracy) = info.accuracy.as_ref() {
+        row(ui, "Accuracy", accuracy);
+    }
+    row(ui, "Ordering", if info.ordering { "yes" } else { "no" });
+    if let Some(nonce) = info.nonce.as_ref() {
+        row(ui, "Nonce", nonce);
+    }
+    if let Some(tsa) = info.tsa.as_ref() {
+        row(ui, "TSA", tsa);
+    }
+    draw_extension_names(ui, &info.extensions);
+}
+
+/// What the user asked to do with a structure.
+pub enum Action {
+    ShowInTree,
+    Decrypt,
+    Verify,
+    ToggleSecrets,
+    /// Open the embedded certificate at the given index in its own tab.
+    OpenCertificate(usize),
+}
+
+/// Draw the summary of a recognized structure. `reveal_secrets` tells whether
+/// private key components are shown.
+pub fn draw(ui: &Ui, idx: usize, structure: &mut Structure, reveal_secrets: bool) -> Option<Action> {
+    let title = match &structure.kind {
+        Kind::Certificate(certificate) => format!("Certificate: {}", certificate.subject),
+        Kind::Crl(crl) => format!("CRL: {}", crl.issuer),
+        Kind::Csr(csr) => format!("CSR: {}", csr.subject),
+        Kind::Pfx(pfx) => format!("PKCS#12: {} bags", pfx.bags.len()),
+        Kind::Cms(cms) => format!("CMS: {}", cms.content_type),
+        Kind::OcspRequest(request) => format!("OCSP request: {} certificate(s)", request.requests.len()),
+        Kind::OcspResponse(response) => format!("OCSP response: {}", response.status),
+        Kind::TimeStampReq(request) => format!("Timestamp request: {}", request.message_imprint.hash_algorithm),
+        Kind::TimeStampResp(response) => format!("Timestamp response: {}", response.status),
+        Kind::TstInfo(info) => format!("Timestamp: {}", info.gen_time),
+        Kind::Key(key) => match key.description.as_ref() {
+            Some(description) => format!("{}: {}", key.format.name(), description),
+            None => key.format.name().to_owned(),
+        },
+        Kind::PublicKey(spki) => format!("Public key: {}", spki.description),
+    };
+
+    let _id = ui.push_id_usize(idx);
+    if !ui.collapsing_header(&title, TreeNodeFlags::empty()) {
+        return None;
+    }
+
+    if let Some(_) = ui.begin_table_with_flags("card", 2, TableFlags::BORDERS_INNER_V | TableFlags::SIZING_FIXED_FIT) {
+        match &structure.kind {
+            Kind::Certificate(certificate) => draw_certificate(ui, certificate),
+            Kind::Crl(crl) => draw_crl(ui, crl),
+            Kind::Csr(csr) => draw_csr(ui, csr),
+            Kind::Pfx(pfx) => draw_pfx(ui, pfx),
+            Kind::Cms(cms) => {
+                if let Some(idx) = draw_cms(ui, cms) {
+                    return Some(Action::OpenCertificate(idx));
+                }
+            }
+            Kind::OcspRequest(request) => draw_ocsp_request(ui, request),
+            Kind::OcspResponse(response) => {
+                if let Some(idx) = draw_ocsp_response(ui, response) {
+                    return Some(Action::OpenCertificate(idx));
+                }
+            }
+            Kind::TimeStampReq(request) => draw_time_stamp_req(ui, request),
+            Kind::TimeStampResp(response) => draw_time_stamp_resp(ui, response),
+            Kind::TstInfo(info) => draw_tst_info(ui, info),
+            Kind::Key(key) => draw_key(ui, key),
+            Kind::PublicKey(spki) => draw_public_key(ui, spki),
+        }
+    }
+
+    if let Kind::Crl(crl) = &mut structure.kind {
+        draw_revoked(ui, crl);
+    }
+
+    if let Kind::Key(key) = &structure.kind {
+        if key.needs_password() {
+            if ui.small_button("Enter password...") {
+                return Some(Action::Decrypt);
+            }
+            ui.same_line();
+        }
+        if key.format.is_private() {
+            let label = if reveal_secrets { "Hide private components" } else { "Reveal private components" };
+            if ui.small_button(label) {
+                return Some(Action::ToggleSecrets);
+            }
+            ui.same_line();
+        }
+    }
+
+    if let Kind::Pfx(pfx) = &structure.kind {
+        if pfx.needs_password() {
+            if ui.small_button("Enter password...") {
+                return Some(Action::Decrypt);
+            }
+            ui.same_line();
+        }
+    }
+
+    if let Kind::Cms(cms) = &structure.kind {
+        if !cms.signers.is_empty() {
+            let label = if cms.detached { "Verify with content..." } else { "Verify signatures" };
+            if ui.small_button(label) {
+                return Some(Action::Verify);
+            }
+            ui.same_line();
+        }
+    }
+
+    match &structure.kind {
+        Kind::OcspRequest(_) => {
+            if ui.small_button("Match loaded certificates") {
+                return Some(Action::Verify);
+            }
+            ui.same_line();
+        }
+        Kind::OcspResponse(response) if response.signature_algorithm.is_some() => {
+            if ui.small_button("Verify and match loaded certificates") {
+                return Some(Action::Verify);
+            }
+            ui.same_line();
+        }
+        Kind::TimeStampReq(_) | Kind::TstInfo(_) => {
+            if ui.small_button("Check against file...") {
+                return Some(Action::Verify);
+            }
+            ui.same_line();
+        }
+        _ => {}
+    }
+
+    if ui.small_button("Show in tree") {
+        return Some(Action::ShowInTree);
+    }
+    return None;
+}
@@ -0,0 +1,541 @@
+use std::collections::BTreeSet;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use chrono::{DateTime, Utc};
+use crate::der::{Class, Tag};
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::signature::{self, PublicKey};
+use crate::{oid, value, x509};
+
+const ANY_POLICY: &str = "2.5.29.32.0";
+const EMAIL_ADDRESS: &str = "1.2.840.113549.1.9.1";
+const NO_POLICY: &str = "No valid certificate policy, while an explicit policy is required";
+
+/// Extensions the path validation processes, which may be critical.
+const PROCESSED_EXTENSIONS: &[&str] = &[
+    "2.5.29.14", // subjectKeyIdentifier
+    "2.5.29.15", // keyUsage
+    "2.5.29.17", // subjectAltName
+    "2.5.29.19", // basicConstraints
+    "2.5.29.30", // nameConstraints
+    "2.5.29.32", // certificatePolicies
+    "2.5.29.33", // policyMappings
+    "2.5.29.35", // authorityKeyIdentifier
+    "2.5.29.36", // policyConstraints
+    "2.5.29.37", // extKeyUsage
+    "2.5.29.54", // inhibitAnyPolicy
+];
+
+/// A loaded certificate that chains are built from.
+pub struct Candidate<'a> {
+    pub node: &'a Node,
+    pub bytes: &'a [u8],
+}
+
+impl<'a> Candidate<'a> {
+    fn fields(&self) -> Option<x509::Fields<'a>> {
+        return x509::Fields::new(self.node).ok();
+    }
+
+    fn raw(&self) -> &'a [u8] {
+        return self.node.raw(self.bytes);
+    }
+
+    /// Whether the subject and the issuer are the same name.
+    fn is_self_issued(&self) -> bool {
+        return match self.fields() {
+            Some(fields) => fields.subject.content(self.bytes) == fields.issuer.content(self.bytes),
+            None => false,
+        };
+    }
+
+    fn is_signed_by(&self, issuer: &Candidate) -> Result<()> {
+        let spki = issuer.fields().map(|fields| fields.spki);
+        let key = PublicKey::parse(spki.ok_or(Error("Not a certificate"))?, issuer.bytes)?;
+        return signature::verify_signed(self.node, self.bytes, &key);
+    }
+}
+
+/// Build the chain from the certificate at `leaf` to a self-signed root,
+/// matching the issuer and subject names and the key identifiers, and
+/// preferring the issuers whose key verifies the signature.
+///
+/// Returns the indices of the certificates, starting with `leaf`. The chain
+/// stops early when no issuer is found.
+pub fn build(candidates: &[Candidate], leaf: usize) -> Vec<usize> {
+    let mut path = vec![leaf];
+    loop {
+        let current = &candidates[*path.last().unwrap()];
+        let fields = match current.fields() {
+            Some(fields) => fields,
+            None => break,
+        };
+        if current.is_self_issued() && current.is_signed_by(current).is_ok() {
+            break;
+        }
+
+        let authority_key_id = fields.authority_key_identifier(current.bytes);
+        let mut best: Option<(usize, bool)> = None;
+        for (idx, candidate) in candidates.iter().enumerate() {
+            // The same certificate is often loaded from several files.
+            if path.iter().any(|used| candidates[*used].raw() == candidate.raw()) {
+                continue;
+            }
+            let issuer = match candidate.fields() {
+                Some(issuer) => issuer,
+                None => continue,
+            };
+            if issuer.subject.content(candidate.bytes) != fields.issuer.content(current.bytes) {
+                continue;
+            }
+            if let (Some(expected), Some(key_id)) = (authority_key_id, issuer.subject_key_identifier(candidate.bytes)) {
+                if expected != key_id {
+                    continue;
+                }
+            }
+
+            let verifies = current.is_signed_by(candidate).is_ok();
+            if best.is_none_or(|(_, best)| verifies && !best) {
+                best = Some((idx, verifies));
+            }
+        }
+
+        match best {
+            Some((idx, _)) => path.push(idx),
+            None => break,
+        }
+    }
+    return path;
+}
+
+/// A name constrained by nameConstraints.
+#[derive(Clone, Debug, PartialEq)]
+enum GeneralName {
+    Email(String),
+    Dns(String),
+    /// The encoding of every RelativeDistinguishedName.
+    Directory(Vec<Vec<u8>>),
+    /// The address, followed by the mask in constraints.
+    Ip(Vec<u8>),
+}
+
+impl GeneralName {
+    fn parse(node: &Node, bytes: &[u8]) -> Option<Self> {
+        if node.tag.class != Class::ContextSpecific {
+            return None;
+        }
+        let content = node.content(bytes);
+        return match (node.tag.number, node.tag.constructed) {
+            (1, false) => Some(Self::Email(value::ascii_string(content).ok()?.to_ascii_lowercase())),
+            (2, false) => Some(Self::Dns(value::ascii_string(content).ok()?.to_ascii_lowercase())),
+            (4, true) => Some(Self::directory(node.children.first()?, bytes)),
+            (7, false) => Some(Self::Ip(content.to_vec())),
+            _ => None,
+        };
+    }
+
+    fn directory(name: &Node, bytes: &[u8]) -> Self {
+        return Self::Directory(name.children.iter().map(|rdn| rdn.raw(bytes).to_vec()).collect());
+    }
+
+    fn same_type(&self, other: &Self) -> bool {
+        return std::mem::discriminant(self) == std::mem::discriminant(other);
+    }
+
+    /// Whether the name is within the subtree of the constraint.
+    fn within(&self, constraint: &Self) -> bool {
+        return match (self, constraint) {
+            (Self::Dns(name), Self::Dns(base)) => {
+                base.is_empty()
+                    || match base.strip_prefix('.') {
+                        Some(_) => name.ends_with(base.as_str()),
+                        None => name == base || name.ends_with(&format!(".{}", base)),
+                    }
+            }
+            (Self::Email(name), Self::Email(base)) => {
+                let host = name.rsplit('@').next().unwrap_or_default();
+                if base.contains('@') {
+                    name == base
+                } else if base.starts_with('.') {
+                    host.ends_with(base.as_str())
+                } else {
+                    host == base
+                }
+            }
+            (Self::Directory(name), Self::Directory(base)) => name.starts_with(base),
+            (Self::Ip(address), Self::Ip(base)) => {
+                let (network, mask) = base.split_at(base.len() / 2);
+                address.len() == network.len()
+                    && address.iter().zip(network).zip(mask).all(|((address, network), mask)| address & mask == network & mask)
+            }
+            _ => false,
+        };
+    }
+
+    fn describe(&self) -> String {
+        return match self {
+            Self::Email(name) => format!("Email {}", name),
+            Self::Dns(name) => format!("DNS name {}", name),
+            Self::Directory(_) => "The directory name".to_owned(),
+            Self::Ip(address) => match address.len() {
+                4 => format!("IP address {}", Ipv4Addr::from(<[u8; 4]>::try_from(address.as_slice()).unwrap())),
+                16 => format!("IP address {}", Ipv6Addr::from(<[u8; 16]>::try_from(address.as_slice()).unwrap())),
+                _ => "An IP address".to_owned(),
+            },
+        };
+    }
+}
+
+/// The names of a certificate that name constraints apply to.
+fn names(fields: &x509::Fields, bytes: &[u8]) -> Vec<GeneralName> {
+    let mut names = Vec::new();
+    if !fields.subject.children.is_empty() {
+        names.push(GeneralName::directory(fields.subject, bytes));
+    }
+    for rdn in fields.subject.children.iter() {
+        for attribute in rdn.children.iter() {
+            let attribute_type = attribute.child(0, Tag::OID).ok().and_then(|id| oid::stringify(id.content(bytes)).ok());
+            if attribute_type.as_deref() == Some(EMAIL_ADDRESS) {
+                if let Some(Ok(email)) = attribute.children.get(1).map(|node| value::ascii_string(node.content(bytes))) {
+                    names.push(GeneralName::Email(email.to_ascii_lowercase()));
+                }
+            }
+        }
+    }
+    if let Some((extn_value, _)) = fields.extension(bytes, "2.5.29.17") {
+        let general_names = extn_value.children.first().map(|node| node.children.as_slice()).unwrap_or_default();
+        names.extend(general_names.iter().filter_map(|node| GeneralName::parse(node, bytes)));
+    }
+    return names;
+}
+
+/// The permitted and excluded subtrees of a nameConstraints extension.
+fn subtrees(extn_value: &Node, bytes: &[u8]) -> (Option<Vec<GeneralName>>, Vec<GeneralName>) {
+    let mut permitted = None;
+    let mut excluded = Vec::new();
+    for child in extn_value.children.first().map(|node| node.children.as_slice()).unwrap_or_default() {
+        let bases = child.children.iter().filter_map(|subtree| GeneralName::parse(subtree.children.first()?, bytes));
+        match child.tag.number {
+            0 => permitted = Some(bases.collect()),
+            _ => excluded.extend(bases),
+        }
+    }
+    return (permitted, excluded);
+}
+
+/// The valid policies of the RFC 5280 policy tree, simplified to the set of
+/// policies at its deepest level.
+struct Policies {
+    /// Whether anyPolicy is still valid, so that any policy is.
+    any: bool,
+    set: BTreeSet<String>,
+}
+
+impl Policies {
+    fn is_null(&self) -> bool {
+        return !self.any && self.set.is_empty();
+    }
+}
+
+/// The OIDs of a SEQUENCE of elements starting with one.
+fn oids(node: &Node, bytes: &[u8]) -> Vec<String> {
+    return node
+        .children
+        .iter()
+        .filter_map(|child| oid::stringify(child.children.first()?.content(bytes)).ok())
+        .collect();
+}
+
+fn skip_certs(node: &Node, bytes: &[u8]) -> usize {
+    return value::unsigned(node.content(bytes)).map(|count| count as usize).unwrap_or(0);
+}
+
+/// Validate the chain returned by `build` at the given time, following
+/// RFC 5280 section 6.1 with the last certificate as the trust anchor, any
+/// policy acceptable, and no initial constraint.
+///
+/// Returns the failures of every certificate of the chain, in its order.
+pub fn validate(candidates: &[Candidate], path: &[usize], time: DateTime<Utc>) -> Vec<Vec<String>> {
+    let mut failures = vec![Vec::new(); path.len()];
+    let anchor = &candidates[path[path.len() - 1]];
+    if !anchor.is_self_issued() || anchor.is_signed_by(anchor).is_err() {
+        failures[path.len() - 1].push("The chain doesn't end with a self-signed certificate".to_owned());
+    }
+
+    let n = path.len() - 1;
+    let mut policies = Policies { any: true, set: BTreeSet::new() };
+    let mut permitted: Vec<Vec<GeneralName>> = Vec::new();
+    let mut excluded: Vec<GeneralName> = Vec::new();
+    let mut explicit_policy = n + 1;
+    let mut inhibit_any_policy = n + 1;
+    let mut policy_mapping = n + 1;
+    let mut max_path_length = n;
+
+    for (depth, idx) in path.iter().enumerate().rev() {
+        let certificate = &candidates[*idx];
+        let errors = &mut failures[depth];
+        let fields = match certificate.fields() {
+            Some(fields) => fields,
+            None => {
+                errors.push("Not a certificate".to_owned());
+                continue;
+            }
+        };
+        let bytes = certificate.bytes;
+
+        match fields.validity(bytes) {
+            Ok((not_before, _)) if time < not_before => errors.push(format!("Not valid before {}", not_before)),
+            Ok((_, not_after)) if time > not_after => errors.push(format!("Expired on {}", not_after)),
+            Ok(_) => {}
+            Err(err) => errors.push(format!("Invalid validity: {}", err)),
+        }
+        if depth == n {
+            continue;
+        }
+
+        // Basic certificate processing.
+        let issuer = &candidates[path[depth + 1]];
+        if let Err(err) = certificate.is_signed_by(issuer) {
+            errors.push(format!("The signature doesn't verify with the key of the issuer: {}", err));
+        }
+        if issuer.fields().map(|issuer_fields| issuer_fields.subject.content(issuer.bytes)) != Some(fields.issuer.content(bytes)) {
+            errors.push("The issuer doesn't match the subject of the issuing certificate".to_owned());
+        }
+        let self_issued = certificate.is_self_issued();
+        let i = n - depth;
+
+        if !(self_issued && i < n) {
+            for name in names(&fields, bytes) {
+                if excluded.iter().any(|base| name.within(base)) {
+                    errors.push(format!("{} is excluded by the name constraints", name.describe()));
+                }
+                let allowed = permitted.iter().all(|bases| {
+                    !bases.iter().any(|base| base.same_type(&name)) || bases.iter().any(|base| name.within(base))
+                });
+                if !allowed {
+                    errors.push(format!("{} isn't permitted by the name constraints", name.describe()));
+                }
+            }
+        }
+
+        match fields.extension(bytes, "2.5.29.32") {
+            Some((extn_value, _)) if !policies.is_null() => {
+                let policy_information = extn_value.children.first().map(|node| oids(node, bytes)).unwrap_or_default();
+                let has_any = policy_information.iter().any(|policy| policy == ANY_POLICY);
+                let any_allowed = has_any && (inhibit_any_policy > 0 || (i < n && self_issued));
+                let listed: BTreeSet<String> = policy_information.into_iter().filter(|policy| policy != ANY_POLICY).collect();
+                let mut set = if policies.any { listed.clone() } else { policies.set.intersection(&listed).cloned().collect() };
+                if any_allowed {
+                    set.extend(policies.set.iter().cloned());
+                }
+                policies = Policies { any: policies.any && any_allowed, set };
+            }
+            Some(_) => {}
+            None => policies = Policies { any: false, set: BTreeSet::new() },
+        }
+        if explicit_policy == 0 && policies.is_null() {
+            errors.push(NO_POLICY.to_owned());
+        }
+
+        for (id, critical, _) in fields.extension_list(bytes) {
+            if critical && !PROCESSED_EXTENSIONS.contains(&id.as_str()) {
+                errors.push(format!("Unsupported critical extension {}", id));
+            }
+        }
+
+        if i == n {
+            // Wrap-up procedure.
+            explicit_policy = explicit_policy.saturating_sub(1);
+            if let Some((extn_value, _)) = fields.extension(bytes, "2.5.29.36") {
+                let constraints = extn_value.children.first().map(|node| node.children.as_slice()).unwrap_or_default();
+                if constraints.iter().any(|child| child.tag.number == 0 && skip_certs(child, bytes) == 0) {
+                    explicit_policy = 0;
+                }
+            }
+            if explicit_policy == 0 && policies.is_null() && !errors.iter().any(|error| error == NO_POLICY) {
+                errors.push(NO_POLICY.to_owned());
+            }
+            continue;
+        }
+
+        // Preparation for the next certificate.
+        if let Some((extn_value, _)) = fields.extension(bytes, "2.5.29.33") {
+            let mappings: Vec<(String, String)> = extn_value
+                .children
+                .first()
+                .map(|node| node.children.as_slice())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|mapping| {
+                    let issuer_domain = oid::stringify(mapping.child(0, Tag::OID).ok()?.content(bytes)).ok()?;
+                    let subject_domain = oid::stringify(mapping.child(1, Tag::OID).ok()?.content(bytes)).ok()?;
+                    return Some((issuer_domain, subject_domain));
+                })
+                .collect();
+            if mappings.iter().any(|(issuer_domain, subject_domain)| issuer_domain == ANY_POLICY || subject_domain == ANY_POLICY) {
+                errors.push("anyPolicy can't be mapped".to_owned());
+            }
+            // An issuer domain policy is also valid through anyPolicy, which
+            // RFC 5280 6.1.4 (b)(1) maps like a listed policy. A policy may
+            // map to several ones, so it's only removed once all are mapped.
+            let mut mapped = BTreeSet::new();
+            for (issuer_domain, subject_domain) in mappings.iter() {
+                let valid = policies.set.contains(issuer_domain) || policies.any;
+                if valid && policy_mapping > 0 {
+                    mapped.insert(subject_domain.clone());
+                }
+            }
+            for (issuer_domain, _) in mappings.iter() {
+                policies.set.remove(issuer_domain);
+            }
+            policies.set.extend(mapped);
+        }
+
+        if let Some((extn_value, _)) = fields.extension(bytes, "2.5.29.30") {
+            let (permitted_subtrees, excluded_subtrees) = subtrees(extn_value, bytes);
+            permitted.extend(permitted_subtrees);
+            excluded.extend(excluded_subtrees);
+        }
+
+        if !self_issued {
+            explicit_policy = explicit_policy.saturating_sub(1);
+            policy_mapping = policy_mapping.saturating_sub(1);
+            inhibit_any_policy = inhibit_any_policy.saturating_sub(1);
+        }
+        if let Some((extn_value, _)) = fields.extension(bytes, "2.5.29.36") {
+            for child in extn_value.children.first().map(|node| node.children.as_slice()).unwrap_or_default() {
+                match child.tag.number {
+                    0 => explicit_policy = explicit_policy.min(skip_certs(child, bytes)),
+                    _ => policy_mapping = policy_mapping.min(skip_certs(child, bytes)),
+                }
+            }
+        }
+        if let Some((extn_value, _)) = fields.extension(bytes, "2.5.29.54") {
+            if let Some(skip) = extn_value.children.first() {
+                inhibit_any_policy = inhibit_any_policy.min(skip_certs(skip, bytes));
+            }
+        }
+
+        let basic_constraints = fields.extension(bytes, "2.5.29.19").and_then(|(extn_value, _)| extn_value.children.first());
+        let children = basic_constraints.map(|node| node.children.as_slice()).unwrap_or_default();
+        let is_ca = children.iter().any(|child| child.tag == Tag::BOOLEAN && value::boolean(child.content(bytes)) == Ok(true));
+        if !is_ca {
+            errors.push("Not a CA: basicConstraints doesn't assert cA".to_owned());
+        }
+        if !self_issued {
+            if max_path_length == 0 {
+                errors.push("The maximum path length of an issuer is exceeded".to_owned());
+            }
+            max_path_length = max_path_length.saturating_sub(1);
+        }
+        if let Some(path_len) = children.iter().find(|child| child.tag == Tag::INTEGER) {
+            max_path_length = max_path_length.min(skip_certs(path_len, bytes));
+        }
+
+        if let Some((extn_value, _)) = fields.extension(bytes, "2.5.29.15") {
+            let bits = extn_value.children.first().and_then(|node| value::bit_string(node.content(bytes)).ok());
+            // keyCertSign is bit 5.
+            if !matches!(bits, Some((_, bits)) if bits.first().is_some_and(|byte| byte & 0x04 != 0)) {
+                errors.push("keyUsage doesn't allow signing certificates".to_owned());
+            }
+        }
+    }
+
+    return failures;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use super::*;
+    use crate::node;
+
+    fn certificates() -> Vec<(&'static [u8], Vec<Node>)> {
+        let files: [&'static [u8]; 4] = [
+            include_bytes!("../tests/leaf.der"),
+            include_bytes!("../tests/ed25519.der"),
+            include_bytes!("../tests/root.der"),
+            include_bytes!("../tests/inter.der"),
+        ];
+        return files.iter().map(|bytes| (*bytes, node::parse(bytes).0)).collect();
+    }
+
+    fn candidates<'a>(certificates: &'a [(&'static [u8], Vec<Node>)]) -> Vec<Candidate<'a>> {
+        return certificates.iter().map(|(bytes, nodes)| Candidate { node: &nodes[0], bytes }).collect();
+    }
+
+    #[test]
+    fn build_chain() {
+        let certificates = certificates();
+        let candidates = candidates(&certificates);
+        assert_eq!(build(&candidates, 0), vec![0, 3, 2]);
+        assert_eq!(build(&candidates, 3), vec![3, 2]);
+        assert_eq!(build(&candidates, 1), vec![1]);
+        assert_eq!(build(&candidates[..2], 0), vec![0]);
+    }
+
+    #[test]
+    fn validate_chain() {
+        let certificates = certificates();
+        let candidates = candidates(&certificates);
+        let time = Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(validate(&candidates, &[3, 2], time), vec![Vec::<String>::new(), Vec::new()]);
+
+        // The intermediate only permits IPv4 addresses in 10.0.0.0/8.
+        let failures = validate(&candidates, &[0, 3, 2], time);
+        assert_eq!(failures[0], vec!["IP address 2001:db8::1 isn't permitted by the name constraints"]);
+        assert!(failures[1].is_empty() && failures[2].is_empty());
+
+        let failures = validate(&candidates, &[0, 3, 2], Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(failures[0][0], "Expired on 2029-01-20 19:03:17 UTC");
+
+        let failures = validate(&candidates, &[3, 0], time);
+        assert_eq!(failures[1], vec!["The chain doesn't end with a self-signed certificate"]);
+        assert!(failures[0].contains(&"The issuer doesn't match the subject of the issuing certificate".to_owned()));
+    }
+
+    #[test]
+    fn validate_policy_mappings() {
+        let files: [&'static [u8]; 8] = [
+            include_bytes!("../tests/policy-leaf-s.der"),
+            include_bytes!("../tests/policy-leaf-p.der"),
+            include_bytes!("../tests/policy-any.der"),
+            include_bytes!("../tests/policy-mapped.der"),
+            include_bytes!("../tests/policy-root.der"),
+            include_bytes!("../tests/policy-leaf-t.der"),
+            include_bytes!("../tests/policy-split.der"),
+            include_bytes!("../tests/policy-split-root.der"),
+        ];
+        let certificates: Vec<_> = files.iter().map(|bytes| (*bytes, node::parse(bytes).0)).collect();
+        let candidates = candidates(&certificates);
+        let time = Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap();
+        let errors = |path: &[usize]| validate(&candidates, path, time).concat();
+
+        // Both intermediates map policy .1 to .2 and require an explicit policy.
+        assert!(errors(&[0, 3, 4]).is_empty());
+        assert_eq!(errors(&[1, 3, 4]), vec![NO_POLICY]);
+        // Through anyPolicy, .1 stays valid next to the policy it maps to.
+        assert!(errors(&[0, 2, 4]).is_empty());
+        assert!(errors(&[1, 2, 4]).is_empty());
+        // Policy .1 maps to both .2 and .3, and the leaf asserts .3.
+        assert!(errors(&[5, 6, 7]).is_empty());
+    }
+
+    #[test]
+    fn match_name_constraints() {
+        let dns = |name: &str| GeneralName::Dns(name.to_owned());
+        assert!(dns("www.example.com").within(&dns("example.com")));
+        assert!(dns("example.com").within(&dns("example.com")));
+        assert!(!dns("badexample.com").within(&dns("example.com")));
+        assert!(!dns("example.com").within(&dns(".example.com")));
+
+        let email = |name: &str| GeneralName::Email(name.to_owned());
+        assert!(email("admin@example.com").within(&email("example.com")));
+        assert!(email("admin@mail.example.com").within(&email(".example.com")));
+        assert!(!email("admin@example.com").within(&email("root@example.com")));
+
+        let network = GeneralName::Ip(vec![10, 0, 0, 0, 255, 0, 0, 0]);
+        assert!(GeneralName::Ip(vec![10, 1, 2, 3]).within(&network));
+        assert!(!GeneralName::Ip(vec![11, 1, 2, 3]).within(&network));
+    }
+}
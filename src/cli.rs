@@ -0,0 +1,427 @@
+use std::ffi::OsString;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crate::der::{Class, Tag};
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::node::{self, Node};
+use crate::notation::{self, Notation};
+use crate::{hex, json, oid, oid_names, pem, value};
+
+const USAGE: &str = "Usage: explo-der dump [options] [file]
+
+Print the elements of a DER file like `openssl asn1parse`, reading the
+standard input when no file is given.
+
+Options:
+  -in file          file to read
+  -inform format    DER, PEM or B64, detected when omitted
+  -i                indent the elements by their depth
+  -strparse offset  parse the content of the element at offset instead,
+                    can be repeated to descend further
+  -json             print the tree as JSON, with the content in hexadecimal,
+                    the decoded values and the fields of the recognized
+                    structures
+  -notation name    print the elements in ASN.1 value notation (value), XER
+                    (xer) or JER (jer), named after the recognized structure
+  -reveal           print the private key components with -json and -notation,
+                    which are hidden otherwise
+";
+
+/// How the input file is encoded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Input {
+    Detect,
+    Der,
+    Pem,
+    Base64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    path: Option<PathBuf>,
+    input: Input,
+    indent: bool,
+    strparse: Vec<usize>,
+    json: bool,
+    notation: Option<Notation>,
+    reveal: bool,
+}
+
+impl Options {
+    fn parse(args: &[OsString]) -> Result<Self> {
+        let mut options = Options {
+            path: None,
+            input: Input::Detect,
+            indent: false,
+            strparse: Vec::new(),
+            json: false,
+            notation: None,
+            reveal: false,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(Error("Missing value of an option"));
+            match arg.to_str() {
+                Some("-in") => options.path = Some(PathBuf::from(value()?)),
+                Some("-inform") => {
+                    options.input = match value()?.to_string_lossy().to_ascii_uppercase().as_str() {
+                        "DER" => Input::Der,
+                        "PEM" => Input::Pem,
+                        "B64" | "BASE64" => Input::Base64,
+                        _ => return Err(Error("The input format must be DER, PEM or B64")),
+                    };
+                }
+                Some("-i") => options.indent = true,
+                Some("-json") => options.json = true,
+                Some("-notation") => {
+                    options.notation = match value()?.to_string_lossy().to_ascii_lowercase().as_str() {
+                        "value" => Some(Notation::Value),
+                        "xer" => Some(Notation::Xer),
+                        "jer" => Some(Notation::Jer),
+                        _ => return Err(Error("The notation must be value, xer or jer")),
+                    };
+                }
+                Some("-reveal") => options.reveal = true,
+                Some("-strparse") => {
+                    let offset = value()?.to_str().and_then(|offset| offset.parse().ok());
+                    options.strparse.push(offset.ok_or(Error("Invalid offset"))?);
+                }
+                Some(option) if option.starts_with('-') && option != "-" => return Err(Error("Unknown option")),
+                _ if options.path.is_none() => options.path = Some(PathBuf::from(arg)),
+                _ => return Err(Error("Only one file can be dumped")),
+            }
+        }
+        return Ok(options);
+    }
+}
+
+/// Decode the input to DER.
+fn decode(bytes: Vec<u8>, input: Input) -> Result<Vec<u8>> {
+    return match input {
+        Input::Detect => Ok(pem::decode(&bytes).unwrap_or(bytes)),
+        Input::Der => Ok(bytes),
+        Input::Pem => pem::decode(&bytes).ok_or(Error("No PEM block found")),
+        Input::Base64 => {
+            let text: Vec<u8> = bytes.into_iter().filter(|byte| !byte.is_ascii_whitespace()).collect();
+            STANDARD.decode(text).map_err(|_| Error("Invalid base64"))
+        }
+    };
+}
+
+/// Find the element starting at `offset`, including the ones encapsulated in
+/// OCTET STRING and BIT STRING.
+fn find(nodes: &[Node], offset: usize) -> Option<&Node> {
+    for node in nodes {
+        if node.offset == offset {
+            return Some(node);
+        }
+        if node.offset < offset && offset < node.end() {
+            return find(&node.children, offset);
+        }
+    }
+    return None;
+}
+
+/// The content of the element at `offset`, without the unused bits octet of
+/// a BIT STRING, to parse it like `-strparse`.
+fn strparse(bytes: &[u8], offset: usize) -> Result<Vec<u8>> {
+    let (nodes, _) = node::parse(bytes);
+    let node = find(&nodes, offset).ok_or(Error("No element starts at the offset"))?;
+    if node.tag.constructed {
+        return Err(Error("The element at the offset isn't primitive"));
+    }
+    return match node.tag {
+        Tag::BIT_STRING => Ok(value::bit_string(node.content(bytes))?.1.to_vec()),
+        _ => Ok(node.content(bytes).to_vec()),
+    };
+}
+
+/// Name of the tag in the output of `openssl asn1parse`.
+fn tag_name(tag: Tag) -> String {
+    const UNIVERSAL: [&str; 31] = [
+        "EOC", "BOOLEAN", "INTEGER", "BIT STRING", "OCTET STRING", "NULL", "OBJECT", "OBJECT DESCRIPTOR",
+        "EXTERNAL", "REAL", "ENUMERATED", "EMBEDDED PDV", "UTF8STRING", "RELATIVE OID", "TIME", "<ASN1 15>",
+        "SEQUENCE", "SET", "NUMERICSTRING", "PRINTABLESTRING", "T61STRING", "VIDEOTEXSTRING", "IA5STRING",
+        "UTCTIME", "GENERALIZEDTIME", "GRAPHICSTRING", "VISIBLESTRING", "GENERALSTRING", "UNIVERSALSTRING",
+        "<ASN1 29>", "BMPSTRING",
+    ];
+    return match tag.class {
+        Class::Universal => match UNIVERSAL.get(tag.number as usize) {
+            Some(name) => name.to_string(),
+            None => format!("<ASN1 {}>", tag.number),
+        },
+        Class::Application => format!("appl [ {} ]", tag.number),
+        Class::ContextSpecific => format!("cont [ {} ]", tag.number),
+        Class::Private => format!("priv [ {} ]", tag.number),
+    };
+}
+
+/// The value printed after the tag, like `openssl asn1parse` does, with
+/// strings decoded and OIDs named.
+fn primitive_value(tag: Tag, content: &[u8]) -> String {
+    return match tag {
+        Tag::BOOLEAN => match content {
+            [byte] => format!(":{}", byte),
+            _ => "Bad boolean".to_owned(),
+        },
+        Tag::INTEGER | Tag::ENUMERATED => match value::integer_magnitude(content) {
+            Ok((true, _)) => {
+                // The magnitude of the two's complement.
+                let mut magnitude: Vec<u8> = content.iter().map(|byte| !byte).collect();
+                for byte in magnitude.iter_mut().rev() {
+                    *byte = byte.wrapping_add(1);
+                    if *byte != 0 {
+                        break;
+                    }
+                }
+                let start = magnitude.iter().position(|byte| *byte != 0).unwrap_or(magnitude.len() - 1);
+                format!(":-{}", hex::hexlify(&magnitude[start..]).to_ascii_uppercase())
+            }
+            Ok((false, magnitude)) => format!(":{}", hex::hexlify(magnitude).to_ascii_uppercase()),
+            Err(_) => format!("BAD INTEGER:[{}]", hex::hexlify(content).to_ascii_uppercase()),
+        },
+        Tag::OID => match oid::stringify(content) {
+            Ok(id) => format!(":{}", oid_names::name_or_oid(id)),
+            Err(_) => "Bad object".to_owned(),
+        },
+        Tag::OCTET_STRING if content.is_empty() => String::new(),
+        Tag::OCTET_STRING => {
+            let printable = content.iter().all(|byte| (b' '..=b'~').contains(byte) || b"\n\r\t".contains(byte));
+            match printable {
+                true => format!(":{}", String::from_utf8_lossy(content)),
+                false => format!("[HEX DUMP]:{}", hex::hexlify(content).to_ascii_uppercase()),
+            }
+        }
+        Tag::UTC_TIME | Tag::GENERALIZED_TIME => format!(":{}", String::from_utf8_lossy(content)),
+        tag if value::is_string(tag) => match value::string(tag, content) {
+            Ok(string) => format!(":{}", string),
+            Err(_) => format!(":{}", String::from_utf8_lossy(content)),
+        },
+        _ => String::new(),
+    };
+}
+
+fn dump_nodes(output: &mut String, nodes: &[Node], bytes: &[u8], depth: usize, indent: bool) {
+    let pad = |depth| if indent { " ".repeat(depth) } else { String::new() };
+    for node in nodes {
+        let len = if node.indefinite { "inf ".to_owned() } else { format!("{:4}", node.len) };
+        let form = if node.tag.constructed { "cons" } else { "prim" };
+        let name = format!("{:<18}", tag_name(node.tag));
+        output.push_str(&format!("{:5}:d={:<2} hl={} l={} {}: {}{}", node.offset, depth, node.header_len, len, form, pad(depth), name));
+        if !node.tag.constructed {
+            output.push_str(&primitive_value(node.tag, node.content(bytes)));
+        }
+        output.push('\n');
+
+        // Like openssl, the elements encapsulated in primitive elements are
+        // only printed with -strparse.
+        if node.tag.constructed {
+            dump_nodes(output, &node.children, bytes, depth + 1, indent);
+        }
+        if node.indefinite {
+            let offset = node.end() - 2;
+            output.push_str(&format!("{:5}:d={:<2} hl=2 l=   0 prim: {}EOC\n", offset, depth + 1, pad(depth + 1)));
+        }
+    }
+}
+
+/// The first error that stopped the parsing of the elements, and its offset.
+fn first_error(nodes: &[Node]) -> Option<&(usize, Error)> {
+    for node in nodes {
+        if node.tag.constructed {
+            if let Some(error) = node.error.as_ref().or_else(|| first_error(&node.children)) {
+                return Some(error);
+            }
+        }
+    }
+    return None;
+}
+
+/// Print the elements of `bytes` in the format of `openssl asn1parse`.
+fn dump(bytes: &[u8], indent: bool) -> (String, Option<(usize, Error)>) {
+    let (nodes, error) = node::parse(bytes);
+    let mut output = String::new();
+    dump_nodes(&mut output, &nodes, bytes, 0, indent);
+    let error = first_error(&nodes).cloned().or(error);
+    return (output, error);
+}
+
+fn read(options: &Options) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let result = match options.path.as_ref() {
+        Some(path) if path.as_os_str() != "-" => std::fs::File::open(path).and_then(|mut file| file.read_to_end(&mut bytes)),
+        _ => std::io::stdin().read_to_end(&mut bytes),
+    };
+    result.map_err(|_| Error("Failed to read the input"))?;
+    return decode(bytes, options.input);
+}
+
+/// The document as JSON, or in the notation of the options.
+fn render(document: &Document, options: &Options) -> String {
+    return match options.notation {
+        Some(notation) => {
+            let render = |node| notation::render(notation, node, &document.bytes, options.reveal);
+            document.nodes.iter().map(render).collect()
+        }
+        None => json::document(document, options.reveal).pretty(),
+    };
+}
+
+fn run_dump(args: &[OsString]) -> i32 {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprint!("{}\n\n{}", err, USAGE);
+            return 2;
+        }
+    };
+    oid_names::reload();
+
+    let mut bytes = match read(&options) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 1;
+        }
+    };
+    for offset in options.strparse.iter() {
+        bytes = match strparse(&bytes, *offset) {
+            Ok(content) => content,
+            Err(err) => {
+                eprintln!("Error parsing structure at offset {}: {}", offset, err);
+                return 1;
+            }
+        };
+    }
+
+    if options.json || options.notation.is_some() {
+        let name = match options.path.as_ref().and_then(|path| path.file_name()) {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => "stdin".to_owned(),
+        };
+        let document = Document::from_bytes(name, bytes);
+        let _ = std::io::stdout().write_all(render(&document, &options).as_bytes());
+        if let Some((offset, err)) = document.error.as_ref().filter(|_| options.notation.is_some()) {
+            eprintln!("Error in encoding at offset {}: {}", offset, err);
+        }
+        return if document.error.is_some() { 1 } else { 0 };
+    }
+
+    let (output, error) = dump(&bytes, options.indent);
+    let _ = std::io::stdout().write_all(output.as_bytes());
+    if let Some((offset, err)) = error {
+        eprintln!("Error in encoding at offset {}: {}", offset, err);
+        return 1;
+    }
+    return 0;
+}
+
+/// Run the subcommand named by the first argument, returning the exit code,
+/// or None when the arguments are files to open in the window.
+pub fn run(args: &[OsString]) -> Option<i32> {
+    let (command, args) = args.split_first()?;
+    return match command.to_str()? {
+        "dump" => Some(run_dump(args)),
+        "help" | "-h" | "--help" => {
+            print!("{}", USAGE);
+            Some(0)
+        }
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        return args.iter().map(OsString::from).collect();
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = Options::parse(&args(&["-i", "-inform", "pem", "-strparse", "12", "cert.pem"])).unwrap();
+        let path = Some(PathBuf::from("cert.pem"));
+        let expected = Options {
+            path,
+            input: Input::Pem,
+            indent: true,
+            strparse: vec![12],
+            json: false,
+            notation: None,
+            reveal: false,
+        };
+        assert_eq!(options, expected);
+        assert!(Options::parse(&args(&["-json"])).unwrap().json);
+        assert!(Options::parse(&args(&["-reveal"])).unwrap().reveal);
+        assert_eq!(Options::parse(&args(&["-notation", "XER"])).unwrap().notation, Some(Notation::Xer));
+        assert!(Options::parse(&args(&["-notation", "ber"])).is_err());
+        assert!(Options::parse(&args(&["-inform", "xml"])).is_err());
+        assert!(Options::parse(&args(&["-strparse"])).is_err());
+        assert!(Options::parse(&args(&["a.der", "b.der"])).is_err());
+        assert_eq!(run(&args(&["leaf.der"])), None);
+    }
+
+    #[test]
+    fn hide_private_keys() {
+        let document = Document::from_bytes("leaf.p8".to_owned(), include_bytes!("../tests/leaf.p8").to_vec());
+        let private_key = concat!(
+            "f1e0877a8303a37246e464b31c64a077dc1ed05d1309661c",
+            "b91b4b6dbf1feca0a1519e8a22dbb92046a3f80cca1c1dfe",
+        );
+        for notation in [None, Some(Notation::Value), Some(Notation::Xer), Some(Notation::Jer)] {
+            let mut options = Options::parse(&[]).unwrap();
+            options.json = notation.is_none();
+            options.notation = notation;
+            assert!(!render(&document, &options).to_ascii_lowercase().contains(private_key));
+            options.reveal = true;
+            assert!(render(&document, &options).to_ascii_lowercase().contains(private_key));
+        }
+    }
+
+    #[test]
+    fn dump_like_openssl() {
+        let bytes = include_bytes!("../tests/kerberos-ticket.der");
+        let (output, error) = dump(bytes, true);
+        assert!(error.is_none());
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "    0:d=0  hl=2 l= 106 cons: appl [ 1 ]        ");
+        assert_eq!(lines[3], "    6:d=3  hl=2 l=   1 prim:    INTEGER           :05");
+        assert_eq!(lines[5], "   11:d=3  hl=2 l=  11 prim:    GENERALSTRING     :EXAMPLE.COM");
+        assert_eq!(lines.len(), 22);
+
+        // SEQUENCE { OCTET STRING { BOOLEAN TRUE }, INTEGER -129, INTEGER 128 }
+        // with an indefinite length.
+        let bytes = [0x30, 0x80, 0x04, 0x03, 0x01, 0x01, 0xFF, 0x02, 0x02, 0xFF, 0x7F, 0x02, 0x02, 0x00, 0x80, 0x00, 0x00];
+        let (output, error) = dump(&bytes, false);
+        assert!(error.is_none());
+        assert_eq!(
+            output,
+            "    0:d=0  hl=2 l=inf  cons: SEQUENCE          \n\
+             \x20   2:d=1  hl=2 l=   3 prim: OCTET STRING      [HEX DUMP]:0101FF\n\
+             \x20   7:d=1  hl=2 l=   2 prim: INTEGER           :-81\n\
+             \x20  11:d=1  hl=2 l=   2 prim: INTEGER           :80\n\
+             \x20  15:d=1  hl=2 l=   0 prim: EOC\n"
+        );
+        let content = strparse(&bytes, 2).unwrap();
+        assert_eq!(dump(&content, false).0, "    0:d=0  hl=2 l=   1 prim: BOOLEAN           :255\n");
+        assert!(strparse(&bytes, 3).is_err());
+
+        let (_, error) = dump(&[0x30, 0x03, 0x02, 0x05, 0x00], false);
+        assert_eq!(error.map(|(offset, _)| offset), Some(2));
+    }
+
+    #[test]
+    fn decode_inputs() {
+        let der = vec![0x30, 0x03, 0x02, 0x01, 0x01];
+        assert_eq!(decode(b"MAMC\nAQE=\n".to_vec(), Input::Base64).unwrap(), der);
+        let pem = b"-----BEGIN X-----\nMAMCAQE=\n-----END X-----\n".to_vec();
+        assert_eq!(decode(pem.clone(), Input::Detect).unwrap(), der);
+        assert_eq!(decode(pem, Input::Pem).unwrap(), der);
+        assert!(decode(der.clone(), Input::Pem).is_err());
+        assert_eq!(decode(der.clone(), Input::Detect).unwrap(), der);
+    }
+}
@@ -0,0 +1,680 @@
+use chrono::{DateTime, Utc};
+use crate::der::{self, Class, Tag};
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::signature::{self, PublicKey};
+use crate::x509::{self, Certificate};
+use crate::{hash, hex, name, oid, oid_names, spki, value};
+
+pub const DATA: &str = "1.2.840.113549.1.7.1";
+pub const SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+pub const ENVELOPED_DATA: &str = "1.2.840.113549.1.7.3";
+pub const DIGESTED_DATA: &str = "1.2.840.113549.1.7.5";
+pub const ENCRYPTED_DATA: &str = "1.2.840.113549.1.7.6";
+pub const AUTHENTICATED_DATA: &str = "1.2.840.113549.1.9.16.1.2";
+pub const AUTH_ENVELOPED_DATA: &str = "1.2.840.113549.1.9.16.1.23";
+
+const CONTENT_TYPE: &str = "1.2.840.113549.1.9.3";
+const MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+const SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+
+/// A certificate embedded in a SignedData, or in the originatorInfo of an
+/// EnvelopedData.
+#[derive(Clone, Debug)]
+pub struct EmbeddedCertificate {
+    pub subject: String,
+    pub der: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Signer {
+    pub identifier: String,
+    pub digest_algorithm: String,
+    pub signature_algorithm: String,
+    pub signing_time: Option<DateTime<Utc>>,
+    /// Result of the last verification of the signature.
+    pub verification: Option<Result<()>>,
+}
+
+/// Summary of a RFC 5652 ContentInfo.
+#[derive(Clone, Debug)]
+pub struct Cms {
+    pub content_type: String,
+    /// Type of the encapsulated or encrypted content.
+    pub inner_content_type: Option<String>,
+    /// The signed or authenticated content isn't part of the structure.
+    pub detached: bool,
+    pub certificates: Vec<EmbeddedCertificate>,
+    pub crls: usize,
+    pub signers: Vec<Signer>,
+    pub recipients: Vec<String>,
+}
+
+pub fn content_type(node: &Node, bytes: &[u8]) -> Result<String> {
+    if node.tag != Tag::SEQUENCE || node.children.is_empty() || 2 < node.children.len() {
+        return Err(Error("ContentInfo must be a SEQUENCE of one or two elements"));
+    }
+    return oid::stringify(node.child(0, Tag::OID)?.content(bytes));
+}
+
+/// Name the fields of a ContentInfo and return its content, that is the
+/// element inside the explicit tag.
+pub fn annotate_content_info<'a>(node: &'a mut Node, bytes: &[u8]) -> Option<&'a mut Node> {
+    if let Ok(content_type) = content_type(node, bytes) {
+        node.note = oid_names::find(&content_type).map(|entry| entry.name.to_string());
+    }
+    node.set_child_field(0, "contentType");
+    return node.set_child_field(1, "content")?.children.first_mut();
+}
+
+/// The content of a string element, concatenating the segments of the
+/// constructed form.
+pub fn octets(node: &Node, bytes: &[u8]) -> Vec<u8> {
+    if !node.tag.constructed {
+        return node.content(bytes).to_vec();
+    }
+    return node.children.iter().flat_map(|child| octets(child, bytes)).collect();
+}
+
+fn algorithm_name(node: &Node, bytes: &[u8]) -> Result<String> {
+    let (oid, _) = spki::algorithm(node, bytes)?;
+    return Ok(oid_names::name_or_oid(oid));
+}
+
+/// Describe an IssuerAndSerialNumber, or the key identifier that replaces it.
+pub fn identifier(node: &Node, bytes: &[u8]) -> Result<String> {
+    if node.tag == Tag::SEQUENCE {
+        let issuer = name::format(node.child(0, Tag::SEQUENCE)?, bytes)?;
+        let (_, serial) = value::integer_magnitude(node.child(1, Tag::INTEGER)?.content(bytes))?;
+        return Ok(format!("{}, serial {}", issuer, hex::colon_separated(serial)));
+    }
+    if node.tag.class == Class::ContextSpecific {
+        let key_id = match node.tag.constructed {
+            // RecipientKeyIdentifier of the key agreement recipients.
+            true => node.child(0, Tag::OCTET_STRING)?.content(bytes),
+            false => node.content(bytes),
+        };
+        return Ok(format!("key identifier {}", hex::colon_separated(key_id)));
+    }
+    return Err(Error("Unexpected identifier"));
+}
+
+/// Position of the next child with the given tag, starting at `idx`.
+fn find(node: &Node, idx: usize, tag: Tag) -> Option<usize> {
+    return match node.children.get(idx) {
+        Some(child) if child.tag == tag => Some(idx),
+        _ => None,
+    };
+}
+
+/// The certificates of a CertificateSet, or of a SEQUENCE of Certificate.
+pub fn certificates(node: &Node, bytes: &[u8]) -> Vec<EmbeddedCertificate> {
+    let mut certificates = Vec::new();
+    for child in node.children.iter() {
+        if let Ok(certificate) = Certificate::parse(child, bytes) {
+            certificates.push(EmbeddedCertificate { subject: certificate.subject, der: child.raw(bytes).to_vec() });
+        }
+    }
+    return certificates;
+}
+
+fn signer(node: &Node, bytes: &[u8]) -> Result<Signer> {
+    if node.tag != Tag::SEQUENCE || node.children.len() < 5 {
+        return Err(Error("SignerInfo must be a SEQUENCE of at least five elements"));
+    }
+
+    let identifier = identifier(&node.children[1], bytes)?;
+    let digest_algorithm = algorithm_name(node.child(2, Tag::SEQUENCE)?, bytes)?;
+    let signed_attributes = find(node, 3, Tag::context_specific(0).into_constructed());
+    let next = if signed_attributes.is_some() { 4 } else { 3 };
+    let signature_algorithm = algorithm_name(node.child(next, Tag::SEQUENCE)?, bytes)?;
+    node.child(next + 1, Tag::OCTET_STRING)?;
+
+    let mut signing_time = None;
+    if let Some(idx) = signed_attributes {
+        for attribute in node.children[idx].children.iter() {
+            let id = oid::stringify(attribute.child(0, Tag::OID)?.content(bytes))?;
+            if id == SIGNING_TIME {
+                let time = attribute.child(1, Tag::SET)?.children.first().ok_or(Error("Missing signing time"))?;
+                signing_time = Some(value::time(time.tag, time.content(bytes))?);
+            }
+        }
+    }
+
+    return Ok(Signer { identifier, digest_algorithm, signature_algorithm, signing_time, verification: None });
+}
+
+/// The single value of the signed attribute with the given OID.
+fn attribute<'a>(attributes: &'a Node, bytes: &[u8], id: &str) -> Result<Option<&'a Node>> {
+    for attribute in attributes.children.iter() {
+        if oid::stringify(attribute.child(0, Tag::OID)?.content(bytes))? != id {
+            continue;
+        }
+        let values = attribute.child(1, Tag::SET)?;
+        if values.children.len() != 1 {
+            return Err(Error("Signed attributes must have a single value"));
+        }
+        return Ok(values.children.first());
+    }
+    return Ok(None);
+}
+
+/// Whether the SignerIdentifier `sid` designates the certificate.
+fn identifies(sid: &Node, certificate: &x509::Fields, bytes: &[u8]) -> bool {
+    if sid.tag == Tag::SEQUENCE && sid.children.len() == 2 {
+        return sid.children[0].raw(bytes) == certificate.issuer.raw(bytes)
+            && sid.children[1].content(bytes) == certificate.serial.content(bytes);
+    }
+    if sid.tag == Tag::context_specific(0) {
+        return certificate.subject_key_identifier(bytes) == Some(sid.content(bytes));
+    }
+    return false;
+}
+
+/// Verify a SignerInfo over the encapsulated or detached content.
+fn verify_signer(node: &Node, bytes: &[u8], certificates: &[Node], content_type: &str, content: &[u8]) -> Result<()> {
+    let digest_algorithm = node.child(2, Tag::SEQUENCE)?;
+    let (digest_oid, _) = spki::algorithm(digest_algorithm, bytes)?;
+    let hash = hash::Algorithm::from_oid(&digest_oid).ok_or(Error("Unsupported digest algorithm"))?;
+    let signed_attributes = find(node, 3, Tag::context_specific(0).into_constructed());
+    let next = if signed_attributes.is_some() { 4 } else { 3 };
+    let signature_algorithm = node.child(next, Tag::SEQUENCE)?;
+    let signature = node.child(next + 1, Tag::OCTET_STRING)?.content(bytes);
+
+    // With signed attributes, the signature covers their DER encoding as a
+    // SET, and the messageDigest attribute binds them to the content.
+    let message = match signed_attributes {
+        Some(idx) => {
+            let attributes = &node.children[idx];
+            let message_digest = attribute(attributes, bytes, MESSAGE_DIGEST)?.ok_or(Error("Missing messageDigest attribute"))?;
+            if message_digest.content(bytes) != hash.digest(content).as_slice() {
+                return Err(Error("The messageDigest attribute doesn't match the content"));
+            }
+            let signed_content_type = attribute(attributes, bytes, CONTENT_TYPE)?.ok_or(Error("Missing contentType attribute"))?;
+            if oid::stringify(signed_content_type.content(bytes))? != content_type {
+                return Err(Error("The contentType attribute doesn't match the content"));
+            }
+            let mut message = attributes.raw(bytes).to_vec();
+            message[0] = der::CONSTRUCTED | 0x11;
+            message
+        }
+        None => content.to_vec(),
+    };
+
+    let sid = &node.children[1];
+    let certificate = certificates
+        .iter()
+        .filter_map(|certificate| x509::Fields::new(certificate).ok())
+        .find(|certificate| identifies(sid, certificate, bytes))
+        .ok_or(Error("The signer's certificate isn't embedded"))?;
+    let key = PublicKey::parse(certificate.spki, bytes)?;
+    return signature::verify(&key, signature_algorithm, bytes, Some(hash), &message, signature);
+}
+
+fn recipient(node: &Node, bytes: &[u8]) -> Result<Vec<String>> {
+    return match (node.tag.class, node.tag.number) {
+        (Class::Universal, _) => {
+            Ok(vec![format!("key transport to {}", identifier(node.children.get(1).ok_or(Error("Missing rid"))?, bytes)?)])
+        }
+        (Class::ContextSpecific, 1) => {
+            let keys = node.children.last().ok_or(Error("Missing recipientEncryptedKeys"))?;
+            let mut recipients = Vec::new();
+            for key in keys.children.iter() {
+                let rid = key.children.first().ok_or(Error("Missing rid"))?;
+                recipients.push(format!("key agreement with {}", identifier(rid, bytes)?));
+            }
+            Ok(recipients)
+        }
+        (Class::ContextSpecific, 2) => Ok(vec!["key encryption key".to_owned()]),
+        (Class::ContextSpecific, 3) => Ok(vec!["password".to_owned()]),
+        (Class::ContextSpecific, 4) => Ok(vec!["other recipient".to_owned()]),
+        _ => Err(Error("Unknown RecipientInfo")),
+    };
+}
+
+fn recipients(node: &Node, bytes: &[u8]) -> Result<Vec<String>> {
+    if node.tag != Tag::SET {
+        return Err(Error("RecipientInfos must be a SET"));
+    }
+    let mut recipients = Vec::new();
+    for child in node.children.iter() {
+        recipients.extend(recipient(child, bytes)?);
+    }
+    return Ok(recipients);
+}
+
+/// Decode an EncapsulatedContentInfo, or an EncryptedContentInfo, returning
+/// its type and whether the content is absent.
+fn encapsulated_content(node: &Node, bytes: &[u8]) -> Result<(String, bool)> {
+    let content_type = oid::stringify(node.child(0, Tag::OID)?.content(bytes))?;
+    return Ok((oid_names::name_or_oid(content_type), node.children.len() == 1));
+}
+
+impl Cms {
+    /// Decode a ContentInfo holding one of the CMS content types, failing if
+    /// the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        let content_type = content_type(node, bytes)?;
+        let explicit = node.child(1, Tag::context_specific(0).into_constructed())?;
+        if explicit.children.len() != 1 {
+            return Err(Error("ContentInfo must hold a single content"));
+        }
+        let content = explicit.child(0, Tag::SEQUENCE)?;
+        content.child(0, Tag::INTEGER)?;
+
+        let mut cms = Self {
+            content_type: oid_names::name_or_oid(content_type.clone()),
+            inner_content_type: None,
+            detached: false,
+            certificates: Vec::new(),
+            crls: 0,
+            signers: Vec::new(),
+            recipients: Vec::new(),
+        };
+
+        let children = &content.children;
+        match content_type.as_str() {
+            SIGNED_DATA => {
+                content.child(1, Tag::SET)?;
+                let (inner, detached) = encapsulated_content(content.child(2, Tag::SEQUENCE)?, bytes)?;
+                cms.inner_content_type = Some(inner);
+                cms.detached = detached;
+                let mut idx = 3;
+                if let Some(found) = find(content, idx, Tag::context_specific(0).into_constructed()) {
+                    cms.certificates = certificates(&children[found], bytes);
+                    idx += 1;
+                }
+                if let Some(found) = find(content, idx, Tag::context_specific(1).into_constructed()) {
+                    cms.crls = children[found].children.len();
+                    idx += 1;
+                }
+                for signer_info in content.child(idx, Tag::SET)?.children.iter() {
+                    cms.signers.push(signer(signer_info, bytes)?);
+                }
+            }
+            ENVELOPED_DATA | AUTHENTICATED_DATA | AUTH_ENVELOPED_DATA => {
+                let mut idx = 1;
+                if let Some(found) = find(content, idx, Tag::context_specific(0).into_constructed()) {
+                    if let Some(certificate_set) = children[found].children.first() {
+                        cms.certificates = certificates(certificate_set, bytes);
+                    }
+                    idx += 1;
+                }
+                cms.recipients = recipients(content.children.get(idx).ok_or(Error("Missing recipientInfos"))?, bytes)?;
+                let info = match content_type.as_str() {
+                    AUTHENTICATED_DATA => {
+                        let next = children.iter().skip(idx + 2).position(|child| child.tag == Tag::SEQUENCE);
+                        &children[idx + 2 + next.ok_or(Error("Missing encapContentInfo"))?]
+                    }
+                    _ => content.child(idx + 1, Tag::SEQUENCE)?,
+                };
+                let (inner, detached) = encapsulated_content(info, bytes)?;
+                cms.inner_content_type = Some(inner);
+                cms.detached = detached && content_type == AUTHENTICATED_DATA;
+            }
+            ENCRYPTED_DATA => {
+                let (inner, _) = encapsulated_content(content.child(1, Tag::SEQUENCE)?, bytes)?;
+                cms.inner_content_type = Some(inner);
+            }
+            DIGESTED_DATA => {
+                content.child(1, Tag::SEQUENCE)?;
+                let (inner, detached) = encapsulated_content(content.child(2, Tag::SEQUENCE)?, bytes)?;
+                cms.inner_content_type = Some(inner);
+                cms.detached = detached;
+            }
+            _ => return Err(Error("Not a CMS content type")),
+        }
+
+        return Ok(cms);
+    }
+
+    /// Verify the signature of every signer of a SignedData previously
+    /// accepted by `parse`, using `detached` as the content if it's absent.
+    pub fn verify(&mut self, node: &Node, bytes: &[u8], detached: Option<&[u8]>) -> Result<()> {
+        if content_type(node, bytes)? != SIGNED_DATA {
+            return Err(Error("Only SignedData can be verified"));
+        }
+        let signed_data = node.child(1, Tag::context_specific(0).into_constructed())?.child(0, Tag::SEQUENCE)?;
+        let encapsulated = signed_data.child(2, Tag::SEQUENCE)?;
+        let inner_content_type = oid::stringify(encapsulated.child(0, Tag::OID)?.content(bytes))?;
+        let content = match (encapsulated.children.get(1), detached) {
+            (Some(explicit), _) => octets(explicit.children.first().ok_or(Error("Missing eContent"))?, bytes),
+            (None, Some(detached)) => detached.to_vec(),
+            (None, None) => return Err(Error("The content is detached")),
+        };
+
+        let mut idx = 3;
+        let mut certificates: &[Node] = &[];
+        if let Some(found) = find(signed_data, idx, Tag::context_specific(0).into_constructed()) {
+            certificates = &signed_data.children[found].children;
+            idx += 1;
+        }
+        if find(signed_data, idx, Tag::context_specific(1).into_constructed()).is_some() {
+            idx += 1;
+        }
+        let signer_infos = signed_data.child(idx, Tag::SET)?;
+        for (signer, signer_info) in self.signers.iter_mut().zip(signer_infos.children.iter()) {
+            signer.verification = Some(verify_signer(signer_info, bytes, certificates, &inner_content_type, &content));
+        }
+        return Ok(());
+    }
+
+    /// Name every field of a node previously accepted by `parse`.
+    pub fn annotate(node: &mut Node, bytes: &[u8]) {
+        let content_type = content_type(node, bytes).unwrap_or_default();
+        let content = match annotate_content_info(node, bytes) {
+            Some(content) => content,
+            None => return,
+        };
+
+        match content_type.as_str() {
+            SIGNED_DATA => annotate_signed_data(content, bytes),
+            ENVELOPED_DATA => annotate_enveloped_data(content, bytes, &["originatorInfo", "recipientInfos", "encryptedContentInfo"]),
+            AUTH_ENVELOPED_DATA => {
+                annotate_enveloped_data(content, bytes, &["originatorInfo", "recipientInfos", "authEncryptedContentInfo"]);
+            }
+            ENCRYPTED_DATA => {
+                content.set_field("encryptedData");
+                content.set_child_field(0, "version");
+                if let Some(info) = content.set_child_field(1, "encryptedContentInfo") {
+                    annotate_encrypted_content_info(info);
+                }
+                if let Some(attributes) = content.set_child_field(2, "unprotectedAttrs") {
+                    annotate_attributes(attributes, bytes);
+                }
+            }
+            DIGESTED_DATA => {
+                content.set_field("digestedData");
+                content.set_child_field(0, "version");
+                content.set_child_field(1, "digestAlgorithm");
+                if let Some(info) = content.set_child_field(2, "encapContentInfo") {
+                    annotate_encapsulated_content_info(info);
+                }
+                content.set_child_field(3, "digest");
+            }
+            AUTHENTICATED_DATA => annotate_authenticated_data(content, bytes),
+            _ => {}
+        }
+    }
+}
+
+fn annotate_encapsulated_content_info(node: &mut Node) {
+    node.set_child_field(0, "eContentType");
+    node.set_child_field(1, "eContent");
+}
+
+pub fn annotate_encrypted_content_info(node: &mut Node) {
+    node.set_child_field(0, "contentType");
+    node.set_child_field(1, "contentEncryptionAlgorithm");
+    node.set_child_field(2, "encryptedContent");
+}
+
+/// Name the fields of the Attributes of a SET, and decode the common ones.
+pub fn annotate_attributes(node: &mut Node, bytes: &[u8]) {
+    for attribute in node.children.iter_mut() {
+        attribute.set_field("attribute");
+        let id = match attribute.child(0, Tag::OID) {
+            Ok(id) => oid::stringify(id.content(bytes)).unwrap_or_default(),
+            Err(_) => continue,
+        };
+        attribute.note = oid_names::find(&id).map(|entry| entry.name.to_string());
+        attribute.set_child_field(0, "attrType");
+        let values = match attribute.set_child_field(1, "attrValues") {
+            Some(values) => values,
+            None => continue,
+        };
+
+        for value in values.children.iter_mut() {
+            match id.as_str() {
+                "1.2.840.113549.1.9.4" => value.note = Some(hex::colon_separated(value.content(bytes))),
+                "1.2.840.113549.1.9.6" => annotate_signer_info(value, bytes),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn annotate_signer_info(node: &mut Node, bytes: &[u8]) {
+    node.set_field("signerInfo");
+    if let Some(sid) = node.children.get(1) {
+        node.note = identifier(sid, bytes).ok();
+    }
+    node.set_child_field(0, "version");
+    node.set_child_field(1, "sid");
+    node.set_child_field(2, "digestAlgorithm");
+    let has_signed_attributes = find(node, 3, Tag::context_specific(0).into_constructed()).is_some();
+    let mut idx = 3;
+    if has_signed_attributes {
+        if let Some(attributes) = node.set_child_field(3, "signedAttrs") {
+            annotate_attributes(attributes, bytes);
+        }
+        idx += 1;
+    }
+    node.set_child_field(idx, "signatureAlgorithm");
+    node.set_child_field(idx + 1, "signature");
+    if let Some(attributes) = node.set_child_field(idx + 2, "unsignedAttrs") {
+        annotate_attributes(attributes, bytes);
+    }
+}
+
+fn annotate_signed_data(node: &mut Node, bytes: &[u8]) {
+    node.set_field("signedData");
+    node.set_child_field(0, "version");
+    node.set_child_field(1, "digestAlgorithms");
+    if let Some(info) = node.set_child_field(2, "encapContentInfo") {
+        annotate_encapsulated_content_info(info);
+    }
+
+    let mut idx = 3;
+    if find(node, idx, Tag::context_specific(0).into_constructed()).is_some() {
+        node.set_child_field(idx, "certificates");
+        idx += 1;
+    }
+    if find(node, idx, Tag::context_specific(1).into_constructed()).is_some() {
+        node.set_child_field(idx, "crls");
+        idx += 1;
+    }
+    if let Some(signer_infos) = node.set_child_field(idx, "signerInfos") {
+        for signer_info in signer_infos.children.iter_mut() {
+            annotate_signer_info(signer_info, bytes);
+        }
+    }
+}
+
+fn annotate_recipient_info(node: &mut Node, bytes: &[u8]) {
+    let (field, fields): (&str, &[&str]) = match (node.tag.class, node.tag.number) {
+        (Class::Universal, _) => ("ktri", &["version", "rid", "keyEncryptionAlgorithm", "encryptedKey"]),
+        (Class::ContextSpecific, 1) => {
+            ("kari", &["version", "originator", "ukm", "keyEncryptionAlgorithm", "recipientEncryptedKeys"])
+        }
+        (Class::ContextSpecific, 2) => ("kekri", &["version", "kekid", "keyEncryptionAlgorithm", "encryptedKey"]),
+        (Class::ContextSpecific, 3) => {
+            ("pwri", &["version", "keyDerivationAlgorithm", "keyEncryptionAlgorithm", "encryptedKey"])
+        }
+        (Class::ContextSpecific, 4) => ("ori", &["oriType", "oriValue"]),
+        _ => return,
+    };
+    node.set_field(field);
+    node.note = recipient(node, bytes).ok().map(|recipients| recipients.join(", "));
+
+    let mut names = fields.iter().peekable();
+    for child in node.children.iter_mut() {
+        // Skip the names of the absent optional fields, which are context-specific.
+        if child.tag.class != Class::ContextSpecific {
+            while let Some(&&("ukm" | "keyDerivationAlgorithm")) = names.peek() {
+                names.next();
+            }
+        }
+        match names.next() {
+            Some(name) => child.set_field(name),
+            None => break,
+        }
+    }
+
+    if field == "kari" {
+        if let Some(keys) = node.children.last_mut() {
+            for key in keys.children.iter_mut() {
+                key.set_field("recipientEncryptedKey");
+                key.note = key.children.first().and_then(|rid| identifier(rid, bytes).ok());
+                key.set_child_field(0, "rid");
+                key.set_child_field(1, "encryptedKey");
+            }
+        }
+    }
+}
+
+fn annotate_enveloped_data(node: &mut Node, bytes: &[u8], fields: &[&str; 3]) {
+    node.set_field(if fields[2] == "encryptedContentInfo" { "envelopedData" } else { "authEnvelopedData" });
+    node.set_child_field(0, "version");
+    let mut idx = 1;
+    if find(node, idx, Tag::context_specific(0).into_constructed()).is_some() {
+        node.set_child_field(idx, fields[0]);
+        idx += 1;
+    }
+    if let Some(recipient_infos) = node.set_child_field(idx, fields[1]) {
+        for recipient_info in recipient_infos.children.iter_mut() {
+            annotate_recipient_info(recipient_info, bytes);
+        }
+    }
+    if let Some(info) = node.set_child_field(idx + 1, fields[2]) {
+        annotate_encrypted_content_info(info);
+    }
+
+    let names: &[&str] = if fields[2] == "encryptedContentInfo" {
+        &["unprotectedAttrs"]
+    } else {
+        &["authAttrs", "mac", "unauthAttrs"]
+    };
+    for (child, name) in node.children.iter_mut().skip(idx + 2).zip(names.iter()) {
+        child.set_field(name);
+        if child.tag.class == Class::ContextSpecific {
+            annotate_attributes(child, bytes);
+        }
+    }
+}
+
+fn annotate_authenticated_data(node: &mut Node, bytes: &[u8]) {
+    node.set_field("authenticatedData");
+    node.set_child_field(0, "version");
+    let mut sequences = 0;
+    for child in node.children.iter_mut().skip(1) {
+        let field = match (child.tag.class, child.tag.number) {
+            (Class::ContextSpecific, 0) => "originatorInfo",
+            (Class::ContextSpecific, 1) => "digestAlgorithm",
+            (Class::ContextSpecific, 2) => "authAttrs",
+            (Class::ContextSpecific, 3) => "unauthAttrs",
+            _ if child.tag == Tag::SET => "recipientInfos",
+            _ if child.tag == Tag::OCTET_STRING => "mac",
+            // The macAlgorithm comes before the encapContentInfo.
+            _ if child.tag == Tag::SEQUENCE && sequences == 0 => "macAlgorithm",
+            _ if child.tag == Tag::SEQUENCE => "encapContentInfo",
+            _ => continue,
+        };
+        child.set_field(field);
+
+        match field {
+            "recipientInfos" => {
+                for recipient_info in child.children.iter_mut() {
+                    annotate_recipient_info(recipient_info, bytes);
+                }
+            }
+            "authAttrs" | "unauthAttrs" => annotate_attributes(child, bytes),
+            "macAlgorithm" => sequences += 1,
+            "encapContentInfo" => annotate_encapsulated_content_info(child),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    #[test]
+    fn parse_signed_data() {
+        let bytes = include_bytes!("../tests/signed.p7s");
+        let (mut nodes, _) = node::parse(bytes);
+        let cms = Cms::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(cms.content_type, "signedData");
+        assert_eq!(cms.inner_content_type.as_deref(), Some("data"));
+        assert!(!cms.detached);
+        assert_eq!(cms.certificates.len(), 2);
+        assert!(cms.certificates[0].subject.starts_with("CN=Explo-DER Test Intermediate CA"));
+        assert!(cms.certificates[1].subject.starts_with("CN=www.example.com"));
+        assert_eq!(cms.signers.len(), 1);
+        assert_eq!(cms.signers[0].digest_algorithm, "sha256");
+        assert!(cms.signers[0].signing_time.is_some());
+
+        Cms::annotate(&mut nodes[0], bytes);
+        let signed_data = &nodes[0].children[1].children[0];
+        assert_eq!(signed_data.children[3].field.as_deref(), Some("certificates"));
+        let signer_info = &signed_data.children[4].children[0];
+        assert_eq!(signer_info.field.as_deref(), Some("signerInfo"));
+        assert_eq!(signer_info.children[3].field.as_deref(), Some("signedAttrs"));
+        assert_eq!(signer_info.children[5].field.as_deref(), Some("signature"));
+    }
+
+    #[test]
+    fn verify_signed_data() {
+        let bytes = include_bytes!("../tests/signed.p7s");
+        let (nodes, _) = node::parse(bytes);
+        let mut cms = Cms::parse(&nodes[0], bytes).unwrap();
+        cms.verify(&nodes[0], bytes, None).unwrap();
+        assert_eq!(cms.signers[0].verification, Some(Ok(())));
+
+        // The signature of the Ed25519 signer covers the signed attributes directly.
+        let bytes = include_bytes!("../tests/ed25519.p7s");
+        let (nodes, _) = node::parse(bytes);
+        let mut cms = Cms::parse(&nodes[0], bytes).unwrap();
+        cms.verify(&nodes[0], bytes, None).unwrap();
+        assert_eq!(cms.signers[0].verification, Some(Ok(())));
+
+        let mut tampered = bytes.to_vec();
+        let signature = nodes[0].children[1].children[0].children.last().unwrap().children[0].children.last().unwrap();
+        tampered[signature.content_offset()] ^= 1;
+        cms.verify(&nodes[0], &tampered, None).unwrap();
+        assert_eq!(cms.signers[0].verification, Some(Err(Error("Invalid signature"))));
+    }
+
+    #[test]
+    fn verify_detached_signed_data() {
+        let bytes = include_bytes!("../tests/detached.p7s");
+        let (nodes, _) = node::parse(bytes);
+        let mut cms = Cms::parse(&nodes[0], bytes).unwrap();
+        assert!(cms.detached);
+        assert!(cms.verify(&nodes[0], bytes, None).is_err());
+
+        cms.verify(&nodes[0], bytes, Some(b"Hello, CMS!\n")).unwrap();
+        assert_eq!(cms.signers[0].verification, Some(Ok(())));
+        cms.verify(&nodes[0], bytes, Some(b"Hello, CMS?\n")).unwrap();
+        let mismatch = Error("The messageDigest attribute doesn't match the content");
+        assert_eq!(cms.signers[0].verification, Some(Err(mismatch)));
+    }
+
+    #[test]
+    fn parse_enveloped_data() {
+        let bytes = include_bytes!("../tests/enveloped.p7m");
+        let (mut nodes, _) = node::parse(bytes);
+        let cms = Cms::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(cms.content_type, "envelopedData");
+        assert_eq!(cms.recipients.len(), 2);
+        assert!(cms.recipients[0].starts_with("key transport to CN=Explo-DER Test Root CA"));
+        assert!(cms.recipients[1].starts_with("key agreement with CN=Explo-DER Test Intermediate CA"));
+
+        Cms::annotate(&mut nodes[0], bytes);
+        let enveloped_data = &nodes[0].children[1].children[0];
+        let kari = &enveloped_data.children[1].children[1];
+        assert_eq!(kari.field.as_deref(), Some("kari"));
+        let fields: Vec<_> = kari.children.iter().map(|child| child.field.clone().unwrap()).collect();
+        assert_eq!(fields, vec!["version", "originator", "keyEncryptionAlgorithm", "recipientEncryptedKeys"]);
+        assert_eq!(enveloped_data.children[2].field.as_deref(), Some("encryptedContentInfo"));
+    }
+
+    #[test]
+    fn reject_pfx() {
+        let bytes = include_bytes!("../tests/no-password.pfx");
+        let (nodes, _) = node::parse(bytes);
+        assert!(Cms::parse(&nodes[0], bytes).is_err());
+    }
+}
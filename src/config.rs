@@ -0,0 +1,20 @@
+use std::path::PathBuf;
+
+/// Returns the directory in which the user configuration files are looked up.
+///
+/// That is `%APPDATA%\explo-der` on Windows, `~/Library/Application Support/explo-der`
+/// on macOS and `$XDG_CONFIG_HOME/explo-der` (or `~/.config/explo-der`) elsewhere.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+
+    return base.map(|dir| dir.join("explo-der"));
+}
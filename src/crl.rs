@@ -0,0 +1,335 @@
+use chrono::{DateTime, Utc};
+use crate::der::Tag;
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::{extension, hex, name, oid, oid_names, spki, value, x509};
+
+/// A revoked certificate.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub serial: Vec<u8>,
+    pub date: DateTime<Utc>,
+    pub reason: Option<&'static str>,
+}
+
+/// Summary of a RFC 5280 CertificateList.
+#[derive(Clone, Debug)]
+pub struct Crl {
+    pub version: u64,
+    pub signature_algorithm: String,
+    pub issuer: String,
+    /// The encoding of the issuer, to match certificates with.
+    pub issuer_der: Vec<u8>,
+    pub this_update: DateTime<Utc>,
+    pub next_update: Option<DateTime<Utc>>,
+    pub number: Option<String>,
+    /// Names of the crlExtensions, with their criticality.
+    pub extensions: Vec<String>,
+    pub entries: Vec<Entry>,
+    /// Text the table of entries is filtered with.
+    pub search: String,
+    /// Indices of the entries in the order the table is sorted by.
+    pub order: Vec<usize>,
+    /// The indices of `order` whose entry matches `search`, updated by
+    /// `filter`.
+    pub shown: Vec<usize>,
+}
+
+/// Index of every field in a TBSCertList.
+struct Layout {
+    version: Option<usize>,
+    signature: usize,
+    issuer: usize,
+    this_update: usize,
+    next_update: Option<usize>,
+    revoked_certificates: Option<usize>,
+    extensions: Option<usize>,
+}
+
+fn is_time(node: &Node) -> bool {
+    return node.tag == Tag::UTC_TIME || node.tag == Tag::GENERALIZED_TIME;
+}
+
+fn layout(tbs: &Node) -> Result<Layout> {
+    if tbs.tag != Tag::SEQUENCE {
+        return Err(Error("TBSCertList must be a SEQUENCE"));
+    }
+
+    let has_version = tbs.children.first().map(|node| node.tag) == Some(Tag::INTEGER);
+    let first = if has_version { 1 } else { 0 };
+    tbs.child(first, Tag::SEQUENCE)?;
+    tbs.child(first + 1, Tag::SEQUENCE)?;
+    if !tbs.children.get(first + 2).is_some_and(is_time) {
+        return Err(Error("Missing thisUpdate"));
+    }
+
+    let mut layout = Layout {
+        version: if has_version { Some(0) } else { None },
+        signature: first,
+        issuer: first + 1,
+        this_update: first + 2,
+        next_update: None,
+        revoked_certificates: None,
+        extensions: None,
+    };
+
+    let mut idx = first + 3;
+    if tbs.children.get(idx).is_some_and(is_time) {
+        layout.next_update = Some(idx);
+        idx += 1;
+    }
+    if tbs.children.get(idx).is_some_and(|node| node.tag == Tag::SEQUENCE) {
+        layout.revoked_certificates = Some(idx);
+        idx += 1;
+    }
+    if tbs.children.get(idx).map(|node| node.tag) == Some(Tag::context_specific(0).into_constructed()) {
+        layout.extensions = Some(idx);
+        idx += 1;
+    }
+    if idx != tbs.children.len() {
+        return Err(Error("Unexpected element in TBSCertList"));
+    }
+
+    return Ok(layout);
+}
+
+/// The OID and criticality of an Extension, and its extnValue.
+fn extension_fields<'a>(extension: &'a Node, bytes: &[u8]) -> Option<(String, bool, &'a Node)> {
+    let id = oid::stringify(extension.child(0, Tag::OID).ok()?.content(bytes)).ok()?;
+    let critical = extension.children.len() == 3 && value::boolean(extension.children[1].content(bytes)) == Ok(true);
+    return Some((id, critical, extension.children.last()?));
+}
+
+fn entry(node: &Node, bytes: &[u8]) -> Result<Entry> {
+    let (_, serial) = value::integer_magnitude(node.child(0, Tag::INTEGER)?.content(bytes))?;
+    let date = node.children.get(1).filter(|node| is_time(node)).ok_or(Error("Missing revocationDate"))?;
+
+    let mut reason = None;
+    let extensions = node.children.get(2).map(|node| node.children.as_slice()).unwrap_or_default();
+    for (id, _, extn_value) in extensions.iter().filter_map(|extension| extension_fields(extension, bytes)) {
+        if id == "2.5.29.21" {
+            let code = extn_value.children.first().filter(|node| node.tag == Tag::ENUMERATED);
+            reason = code.and_then(|code| extension::crl_reason(code.content(bytes)).ok());
+        }
+    }
+
+    return Ok(Entry { serial: serial.to_vec(), date: value::time(date.tag, date.content(bytes))?, reason });
+}
+
+impl Crl {
+    /// Decode a CertificateList, failing if the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() != 3 {
+            return Err(Error("CertificateList must be a SEQUENCE of three elements"));
+        }
+
+        let tbs = &node.children[0];
+        let layout = layout(tbs)?;
+        let (signature_algorithm, _) = spki::algorithm(node.child(1, Tag::SEQUENCE)?, bytes)?;
+        node.child(2, Tag::BIT_STRING)?;
+
+        let version = match layout.version {
+            Some(idx) => match tbs.children[idx].content(bytes) {
+                [version @ 0..=1] => u64::from(*version) + 1,
+                _ => return Err(Error("Invalid CRL version")),
+            },
+            None => 1,
+        };
+
+        let time = |idx: usize| value::time(tbs.children[idx].tag, tbs.children[idx].content(bytes));
+        let issuer = &tbs.children[layout.issuer];
+
+        let mut number = None;
+        let mut extensions = Vec::new();
+        let crl_extensions = layout.extensions.and_then(|idx| tbs.children[idx].children.first());
+        for extension in crl_extensions.map(|node| node.children.as_slice()).unwrap_or_default() {
+            let (id, critical, extn_value) = extension_fields(extension, bytes).ok_or(Error("Invalid extension"))?;
+            if id == "2.5.29.20" {
+                let crl_number = extn_value.children.first().filter(|node| node.tag == Tag::INTEGER);
+                number = crl_number.and_then(|node| value::integer(node.content(bytes)).ok());
+            }
+            let name = oid_names::name_or_oid(id);
+            extensions.push(if critical { format!("{}, critical", name) } else { name });
+        }
+
+        let revoked = layout.revoked_certificates.map(|idx| tbs.children[idx].children.as_slice()).unwrap_or_default();
+        let entries = revoked.iter().map(|node| entry(node, bytes)).collect::<Result<Vec<Entry>>>()?;
+
+        return Ok(Self {
+            version,
+            signature_algorithm: oid_names::name_or_oid(signature_algorithm),
+            issuer: name::format(issuer, bytes)?,
+            issuer_der: issuer.raw(bytes).to_vec(),
+            this_update: time(layout.this_update)?,
+            next_update: layout.next_update.map(time).transpose()?,
+            number,
+            extensions,
+            order: (0..entries.len()).collect(),
+            shown: (0..entries.len()).collect(),
+            entries,
+            search: String::new(),
+        });
+    }
+
+    /// Name every field of a node previously accepted by `parse`.
+    pub fn annotate(node: &mut Node, bytes: &[u8]) {
+        if let Some(algorithm) = node.set_child_field(1, "signatureAlgorithm") {
+            x509::annotate_algorithm(algorithm);
+        }
+        node.set_child_field(2, "signatureValue");
+
+        let tbs = match node.set_child_field(0, "tbsCertList") {
+            Some(tbs) => tbs,
+            None => return,
+        };
+
+        let layout = match layout(tbs) {
+            Ok(layout) => layout,
+            Err(_) => return,
+        };
+
+        if let Some(idx) = layout.version {
+            tbs.set_child_field(idx, "version");
+        }
+        if let Some(algorithm) = tbs.set_child_field(layout.signature, "signature") {
+            x509::annotate_algorithm(algorithm);
+        }
+        tbs.set_child_field(layout.issuer, "issuer");
+        tbs.set_child_field(layout.this_update, "thisUpdate");
+        if let Some(idx) = layout.next_update {
+            tbs.set_child_field(idx, "nextUpdate");
+        }
+        if let Some(idx) = layout.revoked_certificates {
+            if let Some(revoked) = tbs.set_child_field(idx, "revokedCertificates") {
+                for entry in revoked.children.iter_mut() {
+                    entry.set_child_field(0, "userCertificate");
+                    entry.set_child_field(1, "revocationDate");
+                    if let Some(extensions) = entry.set_child_field(2, "crlEntryExtensions") {
+                        for extension in extensions.children.iter_mut() {
+                            extension::annotate(extension, bytes);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(idx) = layout.extensions {
+            if let Some(extensions) = tbs.set_child_field(idx, "crlExtensions") {
+                for extensions in extensions.children.iter_mut() {
+                    for extension in extensions.children.iter_mut() {
+                        extension::annotate(extension, bytes);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether the certificate with the given issuer and serial number is
+    /// revoked, returning its entry. Fails if the CRL has another issuer.
+    pub fn find(&self, issuer: &Node, serial: &Node, bytes: &[u8]) -> Result<Option<&Entry>> {
+        if issuer.raw(bytes) != self.issuer_der.as_slice() {
+            return Err(Error("The CRL isn't issued by the issuer of the certificate"));
+        }
+        if serial.tag != Tag::INTEGER {
+            return Err(Error("Invalid serial number"));
+        }
+        let (_, serial) = value::integer_magnitude(serial.content(bytes))?;
+        return Ok(self.entries.iter().find(|entry| entry.serial == serial));
+    }
+
+    /// Update the entries shown after a change of the search text or of the
+    /// order. They match the search text on their hexadecimal serial number
+    /// or their reason.
+    pub fn filter(&mut self) {
+        let search = self.search.trim().to_ascii_lowercase().replace(':', "");
+        self.shown = self.order.iter().copied().filter(|idx| matches(&self.entries[*idx], &search)).collect();
+    }
+}
+
+fn matches(entry: &Entry, search: &str) -> bool {
+    if search.is_empty() {
+        return true;
+    }
+    return hex::hexlify(&entry.serial).contains(search)
+        || entry.reason.is_some_and(|reason| reason.to_ascii_lowercase().contains(search));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    #[test]
+    fn parse_and_annotate_crl() {
+        let bytes = include_bytes!("../tests/inter.crl");
+        let (mut nodes, _) = node::parse(bytes);
+        let crl = Crl::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(crl.version, 2);
+        assert_eq!(crl.issuer, "CN=Explo-DER Test Intermediate CA,OU=Issuing,O=Explo-DER Test,C=US");
+        assert_eq!(crl.signature_algorithm, "sha256WithRSAEncryption");
+        assert!(crl.next_update.is_some_and(|next_update| crl.this_update < next_update));
+        assert_eq!(crl.number.as_deref(), Some("4096"));
+        assert_eq!(crl.extensions.len(), 2);
+
+        let entries: Vec<(String, Option<&str>)> =
+            crl.entries.iter().map(|entry| (hex::hexlify(&entry.serial), entry.reason)).collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("1001".to_owned(), Some("superseded")),
+                ("2002".to_owned(), None),
+                ("0123456789abcdef0123".to_owned(), Some("keyCompromise")),
+            ]
+        );
+
+        Crl::annotate(&mut nodes[0], bytes);
+        let tbs = &nodes[0].children[0];
+        assert_eq!(tbs.field.as_deref(), Some("tbsCertList"));
+        assert_eq!(tbs.children[5].field.as_deref(), Some("revokedCertificates"));
+        let reason = &tbs.children[5].children[0].children[2].children[0].children[1].children[0];
+        assert_eq!(reason.note.as_deref(), Some("superseded"));
+    }
+
+    #[test]
+    fn filter_entries() {
+        let bytes = include_bytes!("../tests/inter.crl");
+        let (nodes, _) = node::parse(bytes);
+        let mut crl = Crl::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(crl.shown, vec![0, 1, 2]);
+
+        crl.order = vec![2, 1, 0];
+        crl.search = " 01:23 ".to_owned();
+        crl.filter();
+        assert_eq!(crl.shown, vec![2]);
+        crl.search = "SUPER".to_owned();
+        crl.filter();
+        assert_eq!(crl.shown, vec![0]);
+        crl.search.clear();
+        crl.filter();
+        assert_eq!(crl.shown, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn find_revoked_certificate() {
+        let bytes = include_bytes!("../tests/inter.crl");
+        let (nodes, _) = node::parse(bytes);
+        let crl = Crl::parse(&nodes[0], bytes).unwrap();
+
+        let leaf = include_bytes!("../tests/leaf.der");
+        let (nodes, _) = node::parse(leaf);
+        let fields = x509::Fields::new(&nodes[0]).unwrap();
+        let entry = crl.find(fields.issuer, fields.serial, leaf).unwrap().unwrap();
+        assert_eq!(entry.reason, Some("keyCompromise"));
+
+        let inter = include_bytes!("../tests/inter.der");
+        let (nodes, _) = node::parse(inter);
+        let fields = x509::Fields::new(&nodes[0]).unwrap();
+        assert!(crl.find(fields.issuer, fields.serial, inter).is_err());
+    }
+
+    #[test]
+    fn reject_certificate() {
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (nodes, _) = node::parse(bytes);
+        assert!(Crl::parse(&nodes[0], bytes).is_err());
+    }
+}
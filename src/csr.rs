@@ -0,0 +1,179 @@
+use crate::der::Tag;
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::signature::{self, PublicKey};
+use crate::{extension, name, oid, oid_names, spki, value, x509};
+
+const CHALLENGE_PASSWORD: &str = "1.2.840.113549.1.9.7";
+const EXTENSION_REQUEST: &str = "1.2.840.113549.1.9.14";
+
+/// Summary of a PKCS#10 CertificationRequest.
+#[derive(Clone, Debug)]
+pub struct Csr {
+    pub version: u64,
+    pub subject: String,
+    pub public_key: String,
+    pub signature_algorithm: String,
+    pub challenge_password: Option<String>,
+    /// Names of the requested extensions, with their criticality.
+    pub extensions: Vec<String>,
+    /// Names of the other attributes.
+    pub attributes: Vec<String>,
+    /// Whether the request is signed by the key it holds.
+    pub signature: Result<()>,
+}
+
+/// The CertificationRequestInfo, after checking its shape.
+fn info(node: &Node) -> Result<&Node> {
+    if node.tag != Tag::SEQUENCE || node.children.len() != 3 {
+        return Err(Error("CertificationRequest must be a SEQUENCE of three elements"));
+    }
+    let info = node.child(0, Tag::SEQUENCE)?;
+    if info.children.len() != 4 {
+        return Err(Error("CertificationRequestInfo must be a SEQUENCE of four elements"));
+    }
+    info.child(0, Tag::INTEGER)?;
+    info.child(1, Tag::SEQUENCE)?;
+    info.child(2, Tag::SEQUENCE)?;
+    info.child(3, Tag::context_specific(0).into_constructed())?;
+    return Ok(info);
+}
+
+/// The type of an Attribute and its values.
+fn attribute<'a>(node: &'a Node, bytes: &[u8]) -> Result<(String, &'a [Node])> {
+    let id = oid::stringify(node.child(0, Tag::OID)?.content(bytes))?;
+    return Ok((id, &node.child(1, Tag::SET)?.children));
+}
+
+impl Csr {
+    /// Decode a CertificationRequest and verify its signature, failing if
+    /// the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        let info = info(node)?;
+        let (signature_algorithm, _) = spki::algorithm(node.child(1, Tag::SEQUENCE)?, bytes)?;
+        node.child(2, Tag::BIT_STRING)?;
+        let version = value::unsigned(info.children[0].content(bytes))? + 1;
+        let spki = &info.children[2];
+
+        let mut challenge_password = None;
+        let mut extensions = Vec::new();
+        let mut attributes = Vec::new();
+        for child in info.children[3].children.iter() {
+            let (id, values) = attribute(child, bytes)?;
+            match (id.as_str(), values.first()) {
+                (CHALLENGE_PASSWORD, Some(password)) => {
+                    challenge_password = Some(value::string(password.tag, password.content(bytes))?);
+                }
+                (EXTENSION_REQUEST, Some(requested)) => {
+                    for extension in requested.children.iter() {
+                        let extn_id = oid::stringify(extension.child(0, Tag::OID)?.content(bytes))?;
+                        let critical = extension.children.len() == 3
+                            && value::boolean(extension.children[1].content(bytes)) == Ok(true);
+                        let name = oid_names::name_or_oid(extn_id);
+                        extensions.push(if critical { format!("{}, critical", name) } else { name });
+                    }
+                }
+                _ => attributes.push(oid_names::name_or_oid(id)),
+            }
+        }
+
+        let signature = signature::own_key(node)
+            .ok_or(Error("Missing public key"))
+            .and_then(|spki| PublicKey::parse(spki, bytes))
+            .and_then(|key| signature::verify_signed(node, bytes, &key));
+
+        return Ok(Self {
+            version,
+            subject: name::format(&info.children[1], bytes)?,
+            public_key: spki::describe(spki, bytes)?,
+            signature_algorithm: oid_names::name_or_oid(signature_algorithm),
+            challenge_password,
+            extensions,
+            attributes,
+            signature,
+        });
+    }
+
+    /// Name every field of a node previously accepted by `parse`, decoding
+    /// the requested extensions like the ones of certificates.
+    pub fn annotate(node: &mut Node, bytes: &[u8]) {
+        if let Some(algorithm) = node.set_child_field(1, "signatureAlgorithm") {
+            x509::annotate_algorithm(algorithm);
+        }
+        node.set_child_field(2, "signature");
+
+        let info = match node.set_child_field(0, "certificationRequestInfo") {
+            Some(info) => info,
+            None => return,
+        };
+        info.set_child_field(0, "version");
+        info.set_child_field(1, "subject");
+        if let Some(spki) = info.set_child_field(2, "subjectPKInfo") {
+            if let Some(algorithm) = spki.set_child_field(0, "algorithm") {
+                x509::annotate_algorithm(algorithm);
+            }
+            spki.set_child_field(1, "subjectPublicKey");
+        }
+
+        let attributes = match info.set_child_field(3, "attributes") {
+            Some(attributes) => attributes,
+            None => return,
+        };
+        for child in attributes.children.iter_mut() {
+            child.set_field("attribute");
+            let id = match attribute(child, bytes) {
+                Ok((id, _)) => id,
+                Err(_) => continue,
+            };
+            child.set_child_field(0, "type");
+            let values = child.set_child_field(1, "values");
+            if id == EXTENSION_REQUEST {
+                let requested = values.and_then(|values| values.children.first_mut()).into_iter();
+                for extension in requested.flat_map(|requested| requested.children.iter_mut()) {
+                    extension::annotate(extension, bytes);
+                }
+            }
+            child.note = Some(oid_names::name_or_oid(id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    #[test]
+    fn parse_and_annotate_request() {
+        let bytes = include_bytes!("../tests/request.csr");
+        let (mut nodes, _) = node::parse(bytes);
+        let csr = Csr::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(csr.version, 1);
+        assert_eq!(csr.subject, "O=Example\\, Inc.,CN=csr.example.com");
+        assert_eq!(csr.public_key, "RSA 2048 bits");
+        assert_eq!(csr.challenge_password.as_deref(), Some("hunter22"));
+        assert_eq!(csr.extensions, vec!["subjectAltName", "keyUsage, critical", "basicConstraints"]);
+        assert!(csr.attributes.is_empty());
+        assert_eq!(csr.signature, Ok(()));
+
+        Csr::annotate(&mut nodes[0], bytes);
+        let attributes = &nodes[0].children[0].children[3];
+        assert_eq!(attributes.field.as_deref(), Some("attributes"));
+        let extension = &attributes.children[1].children[1].children[0].children[0];
+        assert_eq!(extension.field.as_deref(), Some("extension"));
+        assert_eq!(extension.note.as_deref(), Some("subjectAltName"));
+    }
+
+    #[test]
+    fn reject_tampered_request() {
+        let mut bytes = include_bytes!("../tests/request.csr").to_vec();
+        let name = bytes.windows(3).position(|window| window == b"csr").unwrap();
+        bytes[name] = b'C';
+        let (nodes, _) = node::parse(&bytes);
+        assert!(Csr::parse(&nodes[0], &bytes).unwrap().signature.is_err());
+
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (nodes, _) = node::parse(bytes);
+        assert!(Csr::parse(&nodes[0], bytes).is_err());
+    }
+}
@@ -1,72 +1,123 @@
 use crate::error::{Error, Result};
 
+use std::fmt;
+
 pub const CONSTRUCTED: u8 = 0x20;
 pub const APPLICATION: u8 = 0x40;
 pub const CONTEXT_SPECIFIC: u8 = 0x80;
+pub const PRIVATE: u8 = 0xC0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Class {
+    Universal,
+    Application,
+    ContextSpecific,
+    Private,
+}
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-#[repr(u8)]
-pub enum Tag {
-    Boolean = 0x01,
-    Integer = 0x02,
-    BitString = 0x03,
-    OctetString = 0x04,
-    Null = 0x05,
-    OID = 0x06,
-    Utf8String = 0x0C,
-    PrintableString = 0x13,
-    UTCTime = 0x17,
-    GeneralizedTime = 0x18,
-    UniversalString = 0x1C,
-    BMPString = 0x1E,
-
-    Sequence = CONSTRUCTED | 0x10, // 0x30
-    Set = CONSTRUCTED | 0x11,      // 0x31
-
-    ContextSpecific0 = CONTEXT_SPECIFIC | 0,
-    ContextSpecific1 = CONTEXT_SPECIFIC | 1,
-
-    ContextSpecificConstructed0 = CONTEXT_SPECIFIC | CONSTRUCTED | 0,
-    ContextSpecificConstructed1 = CONTEXT_SPECIFIC | CONSTRUCTED | 1,
-    ContextSpecificConstructed2 = CONTEXT_SPECIFIC | CONSTRUCTED | 2,
-    ContextSpecificConstructed3 = CONTEXT_SPECIFIC | CONSTRUCTED | 3,
-
-    ApplicationConstructed0 = APPLICATION | CONSTRUCTED | 0,
+/// The identifier of a BER/DER element.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tag {
+    pub class: Class,
+    pub constructed: bool,
+    pub number: u32,
 }
 
-impl From<Tag> for u8 {
-    fn from(tag: Tag) -> Self {
-        return tag as Self;
+impl Tag {
+    pub const BOOLEAN: Tag = Tag::universal(0x01);
+    pub const INTEGER: Tag = Tag::universal(0x02);
+    pub const BIT_STRING: Tag = Tag::universal(0x03);
+    pub const OCTET_STRING: Tag = Tag::universal(0x04);
+    pub const NULL: Tag = Tag::universal(0x05);
+    pub const OID: Tag = Tag::universal(0x06);
+    pub const REAL: Tag = Tag::universal(0x09);
+    pub const ENUMERATED: Tag = Tag::universal(0x0A);
+    pub const UTF8_STRING: Tag = Tag::universal(0x0C);
+    pub const RELATIVE_OID: Tag = Tag::universal(0x0D);
+    pub const SEQUENCE: Tag = Tag::universal(0x10).into_constructed(); // 0x30
+    pub const SET: Tag = Tag::universal(0x11).into_constructed();      // 0x31
+    pub const NUMERIC_STRING: Tag = Tag::universal(0x12);
+    pub const PRINTABLE_STRING: Tag = Tag::universal(0x13);
+    pub const T61_STRING: Tag = Tag::universal(0x14);
+    pub const VIDEOTEX_STRING: Tag = Tag::universal(0x15);
+    pub const IA5_STRING: Tag = Tag::universal(0x16);
+    pub const UTC_TIME: Tag = Tag::universal(0x17);
+    pub const GENERALIZED_TIME: Tag = Tag::universal(0x18);
+    pub const GRAPHIC_STRING: Tag = Tag::universal(0x19);
+    pub const VISIBLE_STRING: Tag = Tag::universal(0x1A);
+    pub const GENERAL_STRING: Tag = Tag::universal(0x1B);
+    pub const UNIVERSAL_STRING: Tag = Tag::universal(0x1C);
+    pub const BMP_STRING: Tag = Tag::universal(0x1E);
+
+    pub const fn universal(number: u32) -> Tag {
+        return Tag { class: Class::Universal, constructed: false, number };
+    }
+
+    pub const fn context_specific(number: u32) -> Tag {
+        return Tag { class: Class::ContextSpecific, constructed: false, number };
+    }
+
+    pub const fn into_constructed(self) -> Tag {
+        return Tag { constructed: true, ..self };
+    }
+
+    /// Returns the name of the universal type, if it's known.
+    pub fn universal_name(&self) -> Option<&'static str> {
+        if self.class != Class::Universal {
+            return None;
+        }
+
+        return match self.number {
+            0x00 => Some("EndOfContent"),
+            0x01 => Some("Boolean"),
+            0x02 => Some("Integer"),
+            0x03 => Some("BitString"),
+            0x04 => Some("OctetString"),
+            0x05 => Some("Null"),
+            0x06 => Some("OID"),
+            0x07 => Some("ObjectDescriptor"),
+            0x08 => Some("External"),
+            0x09 => Some("Real"),
+            0x0A => Some("Enumerated"),
+            0x0B => Some("EmbeddedPDV"),
+            0x0C => Some("Utf8String"),
+            0x0D => Some("RelativeOID"),
+            0x0E => Some("Time"),
+            0x10 => Some("Sequence"),
+            0x11 => Some("Set"),
+            0x12 => Some("NumericString"),
+            0x13 => Some("PrintableString"),
+            0x14 => Some("T61String"),
+            0x15 => Some("VideotexString"),
+            0x16 => Some("IA5String"),
+            0x17 => Some("UTCTime"),
+            0x18 => Some("GeneralizedTime"),
+            0x19 => Some("GraphicString"),
+            0x1A => Some("VisibleString"),
+            0x1B => Some("GeneralString"),
+            0x1C => Some("UniversalString"),
+            0x1D => Some("CharacterString"),
+            0x1E => Some("BMPString"),
+            0x1F => Some("Date"),
+            0x20 => Some("TimeOfDay"),
+            0x21 => Some("DateTime"),
+            0x22 => Some("Duration"),
+            _ => None,
+        };
     }
 }
 
-impl TryFrom<u8> for Tag {
-    type Error = Error;
-
-    fn try_from(tag: u8) -> Result<Self> {
-        return match tag {
-            x if x == Tag::Boolean as u8 => Ok(Tag::Boolean),
-            x if x == Tag::Integer as u8 => Ok(Tag::Integer),
-            x if x == Tag::BitString as u8 => Ok(Tag::BitString),
-            x if x == Tag::OctetString as u8 => Ok(Tag::OctetString),
-            x if x == Tag::Null as u8 => Ok(Tag::Null),
-            x if x == Tag::OID as u8 => Ok(Tag::OID),
-            x if x == Tag::Utf8String as u8 => Ok(Tag::Utf8String),
-            x if x == Tag::Sequence as u8 => Ok(Tag::Sequence),
-            x if x == Tag::Set as u8 => Ok(Tag::Set),
-            x if x == Tag::PrintableString as u8 => Ok(Tag::PrintableString),
-            x if x == Tag::UTCTime as u8 => Ok(Tag::UTCTime),
-            x if x == Tag::GeneralizedTime as u8 => Ok(Tag::GeneralizedTime),
-            x if x == Tag::UniversalString as u8 => Ok(Tag::UniversalString),
-            x if x == Tag::BMPString as u8 => Ok(Tag::BMPString),
-            x if x == Tag::ContextSpecific0 as u8 => Ok(Tag::ContextSpecific0),
-            x if x == Tag::ContextSpecific1 as u8 => Ok(Tag::ContextSpecific1),
-            x if x == Tag::ContextSpecificConstructed0 as u8 => Ok(Tag::ContextSpecificConstructed0),
-            x if x == Tag::ContextSpecificConstructed1 as u8 => Ok(Tag::ContextSpecificConstructed1),
-            x if x == Tag::ContextSpecificConstructed2 as u8 => Ok(Tag::ContextSpecificConstructed2),
-            x if x == Tag::ContextSpecificConstructed3 as u8 => Ok(Tag::ContextSpecificConstructed3),
-            x if x == Tag::ApplicationConstructed0 as u8 => Ok(Tag::ApplicationConstructed0),
-            _ => Err(Error("Invalid or unsupported tag")),
+impl fmt::Debug for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let constructed = if self.constructed { "Constructed" } else { "" };
+        return match self.class {
+            Class::Universal => match self.universal_name() {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "Universal{}{}", constructed, self.number),
+            },
+            Class::Application => write!(f, "Application{}{}", constructed, self.number),
+            Class::ContextSpecific => write!(f, "ContextSpecific{}{}", constructed, self.number),
+            Class::Private => write!(f, "Private{}{}", constructed, self.number),
         };
     }
 }
@@ -84,9 +135,9 @@ impl<'a> Reader<'a> {
         return Self { input, index: 0 };
     }
 
-    /// Returns the number of remaining bytes in the stream.
-    pub fn len(&self) -> usize {
-        return self.input.len() - self.index;
+    /// Returns the number of bytes consumed since the creation of the reader.
+    pub fn position(&self) -> usize {
+        return self.index;
     }
 
     /// Returns true if the input was completely read.
@@ -129,80 +180,156 @@ impl<'a> Reader<'a> {
         self.index = new_index;
         return Ok(result);
     }
-
-    /// Consume and returns the remaining bytes.
-    pub fn read_bytes_to_end(&mut self) -> &'a [u8] {
-        let result = &self.input[self.index..];
-        self.index = self.input.len();
-        return result;
-    }
-
-    /// Consume all the remaining bytes of the reader.
-    pub fn skip_to_end(&mut self) {
-        self.index = self.input.len();
-    }
-
-    /// Call the given `callback` and return the consumed bytes and the result.
-    pub fn read_and_get_bytes_read<F, R>(&mut self, callback: F) -> Result<(&'a [u8], R)>
-    where
-        F: FnOnce(&mut Reader<'a>) -> Result<R>,
-    {
-        let index_at_start = self.index;
-        let result = callback(self)?;
-        return Ok((&self.input[index_at_start..self.index], result));
-    }
-
-    /// Create a new reader from the specified input and call the given callback
-    /// with a this reader. If the reader isn't completely consumed by the
-    /// callback, the function returns an error.
-    pub fn read_all<F, R>(input: &'a [u8], callback: F) -> Result<R>
-    where
-        F: FnOnce(&mut Reader<'a>) -> Result<R>,
-    {
-        let mut reader = Reader::new(input);
-        let result = callback(&mut reader)?;
-        if reader.at_end() {
-            return Ok(result);
-        } else {
-            return Err(Error("Incomplete Read"));
-        }
-    }
 }
 
-pub fn read_tag_and_get_value<'a>(input: &mut Reader<'a>) -> Result<(Tag, &'a [u8])> {
-    let tag = input.read_byte()?;
-    if (tag & 0x1F) == 0x1F {
-        return Err(Error("High tag number form is not allowed."));
-    }
-
-    let tag = Tag::try_from(tag)?;
+/// Read the identifier octets of an element.
+pub fn read_tag(input: &mut Reader) -> Result<Tag> {
+    let byte = input.read_byte()?;
+    let class = match byte & 0xC0 {
+        0x00 => Class::Universal,
+        APPLICATION => Class::Application,
+        CONTEXT_SPECIFIC => Class::ContextSpecific,
+        _ => Class::Private,
+    };
+    let constructed = (byte & CONSTRUCTED) != 0;
 
-    // If the high order bit of the first byte is set to zero then the length
-    // is encoded in the seven remaining bits of that byte. Otherwise, those
-    // seven bits represent the number of bytes used to encode the length.
-    let length = match input.read_byte()? {
-        n if (n & 0x80) == 0 => usize::from(n),
-        0x81 => {
-            let second_byte = input.read_byte()?;
-            if second_byte < 128 {
+    // In the high tag number form, the number is encoded in base 128 in the
+    // following bytes, with the highest order bit set on every byte but the
+    // last one.
+    let number = if (byte & 0x1F) == 0x1F {
+        let mut number: u32 = 0;
+        loop {
+            let byte = input.read_byte()?;
+            if number == 0 && byte == 0x80 {
                 return Err(Error("Not the canonical encoding."));
             }
-            usize::from(second_byte)
-        }
-        0x82 => {
-            let second_byte = usize::from(input.read_byte()?);
-            let third_byte = usize::from(input.read_byte()?);
-            let combined = (second_byte << 8) | third_byte;
-            if combined < 256 {
-                return Err(Error("Not the canonical encoding."));
+            if (u32::MAX >> 7) < number {
+                return Err(Error("Tag number is too large"));
+            }
+            number = (number << 7) | u32::from(byte & 0x7F);
+            if (byte & 0x80) == 0 {
+                break;
             }
-            combined
         }
-        _ => {
-            return Err(Error("We don't support longer lengths."));
+        if number < 0x1F {
+            return Err(Error("Not the canonical encoding."));
         }
+        number
+    } else {
+        u32::from(byte & 0x1F)
     };
 
-    let inner = input.read_bytes(length)?;
-    return Ok((tag, inner));
+    return Ok(Tag { class, constructed, number });
+}
+
+/// Read the length octets of an element. `None` is returned for the BER
+/// indefinite length form.
+pub fn read_length(input: &mut Reader) -> Result<Option<usize>> {
+    // If the high order bit of the first byte is set to zero then the length
+    // is encoded in the seven remaining bits of that byte. Otherwise, those
+    // seven bits represent the number of bytes used to encode the length.
+    let first_byte = input.read_byte()?;
+    if (first_byte & 0x80) == 0 {
+        return Ok(Some(usize::from(first_byte)));
+    }
+
+    let count = usize::from(first_byte & 0x7F);
+    if count == 0 {
+        return Ok(None);
+    }
+
+    if std::mem::size_of::<usize>() < count {
+        return Err(Error("We don't support longer lengths."));
+    }
+
+    let mut length: usize = 0;
+    for byte in input.read_bytes(count)? {
+        length = (length << 8) | usize::from(*byte);
+    }
+
+    // The shortest form must be used, so no leading zero and no long form for
+    // lengths that fit in the short form.
+    if length < 128 || (length >> ((count - 1) * 8)) == 0 {
+        return Err(Error("Not the canonical encoding."));
+    }
+
+    return Ok(Some(length));
+}
+
+/// Encode an element with the given tag and content.
+pub fn encode(tag: Tag, content: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(content.len() + 8);
+    let class = match tag.class {
+        Class::Universal => 0x00,
+        Class::Application => APPLICATION,
+        Class::ContextSpecific => CONTEXT_SPECIFIC,
+        Class::Private => PRIVATE,
+    };
+    let constructed = if tag.constructed { CONSTRUCTED } else { 0 };
+
+    if tag.number < 0x1F {
+        result.push(class | constructed | (tag.number as u8));
+    } else {
+        result.push(class | constructed | 0x1F);
+        let mut shift = 28;
+        while (tag.number >> shift) == 0 {
+            shift -= 7;
+        }
+        while shift != 0 {
+            result.push((((tag.number >> shift) & 0x7F) as u8) | 0x80);
+            shift -= 7;
+        }
+        result.push((tag.number & 0x7F) as u8);
+    }
+
+    if content.len() < 128 {
+        result.push(content.len() as u8);
+    } else {
+        let bytes = content.len().to_be_bytes();
+        let skip = bytes.iter().take_while(|&&byte| byte == 0).count();
+        result.push(0x80 | ((bytes.len() - skip) as u8));
+        result.extend_from_slice(&bytes[skip..]);
+    }
+
+    result.extend_from_slice(content);
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_low_and_high_tags() {
+        assert_eq!(read_tag(&mut Reader::new(b"\x30")).unwrap(), Tag::SEQUENCE);
+        assert_eq!(read_tag(&mut Reader::new(b"\xA3")).unwrap(), Tag::context_specific(3).into_constructed());
+        assert_eq!(read_tag(&mut Reader::new(b"\x5F\x81\x00")).unwrap(), Tag { class: Class::Application, constructed: false, number: 128 });
+        assert!(read_tag(&mut Reader::new(b"\x1F\x1E")).is_err());
+        assert!(read_tag(&mut Reader::new(b"\x1F\x80\x7F")).is_err());
+    }
+
+    #[test]
+    fn read_lengths() {
+        assert_eq!(read_length(&mut Reader::new(b"\x05")).unwrap(), Some(5));
+        assert_eq!(read_length(&mut Reader::new(b"\x81\x80")).unwrap(), Some(128));
+        assert_eq!(read_length(&mut Reader::new(b"\x83\x01\x00\x00")).unwrap(), Some(65536));
+        assert_eq!(read_length(&mut Reader::new(b"\x80")).unwrap(), None);
+        assert!(read_length(&mut Reader::new(b"\x81\x05")).is_err());
+        assert!(read_length(&mut Reader::new(b"\x82\x00\xFF")).is_err());
+    }
+
+    #[test]
+    fn encode_and_read_back() {
+        assert_eq!(encode(Tag::NULL, b""), b"\x05\x00");
+        assert_eq!(encode(Tag { class: Class::Application, constructed: false, number: 128 }, b"\x01"), b"\x5F\x81\x00\x01\x01");
+
+        let content = vec![0xAB; 300];
+        let encoded = encode(Tag::SEQUENCE, &content);
+        assert_eq!(&encoded[..4], b"\x30\x82\x01\x2C");
+        let mut reader = Reader::new(&encoded);
+        assert_eq!(read_tag(&mut reader).unwrap(), Tag::SEQUENCE);
+        assert_eq!(read_length(&mut reader).unwrap(), Some(content.len()));
+        assert_eq!(reader.read_bytes(content.len()).unwrap(), content.as_slice());
+        assert!(reader.at_end());
+    }
 }
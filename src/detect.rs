@@ -0,0 +1,274 @@
+use lazy_static::lazy_static;
+use crate::der::Tag;
+use crate::node::Node;
+use crate::schema::{self, Schema};
+
+/// Minimum score of the shape applied when a document is opened.
+const MIN_SCORE: i64 = 3;
+
+/// A known structure the first element of a document is scored against.
+pub struct Shape {
+    pub name: &'static str,
+    /// Type of the built-in modules, as `Module.Type`.
+    pub type_name: &'static str,
+}
+
+pub const SHAPES: &[Shape] = &[
+    Shape { name: "Certificate", type_name: "PKIX1.Certificate" },
+    Shape { name: "CRL", type_name: "PKIX1.CertificateList" },
+    Shape { name: "CSR", type_name: "PKCS-10.CertificationRequest" },
+    Shape { name: "PKCS#12", type_name: "PKCS-12.PFX" },
+    Shape { name: "PKCS#8 private key", type_name: "PKCS-8.PrivateKeyInfo" },
+    Shape { name: "PKCS#8 encrypted private key", type_name: "PKCS-8.EncryptedPrivateKeyInfo" },
+    Shape { name: "PKCS#1 private key", type_name: "PKCS-1.RSAPrivateKey" },
+    Shape { name: "PKCS#1 public key", type_name: "PKCS-1.RSAPublicKey" },
+    Shape { name: "SEC1 private key", type_name: "SEC1.ECPrivateKey" },
+    Shape { name: "Public key", type_name: "PKIX1.SubjectPublicKeyInfo" },
+    Shape { name: "CMS", type_name: "CMS.ContentInfo" },
+    Shape { name: "OCSP request", type_name: "OCSP.OCSPRequest" },
+    Shape { name: "OCSP response", type_name: "OCSP.OCSPResponse" },
+    Shape { name: "Timestamp request", type_name: "PKIXTSP.TimeStampReq" },
+    Shape { name: "Timestamp response", type_name: "PKIXTSP.TimeStampResp" },
+    Shape { name: "TSTInfo", type_name: "PKIXTSP.TSTInfo" },
+    Shape { name: "Kerberos ticket", type_name: "KerberosV5Spec2.Ticket" },
+    Shape { name: "Kerberos AS-REQ", type_name: "KerberosV5Spec2.AS-REQ" },
+    Shape { name: "Kerberos AS-REP", type_name: "KerberosV5Spec2.AS-REP" },
+    Shape { name: "Kerberos TGS-REQ", type_name: "KerberosV5Spec2.TGS-REQ" },
+    Shape { name: "Kerberos TGS-REP", type_name: "KerberosV5Spec2.TGS-REP" },
+    Shape { name: "Kerberos AP-REQ", type_name: "KerberosV5Spec2.AP-REQ" },
+    Shape { name: "Kerberos error", type_name: "KerberosV5Spec2.KRB-ERROR" },
+    Shape { name: "LDAP message", type_name: "LDAP.LDAPMessage" },
+];
+
+lazy_static! {
+    static ref SCHEMA: Schema = {
+        let (modules, _) = schema::parse(include_str!("../resources/shapes.asn1"));
+        let mut schema = Schema::default();
+        schema.add(modules);
+        schema
+    };
+}
+
+/// Remove the names given by the structures the element was recognized as,
+/// so that only the ones of the shape are counted.
+fn strip(node: &mut Node) {
+    node.field = None;
+    node.type_name = None;
+    node.alternative = None;
+    node.implicit = None;
+    for child in node.children.iter_mut() {
+        strip(child);
+    }
+}
+
+fn count_named(node: &Node) -> i64 {
+    let named = i64::from(node.field.is_some() || node.type_name.is_some());
+    return named + node.children.iter().map(count_named).sum::<i64>();
+}
+
+/// The names of an element which applying a shape can change.
+#[derive(Clone, PartialEq)]
+struct Names {
+    field: Option<String>,
+    note: Option<String>,
+    type_name: Option<String>,
+    alternative: Option<String>,
+    implicit: Option<Tag>,
+}
+
+impl Names {
+    fn of(node: &Node) -> Self {
+        return Self {
+            field: node.field.clone(),
+            note: node.note.clone(),
+            type_name: node.type_name.clone(),
+            alternative: node.alternative.clone(),
+            implicit: node.implicit,
+        };
+    }
+
+    fn set(self, node: &mut Node) {
+        node.field = self.field;
+        node.note = self.note;
+        node.type_name = self.type_name;
+        node.alternative = self.alternative;
+        node.implicit = self.implicit;
+    }
+}
+
+/// The names of the element and its descendants, with their path from it.
+fn names(node: &Node, path: &mut Vec<usize>, names: &mut Vec<(Vec<usize>, Names)>) {
+    names.push((path.clone(), Names::of(node)));
+    for (idx, child) in node.children.iter().enumerate() {
+        path.push(idx);
+        self::names(child, path, names);
+        path.pop();
+    }
+}
+
+/// How well the element matches the shape: the number of elements the shape
+/// names, less two for every element which doesn't match.
+pub fn score(shape: &Shape, node: &Node, bytes: &[u8]) -> i64 {
+    let mut node = node.clone();
+    strip(&mut node);
+    let mismatches = SCHEMA.apply(shape.type_name, &mut node, bytes);
+    return count_named(&node) - 2 * mismatches.len() as i64;
+}
+
+/// The shapes scored for the first element of a document, and the one
+/// applied to it.
+#[derive(Default)]
+pub struct Detection {
+    /// Index in `SHAPES` and score of the shapes whose tags fit the element,
+    /// best first.
+    pub scores: Vec<(usize, i64)>,
+    pub shape: Option<usize>,
+    /// Number of elements which don't match the applied shape.
+    pub mismatches: usize,
+    /// Names the applied shape changed, as they were before it, to restore
+    /// them when another one is applied.
+    renamed: Vec<(Vec<usize>, Names)>,
+}
+
+impl Detection {
+    /// Score the element against every shape and apply the best one, unless
+    /// none matches well enough.
+    pub fn detect(node: &mut Node, bytes: &[u8]) -> Self {
+        let mut scores: Vec<(usize, i64)> = SHAPES
+            .iter()
+            .enumerate()
+            .filter(|(_, shape)| SCHEMA.fits(shape.type_name, node))
+            .map(|(idx, shape)| (idx, score(shape, node, bytes)))
+            .collect();
+        scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        let best = scores.first().filter(|(_, score)| MIN_SCORE <= *score).map(|(idx, _)| *idx);
+
+        let mut detection = Self { scores, ..Self::default() };
+        detection.apply(best, node, bytes);
+        return detection;
+    }
+
+    /// Name the element after another shape, or after none, restoring first
+    /// the names the applied one changed.
+    pub fn apply(&mut self, shape: Option<usize>, node: &mut Node, bytes: &[u8]) {
+        for (path, names) in self.renamed.drain(..) {
+            if let Some(target) = path.iter().try_fold(&mut *node, |node, idx| node.children.get_mut(*idx)) {
+                names.set(target);
+            }
+        }
+        self.shape = shape;
+        self.mismatches = 0;
+        let shape = match shape {
+            Some(shape) => shape,
+            None => return,
+        };
+
+        let mut before = Vec::new();
+        names(node, &mut Vec::new(), &mut before);
+        self.mismatches = SCHEMA.apply(SHAPES[shape].type_name, node, bytes).len();
+        let mut after = Vec::new();
+        names(node, &mut Vec::new(), &mut after);
+        self.renamed =
+            before.into_iter().zip(after).filter(|(before, after)| before != after).map(|(before, _)| before).collect();
+    }
+
+    pub fn name(&self) -> Option<&'static str> {
+        return self.shape.map(|shape| SHAPES[shape].name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    fn detect(bytes: &[u8]) -> (Detection, Node) {
+        let (mut nodes, _) = node::parse(bytes);
+        let detection = Detection::detect(&mut nodes[0], bytes);
+        return (detection, nodes.swap_remove(0));
+    }
+
+    #[test]
+    fn builtin_modules() {
+        let (modules, errors) = schema::parse(include_str!("../resources/shapes.asn1"));
+        assert_eq!(errors, Vec::<String>::new());
+        for shape in SHAPES {
+            let (module_name, name) = shape.type_name.split_once('.').unwrap();
+            let module = modules.iter().find(|module| module.name == module_name).unwrap();
+            assert!(module.types.iter().any(|(type_name, _)| type_name == name), "{}", shape.type_name);
+        }
+    }
+
+    #[test]
+    fn detect_files() {
+        let files: [(&[u8], &str); 16] = [
+            (include_bytes!("../tests/leaf.der"), "Certificate"),
+            (include_bytes!("../tests/inter.crl"), "CRL"),
+            (include_bytes!("../tests/leaf.csr"), "CSR"),
+            (include_bytes!("../tests/leaf.p12"), "PKCS#12"),
+            (include_bytes!("../tests/leaf.p8"), "PKCS#8 private key"),
+            (include_bytes!("../tests/leaf-pbes2.p8"), "PKCS#8 encrypted private key"),
+            (include_bytes!("../tests/rsa-pkcs1.der"), "PKCS#1 private key"),
+            (include_bytes!("../tests/ec-sec1.der"), "SEC1 private key"),
+            (include_bytes!("../tests/rsa-public.der"), "PKCS#1 public key"),
+            (include_bytes!("../tests/signed.p7s"), "CMS"),
+            (include_bytes!("../tests/ocsp-request.der"), "OCSP request"),
+            (include_bytes!("../tests/ocsp-response.der"), "OCSP response"),
+            (include_bytes!("../tests/timestamp.tsq"), "Timestamp request"),
+            (include_bytes!("../tests/timestamp.tsr"), "Timestamp response"),
+            (include_bytes!("../tests/kerberos-ticket.der"), "Kerberos ticket"),
+            (include_bytes!("../tests/ldap-search.der"), "LDAP message"),
+        ];
+        for (bytes, name) in files {
+            let (detection, _) = detect(bytes);
+            assert_eq!(detection.name(), Some(name));
+            assert_eq!(detection.mismatches, 0, "{}", name);
+        }
+
+        let (detection, _) = detect(&[0x30, 0x03, 0x01, 0x01, 0xFF]);
+        assert_eq!(detection.name(), None);
+    }
+
+    #[test]
+    fn name_and_override() {
+        let bytes = include_bytes!("../tests/ldap-search.der");
+        let (mut detection, mut node) = detect(bytes);
+        assert_eq!(node.type_name.as_deref(), Some("LDAP.LDAPMessage"));
+        let request = &node.children[1];
+        assert_eq!(request.field.as_deref(), Some("protocolOp"));
+        assert_eq!(request.note.as_deref(), Some("searchRequest"));
+        assert_eq!(request.children[1].note.as_deref(), Some("wholeSubtree"));
+        assert_eq!(request.children[6].note.as_deref(), Some("and"));
+        assert_eq!(request.children[6].children[1].field.as_deref(), Some("substrings"));
+
+        detection.apply(Some(0), &mut node, bytes);
+        assert_eq!(detection.name(), Some("Certificate"));
+        assert_ne!(detection.mismatches, 0);
+        detection.apply(None, &mut node, bytes);
+        assert_eq!(node.type_name, None);
+        let request = &node.children[1];
+        assert_eq!((request.field.as_ref(), request.note.as_ref()), (None, None));
+        assert_eq!(request.children[1].note, None);
+        assert_eq!(request.children[6].children[1].field, None);
+
+        // The names given before the shape are restored.
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (mut nodes, _) = node::parse(bytes);
+        let analyzed = Some("analyzed".to_owned());
+        nodes[0].children[0].field = analyzed.clone();
+        nodes[0].children[0].children[1].note = analyzed.clone();
+        let mut detection = Detection::detect(&mut nodes[0], bytes);
+        detection.apply(Some(SHAPES.len() - 1), &mut nodes[0], bytes);
+        detection.apply(None, &mut nodes[0], bytes);
+        assert_eq!(nodes[0].children[0].field, analyzed);
+        assert_eq!(nodes[0].children[0].children[1].note, analyzed);
+        assert_eq!(nodes[0].children[0].children[2].field, None);
+    }
+
+    #[test]
+    fn filter_shapes_on_tags() {
+        let (detection, _) = detect(include_bytes!("../tests/leaf.der"));
+        assert!(detection.scores.len() < SHAPES.len());
+        assert!(!detection.scores.iter().any(|(shape, _)| SHAPES[*shape].name == "Kerberos ticket"));
+    }
+}
@@ -0,0 +1,323 @@
+use std::path::{Path, PathBuf};
+use crate::cms::Cms;
+use crate::crl::Crl;
+use crate::csr::Csr;
+use crate::detect::Detection;
+use crate::error::{Error, Result};
+use crate::key::{Format, Key};
+use crate::node::{self, Node};
+use crate::ocsp::{OcspRequest, OcspResponse};
+use crate::pbe;
+use crate::pem;
+use crate::pkcs12::Pfx;
+use crate::spki::Spki;
+use crate::tsp::{TimeStampReq, TimeStampResp, TstInfo};
+use crate::x509::Certificate;
+
+/// Highest iteration count of the MAC and of the encrypted contents of the
+/// PKCS#12 files the empty password is tried on when they are opened. The
+/// others stay locked until the user enters a password, so that opening a
+/// file never takes long.
+const MAX_OPEN_ITERATIONS: u64 = 10_000;
+
+/// A known structure recognized in a document.
+pub enum Kind {
+    Certificate(Certificate),
+    Crl(Crl),
+    Csr(Csr),
+    Pfx(Pfx),
+    Cms(Cms),
+    OcspRequest(OcspRequest),
+    OcspResponse(OcspResponse),
+    TimeStampReq(TimeStampReq),
+    TimeStampResp(TimeStampResp),
+    TstInfo(TstInfo),
+    Key(Key),
+    PublicKey(Spki),
+}
+
+pub struct Structure {
+    /// Path of the node the structure was recognized on.
+    pub path: Vec<usize>,
+    pub kind: Kind,
+}
+
+/// A file loaded in the application, with its parsed elements.
+pub struct Document {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    /// The DER the elements are parsed from, followed by the decrypted
+    /// plaintexts.
+    pub bytes: Vec<u8>,
+    /// The file as loaded, when it was PEM and `bytes` holds its decoded DER.
+    pem: Option<Vec<u8>>,
+    /// Length of the DER at the start of `bytes`.
+    len: usize,
+    pub nodes: Vec<Node>,
+    /// Error, and its offset, that stopped the parsing of the top-level elements.
+    pub error: Option<(usize, Error)>,
+    pub structures: Vec<Structure>,
+    /// Shape detected for the first element, or chosen by the user.
+    pub detection: Detection,
+    /// Incremented whenever the elements may have been renamed or replaced.
+    pub revision: usize,
+}
+
+impl Document {
+    pub fn from_bytes(name: String, bytes: Vec<u8>) -> Self {
+        let (bytes, pem) = match pem::decode(&bytes) {
+            Some(der) => (der, Some(bytes)),
+            None => (bytes, None),
+        };
+        let (mut nodes, error) = node::parse(&bytes);
+        let mut structures = Vec::new();
+        analyze(&mut nodes, &bytes, &mut Vec::new(), &mut structures);
+        let len = bytes.len();
+        let detection = Detection::default();
+        let mut document = Self { name, path: None, bytes, pem, len, nodes, error, structures, detection, revision: 0 };
+
+        // Many PKCS#12 files are protected by an empty password.
+        let paths: Vec<Vec<usize>> = document
+            .structures
+            .iter()
+            .filter(|structure| match &structure.kind {
+                Kind::Pfx(pfx) => pfx.mac.as_ref().is_some_and(|mac| mac.iterations <= MAX_OPEN_ITERATIONS),
+                _ => false,
+            })
+            .map(|structure| structure.path.clone())
+            .collect();
+        for path in paths {
+            if let Some(idx) = document.structures.iter().position(|structure| structure.path == path) {
+                let _ = document.decrypt_within(idx, "", MAX_OPEN_ITERATIONS);
+            }
+        }
+
+        if let Some(node) = document.nodes.first_mut() {
+            document.detection = Detection::detect(node, &document.bytes);
+        }
+        return document;
+    }
+
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let name = match path.file_name() {
+            Some(file_name) => file_name.to_string_lossy().into_owned(),
+            None => path.display().to_string(),
+        };
+
+        let mut document = Self::from_bytes(name, bytes);
+        document.path = Some(path.to_owned());
+        return Ok(document);
+    }
+
+    /// The bytes of the file as loaded, before PEM decoding and decryption,
+    /// which detached signatures and timestamps are computed over.
+    pub fn file(&self) -> &[u8] {
+        return match &self.pem {
+            Some(file) => file,
+            None => &self.bytes[..self.len],
+        };
+    }
+
+    /// Decrypt the PKCS#12 or the encrypted key recognized as the structure
+    /// at `idx` and recognize the structures of the plaintext.
+    pub fn decrypt(&mut self, idx: usize, password: &str) -> Result<()> {
+        return self.decrypt_within(idx, password, pbe::MAX_ITERATIONS);
+    }
+
+    /// `decrypt`, refusing PKCS#12 key derivations of more than `max`
+    /// iterations.
+    fn decrypt_within(&mut self, idx: usize, password: &str, max: u64) -> Result<()> {
+        self.revision += 1;
+        let path = self.structures[idx].path.clone();
+        let node = node_mut(&mut self.nodes, &path).ok_or(Error("Missing element"))?;
+        let result = match &mut self.structures[idx].kind {
+            Kind::Pfx(pfx) => pfx.decrypt(node, &mut self.bytes, password, max),
+            Kind::Key(key) => key.decrypt(node, &mut self.bytes, password),
+            _ => Err(Error("Only PKCS#12 files and encrypted keys can be decrypted")),
+        };
+
+        let mut found = Vec::new();
+        analyze(&mut node.children, &self.bytes, &mut path.clone(), &mut found);
+        for structure in found {
+            if !self.structures.iter().any(|known| known.path == structure.path) {
+                self.structures.push(structure);
+            }
+        }
+        self.structures.sort_by(|a, b| a.path.cmp(&b.path));
+        return result;
+    }
+
+    /// The element at the given path, as the indices of every node on the
+    /// way to it.
+    pub fn node(&self, path: &[usize]) -> Option<&Node> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.nodes.get(*first)?;
+        for idx in rest {
+            node = node.children.get(*idx)?;
+        }
+        return Some(node);
+    }
+
+    pub fn node_mut(&mut self, path: &[usize]) -> Option<&mut Node> {
+        self.revision += 1;
+        return node_mut(&mut self.nodes, path);
+    }
+
+    /// Name the first element after another shape, or after none.
+    pub fn set_shape(&mut self, shape: Option<usize>) {
+        self.revision += 1;
+        if let Some(node) = self.nodes.first_mut() {
+            self.detection.apply(shape, node, &self.bytes);
+        }
+    }
+
+    /// Verify the signatures of the SignedData or OCSP response recognized as
+    /// the structure at `idx`, with `detached` as the content if it isn't
+    /// encapsulated, and match the OCSP CertIDs with `certificates`, the DER
+    /// of the loaded certificates. The messageImprint of timestamps is
+    /// compared with the hash of `detached`.
+    pub fn verify(&mut self, idx: usize, detached: Option<&[u8]>, certificates: &[Vec<u8>]) -> Result<()> {
+        self.revision += 1;
+        let node = node_mut(&mut self.nodes, &self.structures[idx].path).ok_or(Error("Missing element"))?;
+        return match &mut self.structures[idx].kind {
+            Kind::Cms(cms) => cms.verify(node, &self.bytes, detached),
+            Kind::OcspRequest(request) => request.check(node, &self.bytes, certificates),
+            Kind::OcspResponse(response) => response.check(node, &self.bytes, certificates),
+            Kind::TimeStampReq(request) => {
+                request.check(node, &self.bytes, detached.ok_or(Error("Missing time-stamped file"))?)
+            }
+            Kind::TstInfo(info) => info.check(node, &self.bytes, detached.ok_or(Error("Missing time-stamped file"))?),
+            _ => Err(Error("Only SignedData, OCSP messages and timestamps can be verified")),
+        };
+    }
+}
+
+fn node_mut<'a>(nodes: &'a mut [Node], path: &[usize]) -> Option<&'a mut Node> {
+    let (first, rest) = path.split_first()?;
+    let node = nodes.get_mut(*first)?;
+    if rest.is_empty() {
+        return Some(node);
+    }
+    return node_mut(&mut node.children, rest);
+}
+
+/// Recognize the known structures anywhere in the tree and name their fields.
+fn analyze(nodes: &mut [Node], bytes: &[u8], path: &mut Vec<usize>, structures: &mut Vec<Structure>) {
+    for (idx, node) in nodes.iter_mut().enumerate() {
+        path.push(idx);
+        if let Ok(certificate) = Certificate::parse(node, bytes) {
+            Certificate::annotate(node, bytes, certificate.version);
+            structures.push(Structure { path: path.clone(), kind: Kind::Certificate(certificate) });
+        } else if let Ok(crl) = Crl::parse(node, bytes) {
+            Crl::annotate(node, bytes);
+            structures.push(Structure { path: path.clone(), kind: Kind::Crl(crl) });
+        } else if let Ok(csr) = Csr::parse(node, bytes) {
+            Csr::annotate(node, bytes);
+            structures.push(Structure { path: path.clone(), kind: Kind::Csr(csr) });
+        } else if let Ok(request) = OcspRequest::parse(node, bytes) {
+            OcspRequest::annotate(node, bytes);
+            structures.push(Structure { path: path.clone(), kind: Kind::OcspRequest(request) });
+        } else if let Ok(response) = OcspResponse::parse(node, bytes) {
+            OcspResponse::annotate(node, bytes);
+            structures.push(Structure { path: path.clone(), kind: Kind::OcspResponse(response) });
+        } else if let Ok(request) = TimeStampReq::parse(node, bytes) {
+            TimeStampReq::annotate(node, bytes);
+            structures.push(Structure { path: path.clone(), kind: Kind::TimeStampReq(request) });
+        } else if let Ok(response) = TimeStampResp::parse(node, bytes) {
+            TimeStampResp::annotate(node);
+            structures.push(Structure { path: path.clone(), kind: Kind::TimeStampResp(response) });
+        } else if let Ok(info) = TstInfo::parse(node, bytes) {
+            TstInfo::annotate(node, bytes);
+            structures.push(Structure { path: path.clone(), kind: Kind::TstInfo(info) });
+        } else if let Ok(mut pfx) = Pfx::parse(node, bytes) {
+            pfx.annotate(node, bytes);
+            structures.push(Structure { path: path.clone(), kind: Kind::Pfx(pfx) });
+        } else if let Ok(key) = Key::parse(node, bytes, path.len() == 1) {
+            // Keys inside other structures are only described when they're
+            // PKCS#8, like the ones of PKCS#12 bags.
+            if node.field.is_none() || key.format == Format::PrivateKeyInfo {
+                key.annotate(node, bytes);
+                structures.push(Structure { path: path.clone(), kind: Kind::Key(key) });
+            }
+        } else if let Ok(spki) = Spki::parse(node, bytes) {
+            // The keys of certificates and requests are described too, for pinning.
+            let standalone = node.field.is_none();
+            if standalone || matches!(node.field.as_deref(), Some("subjectPublicKeyInfo" | "subjectPKInfo")) {
+                if standalone {
+                    Spki::annotate(node);
+                }
+                structures.push(Structure { path: path.clone(), kind: Kind::PublicKey(spki) });
+            }
+        } else if node.field.is_none() || node.field.as_deref() == Some("timeStampToken") {
+            // The ContentInfos inside a PKCS#12 are labeled, and described by its card.
+            if let Ok(cms) = Cms::parse(node, bytes) {
+                Cms::annotate(node, bytes);
+                structures.push(Structure { path: path.clone(), kind: Kind::Cms(cms) });
+            }
+        }
+        analyze(&mut node.children, bytes, path, structures);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    fn mac_verified(document: &Document) -> bool {
+        let pfx = document.structures.iter().find_map(|structure| match &structure.kind {
+            Kind::Pfx(pfx) => Some(pfx),
+            _ => None,
+        });
+        return pfx.unwrap().mac_verified;
+    }
+
+    #[test]
+    fn try_empty_password_on_open() {
+        let mut bytes = include_bytes!("../tests/no-password.pfx").to_vec();
+        assert!(mac_verified(&Document::from_bytes("no-password.pfx".to_owned(), bytes.clone())));
+
+        // The MAC iteration count, 2048, is in the last two octets.
+        let len = bytes.len();
+        bytes[len - 2..].copy_from_slice(&[0x7F, 0xFF]);
+        assert!(!mac_verified(&Document::from_bytes("no-password.pfx".to_owned(), bytes)));
+    }
+
+    #[test]
+    fn keep_file_bytes() {
+        let der = include_bytes!("../tests/leaf.der");
+        let pem = format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", STANDARD.encode(der));
+        let document = Document::from_bytes("leaf.pem".to_owned(), pem.clone().into_bytes());
+        assert_eq!(document.bytes, der);
+        assert_eq!(document.file(), pem.as_bytes());
+
+        let bytes = include_bytes!("../tests/leaf-pbes2.p8").to_vec();
+        let mut document = Document::from_bytes("leaf-pbes2.p8".to_owned(), bytes.clone());
+        let idx = document.structures.iter().position(|structure| matches!(structure.kind, Kind::Key(_))).unwrap();
+        document.decrypt(idx, "secret").unwrap();
+        assert!(bytes.len() < document.bytes.len());
+        assert_eq!(document.file(), bytes);
+    }
+
+    #[test]
+    fn skip_expensive_contents_on_open() {
+        // A single MAC iteration, but 20000 for the certificates and the key.
+        let bytes = include_bytes!("../tests/slow-bags.pfx").to_vec();
+        let mut document = Document::from_bytes("slow-bags.pfx".to_owned(), bytes);
+        assert!(mac_verified(&document));
+        let idx = document.structures.iter().position(|structure| matches!(structure.kind, Kind::Pfx(_))).unwrap();
+        match &document.structures[idx].kind {
+            Kind::Pfx(pfx) => assert_eq!((pfx.encrypted, pfx.decrypted), (2, 0)),
+            _ => unreachable!(),
+        }
+
+        document.decrypt(idx, "").unwrap();
+        match &document.structures[idx].kind {
+            Kind::Pfx(pfx) => assert!(!pfx.needs_password()),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,670 @@
+use chrono::{offset::LocalResult, TimeZone, Utc};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use crate::der::{Class, Tag};
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::{hex, name, oid, oid_names, value};
+
+const KEY_USAGES: &[&str] = &[
+    "digitalSignature",
+    "nonRepudiation",
+    "keyEncipherment",
+    "dataEncipherment",
+    "keyAgreement",
+    "keyCertSign",
+    "cRLSign",
+    "encipherOnly",
+    "decipherOnly",
+];
+
+const REASONS: &[&str] = &[
+    "unused",
+    "keyCompromise",
+    "cACompromise",
+    "affiliationChanged",
+    "superseded",
+    "cessationOfOperation",
+    "certificateHold",
+    "privilegeWithdrawn",
+    "aACompromise",
+];
+
+/// CRLReason values, where 7 is unused.
+const CRL_REASONS: &[&str] = &[
+    "unspecified",
+    "keyCompromise",
+    "cACompromise",
+    "affiliationChanged",
+    "superseded",
+    "cessationOfOperation",
+    "certificateHold",
+    "unknown",
+    "removeFromCRL",
+    "privilegeWithdrawn",
+    "aACompromise",
+];
+
+type Decoder = fn(&mut Node, &[u8]) -> Result<()>;
+
+fn decoder(oid: &str) -> Option<Decoder> {
+    return match oid {
+        "2.5.29.14" => Some(key_identifier),
+        "2.5.29.15" => Some(key_usage),
+        "2.5.29.17" | "2.5.29.18" => Some(general_names),
+        "2.5.29.19" => Some(basic_constraints),
+        "2.5.29.20" | "2.5.29.27" => Some(crl_number),
+        "2.5.29.21" => Some(reason_code),
+        "2.5.29.24" => Some(invalidity_date),
+        "2.5.29.29" => Some(general_names),
+        "2.5.29.30" => Some(name_constraints),
+        "2.5.29.31" | "2.5.29.46" => Some(crl_distribution_points),
+        "2.5.29.32" => Some(certificate_policies),
+        "2.5.29.33" => Some(policy_mappings),
+        "2.5.29.35" => Some(authority_key_identifier),
+        "2.5.29.36" => Some(policy_constraints),
+        "2.5.29.37" => Some(extended_key_usage),
+        "2.5.29.54" => Some(skip_certs),
+        "1.3.6.1.5.5.7.1.1" | "1.3.6.1.5.5.7.1.11" => Some(access_descriptions),
+        "1.3.6.1.4.1.11129.2.4.2" => Some(sct_list),
+        "1.3.6.1.5.5.7.48.1.2" => Some(nonce),
+        _ => None,
+    };
+}
+
+/// Name the fields of an Extension and decode its value when the extension
+/// is known.
+pub fn annotate(node: &mut Node, bytes: &[u8]) {
+    node.set_field("extension");
+    let oid = match node.children.first() {
+        Some(id) if id.tag == Tag::OID => oid::stringify(id.content(bytes)).unwrap_or_default(),
+        _ => String::new(),
+    };
+    node.set_child_field(0, "extnID");
+
+    let critical = node.children.len() == 3
+        && node.children[1].tag == Tag::BOOLEAN
+        && value::boolean(node.children[1].content(bytes)) == Ok(true);
+    let name = oid_names::name_or_oid(oid.clone());
+    node.note = Some(if critical { format!("{}, critical", name) } else { name });
+
+    let extn_value = if node.children.len() == 3 {
+        node.set_child_field(1, "critical");
+        node.set_child_field(2, "extnValue")
+    } else {
+        node.set_child_field(1, "extnValue")
+    };
+
+    let extn_value = match extn_value {
+        Some(extn_value) if extn_value.tag == Tag::OCTET_STRING => extn_value,
+        _ => return,
+    };
+
+    let decode = match decoder(&oid) {
+        Some(decode) => decode,
+        None => return,
+    };
+
+    // The SCT list isn't DER, so it's decoded from the OCTET STRING itself.
+    let result = match extn_value.children.first_mut() {
+        Some(inner) if extn_value.encapsulated => decode(inner, bytes),
+        _ => decode(extn_value, bytes),
+    };
+    if let Err(err) = result {
+        extn_value.note = Some(format!("invalid: {}", err));
+    }
+}
+
+/// Names of the bits set in a named BIT STRING.
+pub fn named_bits(content: &[u8], names: &[&'static str]) -> Result<Vec<&'static str>> {
+    let (_, bits) = value::bit_string(content)?;
+    let mut set = Vec::new();
+    for (idx, byte) in bits.iter().enumerate() {
+        for bit in 0..8 {
+            if byte & (0x80 >> bit) != 0 {
+                set.push(names.get(idx * 8 + bit).copied().unwrap_or("unknown"));
+            }
+        }
+    }
+    return Ok(set);
+}
+
+fn ip_address(content: &[u8]) -> Result<String> {
+    return match content.len() {
+        4 => Ok(Ipv4Addr::from(<[u8; 4]>::try_from(content).unwrap()).to_string()),
+        16 => Ok(Ipv6Addr::from(<[u8; 16]>::try_from(content).unwrap()).to_string()),
+        _ => Err(Error("Invalid IP address length")),
+    };
+}
+
+/// Decode the address and mask of an iPAddress in name constraints.
+fn ip_address_range(content: &[u8]) -> Result<String> {
+    return match content.len() {
+        8 | 32 => {
+            let (address, mask) = content.split_at(content.len() / 2);
+            Ok(format!("{}/{}", ip_address(address)?, ip_address(mask)?))
+        }
+        _ => Err(Error("Invalid IP address range length")),
+    };
+}
+
+fn other_name(node: &mut Node, bytes: &[u8]) -> Result<String> {
+    let type_id = oid::stringify(node.child(0, Tag::OID)?.content(bytes))?;
+    let value = node.child(1, Tag::context_specific(0).into_constructed())?;
+    let inner = value.children.first().ok_or(Error("Missing otherName value"))?;
+    let decoded = if value::is_string(inner.tag) {
+        value::string(inner.tag, inner.content(bytes))?
+    } else {
+        value::truncated_hex(inner.raw(bytes), 32)
+    };
+
+    node.set_child_field(0, "type-id");
+    node.set_child_field(1, "value");
+    let name = oid_names::name_or_oid(type_id);
+    return Ok(format!("{}: {}", name, decoded));
+}
+
+/// Name and decode a GeneralName, returning its readable form.
+pub fn general_name(node: &mut Node, bytes: &[u8]) -> Result<String> {
+    return decode_general_name(node, bytes, ip_address);
+}
+
+/// Name and decode a GeneralName whose iPAddress is decoded by `ip_address`,
+/// since name constraints hold an address and a mask instead.
+fn decode_general_name(node: &mut Node, bytes: &[u8], ip_address: fn(&[u8]) -> Result<String>) -> Result<String> {
+    if node.tag.class != Class::ContextSpecific {
+        return Err(Error("GeneralName must be context-specific"));
+    }
+
+    let content = node.content(bytes);
+    let (field, decoded) = match (node.tag.number, node.tag.constructed) {
+        (0, true) => ("otherName", other_name(node, bytes)?),
+        (1, false) => ("rfc822Name", value::ascii_string(content)?),
+        (2, false) => ("dNSName", value::ascii_string(content)?),
+        (3, true) => ("x400Address", value::truncated_hex(content, 32)),
+        (4, true) => {
+            let directory_name = node.children.first().ok_or(Error("Missing directoryName"))?;
+            ("directoryName", name::format(directory_name, bytes)?)
+        }
+        (5, true) => ("ediPartyName", value::truncated_hex(content, 32)),
+        (6, false) => ("uniformResourceIdentifier", value::ascii_string(content)?),
+        (7, false) => ("iPAddress", ip_address(content)?),
+        (8, false) => ("registeredID", value::oid_with_name(content)?),
+        _ => return Err(Error("Unknown GeneralName")),
+    };
+
+    node.set_field(field);
+    node.note = Some(decoded.clone());
+    return Ok(decoded);
+}
+
+/// Name and decode every GeneralName of a GeneralNames.
+fn general_names(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    for child in node.children.iter_mut() {
+        general_name(child, bytes)?;
+    }
+    return Ok(());
+}
+
+fn key_identifier(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::OCTET_STRING {
+        return Err(Error("KeyIdentifier must be an OCTET STRING"));
+    }
+    node.set_field("keyIdentifier");
+    node.note = Some(hex::colon_separated(node.content(bytes)));
+    return Ok(());
+}
+
+fn key_usage(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::BIT_STRING {
+        return Err(Error("KeyUsage must be a BIT STRING"));
+    }
+    node.note = Some(named_bits(node.content(bytes), KEY_USAGES)?.join(", "));
+    return Ok(());
+}
+
+fn basic_constraints(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("BasicConstraints must be a SEQUENCE"));
+    }
+
+    let mut ca = false;
+    let mut path_len = None;
+    for child in node.children.iter_mut() {
+        match child.tag {
+            Tag::BOOLEAN if path_len.is_none() => {
+                ca = value::boolean(child.content(bytes))?;
+                child.set_field("cA");
+            }
+            Tag::INTEGER => {
+                path_len = Some(value::integer(child.content(bytes))?);
+                child.set_field("pathLenConstraint");
+            }
+            _ => return Err(Error("Unexpected element in BasicConstraints")),
+        }
+    }
+
+    node.note = Some(match (ca, path_len) {
+        (true, Some(path_len)) => format!("CA, path length {}", path_len),
+        (true, None) => "CA".to_owned(),
+        (false, _) => "end entity".to_owned(),
+    });
+    return Ok(());
+}
+
+fn crl_number(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::INTEGER {
+        return Err(Error("CRLNumber must be an INTEGER"));
+    }
+    node.note = Some(value::integer(node.content(bytes))?);
+    return Ok(());
+}
+
+/// The name of a CRLReason.
+pub fn crl_reason(content: &[u8]) -> Result<&'static str> {
+    let code = value::unsigned(content)? as usize;
+    return CRL_REASONS.get(code).copied().ok_or(Error("Unknown CRL reason"));
+}
+
+fn reason_code(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::ENUMERATED {
+        return Err(Error("CRLReason must be an ENUMERATED"));
+    }
+    node.note = Some(crl_reason(node.content(bytes))?.to_owned());
+    return Ok(());
+}
+
+fn invalidity_date(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::GENERALIZED_TIME {
+        return Err(Error("InvalidityDate must be a GeneralizedTime"));
+    }
+    value::time(node.tag, node.content(bytes))?;
+    return Ok(());
+}
+
+fn nonce(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::OCTET_STRING {
+        return Err(Error("The OCSP nonce must be an OCTET STRING"));
+    }
+    node.note = Some(hex::colon_separated(node.content(bytes)));
+    return Ok(());
+}
+
+fn extended_key_usage(node: &mut Node, _bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("ExtKeyUsageSyntax must be a SEQUENCE"));
+    }
+    for child in node.children.iter_mut() {
+        child.set_field("keyPurposeId");
+    }
+    return Ok(());
+}
+
+fn authority_key_identifier(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("AuthorityKeyIdentifier must be a SEQUENCE"));
+    }
+
+    for child in node.children.iter_mut() {
+        match (child.tag.number, child.tag.constructed) {
+            (0, false) => {
+                child.set_field("keyIdentifier");
+                child.note = Some(hex::colon_separated(child.content(bytes)));
+            }
+            (1, true) => {
+                child.set_field("authorityCertIssuer");
+                general_names(child, bytes)?;
+            }
+            (2, false) => {
+                child.set_field("authorityCertSerialNumber");
+                child.note = Some(hex::colon_separated(child.content(bytes)));
+            }
+            _ => return Err(Error("Unexpected element in AuthorityKeyIdentifier")),
+        }
+    }
+    return Ok(());
+}
+
+fn crl_distribution_points(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("CRLDistributionPoints must be a SEQUENCE"));
+    }
+
+    for point in node.children.iter_mut() {
+        point.set_field("distributionPoint");
+        for child in point.children.iter_mut() {
+            match (child.tag.number, child.tag.constructed) {
+                (0, true) => {
+                    child.set_field("distributionPoint");
+                    for point_name in child.children.iter_mut() {
+                        match point_name.tag.number {
+                            0 => {
+                                point_name.set_field("fullName");
+                                general_names(point_name, bytes)?;
+                            }
+                            _ => point_name.set_field("nameRelativeToCRLIssuer"),
+                        }
+                    }
+                }
+                (1, false) => {
+                    child.set_field("reasons");
+                    child.note = Some(named_bits(child.content(bytes), REASONS)?.join(", "));
+                }
+                (2, true) => {
+                    child.set_field("cRLIssuer");
+                    general_names(child, bytes)?;
+                }
+                _ => return Err(Error("Unexpected element in DistributionPoint")),
+            }
+        }
+    }
+    return Ok(());
+}
+
+fn access_descriptions(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("AuthorityInfoAccessSyntax must be a SEQUENCE"));
+    }
+
+    for description in node.children.iter_mut() {
+        description.set_field("accessDescription");
+        description.set_child_field(0, "accessMethod");
+        let location = description.set_child_field(1, "accessLocation").ok_or(Error("Missing accessLocation"))?;
+        let decoded = general_name(location, bytes)?;
+        location.set_field("accessLocation");
+        description.note = Some(decoded);
+    }
+    return Ok(());
+}
+
+/// Decode a DisplayText, which may be any of the string types.
+fn display_text(node: &Node, bytes: &[u8]) -> Result<String> {
+    return value::string(node.tag, node.content(bytes));
+}
+
+fn user_notice(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    for child in node.children.iter_mut() {
+        if child.tag == Tag::SEQUENCE {
+            child.set_field("noticeRef");
+            child.set_child_field(0, "organization");
+            child.set_child_field(1, "noticeNumbers");
+        } else {
+            child.set_field("explicitText");
+            node.note = Some(display_text(child, bytes)?);
+        }
+    }
+    return Ok(());
+}
+
+fn certificate_policies(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("CertificatePolicies must be a SEQUENCE"));
+    }
+
+    for policy in node.children.iter_mut() {
+        policy.set_field("policyInformation");
+        policy.set_child_field(0, "policyIdentifier");
+        let qualifiers = match policy.set_child_field(1, "policyQualifiers") {
+            Some(qualifiers) => qualifiers,
+            None => continue,
+        };
+
+        for info in qualifiers.children.iter_mut() {
+            info.set_field("policyQualifierInfo");
+            let id = oid::stringify(info.child(0, Tag::OID)?.content(bytes))?;
+            info.set_child_field(0, "policyQualifierId");
+            let qualifier = info.set_child_field(1, "qualifier").ok_or(Error("Missing qualifier"))?;
+            match id.as_str() {
+                "1.3.6.1.5.5.7.2.1" => qualifier.note = Some(format!("CPS: {}", value::ascii_string(qualifier.content(bytes))?)),
+                "1.3.6.1.5.5.7.2.2" => user_notice(qualifier, bytes)?,
+                _ => {}
+            }
+        }
+    }
+    return Ok(());
+}
+
+fn policy_mappings(node: &mut Node, _bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("PolicyMappings must be a SEQUENCE"));
+    }
+    for mapping in node.children.iter_mut() {
+        mapping.set_child_field(0, "issuerDomainPolicy");
+        mapping.set_child_field(1, "subjectDomainPolicy");
+    }
+    return Ok(());
+}
+
+fn general_subtrees(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    for subtree in node.children.iter_mut() {
+        subtree.set_field("generalSubtree");
+        let base = subtree.children.first_mut().ok_or(Error("Missing base"))?;
+        let decoded = decode_general_name(base, bytes, ip_address_range)?;
+        base.set_field("base");
+        subtree.note = Some(decoded);
+        for child in subtree.children.iter_mut().skip(1) {
+            match child.tag.number {
+                0 => child.set_field("minimum"),
+                _ => child.set_field("maximum"),
+            }
+            child.note = Some(value::integer(child.content(bytes))?);
+        }
+    }
+    return Ok(());
+}
+
+fn name_constraints(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("NameConstraints must be a SEQUENCE"));
+    }
+
+    for child in node.children.iter_mut() {
+        match (child.tag.number, child.tag.constructed) {
+            (0, true) => child.set_field("permittedSubtrees"),
+            (1, true) => child.set_field("excludedSubtrees"),
+            _ => return Err(Error("Unexpected element in NameConstraints")),
+        }
+        general_subtrees(child, bytes)?;
+    }
+    return Ok(());
+}
+
+fn policy_constraints(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("PolicyConstraints must be a SEQUENCE"));
+    }
+
+    for child in node.children.iter_mut() {
+        match (child.tag.number, child.tag.constructed) {
+            (0, false) => child.set_field("requireExplicitPolicy"),
+            (1, false) => child.set_field("inhibitPolicyMapping"),
+            _ => return Err(Error("Unexpected element in PolicyConstraints")),
+        }
+        child.note = Some(value::integer(child.content(bytes))?);
+    }
+    return Ok(());
+}
+
+fn skip_certs(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::INTEGER {
+        return Err(Error("SkipCerts must be an INTEGER"));
+    }
+    node.note = Some(format!("skip {} certificates", value::integer(node.content(bytes))?));
+    return Ok(());
+}
+
+/// Read a TLS vector with a 16-bit length prefix.
+fn read_vector<'a>(input: &mut &'a [u8]) -> Result<&'a [u8]> {
+    if input.len() < 2 {
+        return Err(Error("Truncated length"));
+    }
+    let len = usize::from(u16::from_be_bytes([input[0], input[1]]));
+    if input.len() < 2 + len {
+        return Err(Error("Truncated vector"));
+    }
+    let vector = &input[2..2 + len];
+    *input = &input[2 + len..];
+    return Ok(vector);
+}
+
+/// Describe one SignedCertificateTimestamp of RFC 6962.
+fn sct(mut sct: &[u8]) -> Result<String> {
+    if sct.len() < 1 + 32 + 8 {
+        return Err(Error("Truncated SCT"));
+    }
+    if sct[0] != 0 {
+        return Ok(format!("unknown SCT version {}", sct[0]));
+    }
+
+    let log_id = &sct[1..33];
+    let timestamp = i64::from_be_bytes(sct[33..41].try_into().unwrap());
+    sct = &sct[41..];
+    read_vector(&mut sct)?;
+    let algorithm = match sct {
+        [hash, signature, ..] => {
+            let hash = match hash {
+                4 => "SHA-256",
+                5 => "SHA-384",
+                6 => "SHA-512",
+                _ => "unknown hash",
+            };
+            let signature = match signature {
+                1 => "RSA",
+                3 => "ECDSA",
+                _ => "unknown signature",
+            };
+            format!("{} with {}", signature, hash)
+        }
+        _ => return Err(Error("Truncated SCT")),
+    };
+
+    let time = match Utc.timestamp_millis_opt(timestamp) {
+        LocalResult::Single(time) => time.to_string(),
+        _ => return Err(Error("Invalid SCT timestamp")),
+    };
+
+    return Ok(format!("log {} at {}, {}", value::truncated_hex(log_id, 8), time, algorithm));
+}
+
+fn sct_list(node: &mut Node, bytes: &[u8]) -> Result<()> {
+    if node.tag != Tag::OCTET_STRING {
+        return Err(Error("SignedCertificateTimestampList must be an OCTET STRING"));
+    }
+
+    let mut content = node.content(bytes);
+    let mut list = read_vector(&mut content)?;
+    let mut scts = Vec::new();
+    while !list.is_empty() {
+        scts.push(sct(read_vector(&mut list)?)?);
+    }
+    node.note = Some(format!("{} SCTs: {}", scts.len(), scts.join("; ")));
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x509::Certificate;
+    use crate::node;
+
+    fn find<'a>(nodes: &'a [Node], oid: &str) -> Option<&'a Node> {
+        for node in nodes {
+            if node.field.as_deref() == Some("extension") && node.note.as_deref().unwrap_or("").starts_with(oid) {
+                return Some(node);
+            }
+            if let Some(found) = find(&node.children, oid) {
+                return Some(found);
+            }
+        }
+        return None;
+    }
+
+    #[test]
+    fn decode_bits_and_addresses() {
+        assert_eq!(named_bits(b"\x05\xA0", KEY_USAGES).unwrap(), vec!["digitalSignature", "keyEncipherment"]);
+        assert_eq!(named_bits(b"\x07\x00\x80", KEY_USAGES).unwrap(), vec!["decipherOnly"]);
+        assert_eq!(ip_address(b"\xC0\x00\x02\x01").unwrap(), "192.0.2.1");
+        assert_eq!(ip_address(b"\x20\x01\x0D\xB8\0\0\0\0\0\0\0\0\0\0\0\x01").unwrap(), "2001:db8::1");
+        assert!(ip_address(b"\x0A\0\0\0\xFF\0\0\0").is_err());
+        assert!(ip_address(b"\x01\x02").is_err());
+        assert_eq!(ip_address_range(b"\x0A\0\0\0\xFF\0\0\0").unwrap(), "10.0.0.0/255.0.0.0");
+        assert!(ip_address_range(b"\xC0\x00\x02\x01").is_err());
+    }
+
+    #[test]
+    fn decode_certificate_extensions() {
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (mut nodes, _) = node::parse(bytes);
+        let certificate = Certificate::parse(&nodes[0], bytes).unwrap();
+        Certificate::annotate(&mut nodes[0], bytes, certificate.version);
+
+        let san = find(&nodes, "subjectAltName").unwrap();
+        let names = &san.children.last().unwrap().children[0];
+        let notes: Vec<_> = names.children.iter().map(|node| node.note.clone().unwrap()).collect();
+        assert!(notes.contains(&"www.example.com".to_owned()));
+        assert!(notes.contains(&"10.1.2.3".to_owned()));
+        assert!(notes.contains(&"userPrincipalName: admin@example.com".to_owned()));
+        assert!(notes.iter().any(|note| note.starts_with("2001:db8:")));
+
+        let key_usage = find(&nodes, "keyUsage").unwrap();
+        assert_eq!(key_usage.note.as_deref(), Some("keyUsage, critical"));
+        assert_eq!(key_usage.children[2].children[0].note.as_deref(), Some("digitalSignature"));
+
+        let basic_constraints = find(&nodes, "basicConstraints").unwrap();
+        assert_eq!(basic_constraints.children.last().unwrap().children[0].note.as_deref(), Some("end entity"));
+    }
+
+    #[test]
+    fn decode_ca_extensions() {
+        let bytes = include_bytes!("../tests/inter.der");
+        let (mut nodes, _) = node::parse(bytes);
+        let certificate = Certificate::parse(&nodes[0], bytes).unwrap();
+        Certificate::annotate(&mut nodes[0], bytes, certificate.version);
+
+        let name_constraints = find(&nodes, "nameConstraints").unwrap();
+        let subtrees = &name_constraints.children.last().unwrap().children[0];
+        assert_eq!(subtrees.children[0].field.as_deref(), Some("permittedSubtrees"));
+        let permitted: Vec<_> = subtrees.children[0].children.iter().map(|node| node.note.clone().unwrap()).collect();
+        assert_eq!(permitted, vec!["example.com", "10.0.0.0/255.0.0.0"]);
+        assert_eq!(subtrees.children[1].children[0].note.as_deref(), Some("bad.example.com"));
+
+        let policies = find(&nodes, "certificatePolicies").unwrap();
+        let qualifiers = &policies.children.last().unwrap().children[0].children[0].children[1];
+        assert_eq!(qualifiers.children[0].children[1].note.as_deref(), Some("CPS: https://pki.example.com/cps"));
+        assert_eq!(qualifiers.children[1].children[1].note.as_deref(), Some("Test policy"));
+
+        let distribution_points = find(&nodes, "cRLDistributionPoints").unwrap();
+        let full_name = &distribution_points.children.last().unwrap().children[0].children[0].children[0].children[0];
+        assert_eq!(full_name.field.as_deref(), Some("fullName"));
+        assert_eq!(full_name.children[0].note.as_deref(), Some("http://crl.example.com/root.crl"));
+
+        let access = find(&nodes, "authorityInfoAccess").unwrap();
+        let notes: Vec<_> = access.children.last().unwrap().children[0].children.iter().map(|node| node.note.clone().unwrap()).collect();
+        assert_eq!(notes, vec!["http://pki.example.com/root.cer", "http://ocsp.example.com"]);
+    }
+
+    #[test]
+    fn reject_ip_address_range_outside_name_constraints() {
+        let bytes = b"\x87\x08\x0A\0\0\0\xFF\0\0\0";
+        let (mut nodes, _) = node::parse(bytes);
+        assert!(general_name(&mut nodes[0], bytes).is_err());
+    }
+
+    #[test]
+    fn decode_sct_list() {
+        let mut sct = vec![0];
+        sct.extend([0x11; 32]);
+        sct.extend(1_700_000_000_000i64.to_be_bytes());
+        sct.extend([0x00, 0x00, 0x04, 0x03, 0x00, 0x02, 0xAA, 0xBB]);
+        let mut bytes = vec![0x30, 0x45, 0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0xD6, 0x79, 0x02, 0x04, 0x02];
+        bytes.extend([0x04, 0x37, 0x04, 0x35, 0x00, 0x33, 0x00, 0x31]);
+        bytes.extend(sct);
+
+        let (mut nodes, error) = node::parse(&bytes);
+        assert!(error.is_none());
+        annotate(&mut nodes[0], &bytes);
+        let list = &nodes[0].children[1].children[0];
+        let note = list.note.as_deref().unwrap();
+        assert!(note.starts_with("1 SCTs: log 11"), "{}", note);
+        assert!(note.ends_with("at 2023-11-14 22:13:20 UTC, ECDSA with SHA-256"), "{}", note);
+    }
+}
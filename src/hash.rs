@@ -0,0 +1,147 @@
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+use sha3::Sha3_256;
+
+/// Hash functions used by the signature and password-based schemes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_256,
+}
+
+impl Algorithm {
+    /// The hash function identified by a digest algorithm OID.
+    pub fn from_oid(oid: &str) -> Option<Self> {
+        return match oid {
+            "1.2.840.113549.2.5" => Some(Self::Md5),
+            "1.3.14.3.2.26" => Some(Self::Sha1),
+            "2.16.840.1.101.3.4.2.4" => Some(Self::Sha224),
+            "2.16.840.1.101.3.4.2.1" => Some(Self::Sha256),
+            "2.16.840.1.101.3.4.2.2" => Some(Self::Sha384),
+            "2.16.840.1.101.3.4.2.3" => Some(Self::Sha512),
+            "2.16.840.1.101.3.4.2.8" => Some(Self::Sha3_256),
+            _ => None,
+        };
+    }
+
+    /// The hash function of an HMAC algorithm OID, as used by PBKDF2.
+    pub fn from_hmac_oid(oid: &str) -> Option<Self> {
+        return match oid {
+            "1.2.840.113549.2.7" => Some(Self::Sha1),
+            "1.2.840.113549.2.8" => Some(Self::Sha224),
+            "1.2.840.113549.2.9" => Some(Self::Sha256),
+            "1.2.840.113549.2.10" => Some(Self::Sha384),
+            "1.2.840.113549.2.11" => Some(Self::Sha512),
+            "2.16.840.1.101.3.4.2.14" => Some(Self::Sha3_256),
+            _ => None,
+        };
+    }
+
+    pub fn name(self) -> &'static str {
+        return match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha224 => "SHA-224",
+            Self::Sha256 => "SHA-256",
+            Self::Sha384 => "SHA-384",
+            Self::Sha512 => "SHA-512",
+            Self::Sha3_256 => "SHA3-256",
+        };
+    }
+
+    pub fn output_size(self) -> usize {
+        return match self {
+            Self::Md5 => 16,
+            Self::Sha1 => 20,
+            Self::Sha224 => 28,
+            Self::Sha256 => 32,
+            Self::Sha384 => 48,
+            Self::Sha512 => 64,
+            Self::Sha3_256 => 32,
+        };
+    }
+
+    /// Size in bytes of the blocks the hash function processes.
+    pub fn block_size(self) -> usize {
+        return match self {
+            Self::Md5 | Self::Sha1 | Self::Sha224 | Self::Sha256 => 64,
+            Self::Sha384 | Self::Sha512 => 128,
+            Self::Sha3_256 => 136,
+        };
+    }
+
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        return match self {
+            Self::Md5 => Md5::digest(data).to_vec(),
+            Self::Sha1 => Sha1::digest(data).to_vec(),
+            Self::Sha224 => Sha224::digest(data).to_vec(),
+            Self::Sha256 => Sha256::digest(data).to_vec(),
+            Self::Sha384 => Sha384::digest(data).to_vec(),
+            Self::Sha512 => Sha512::digest(data).to_vec(),
+            Self::Sha3_256 => Sha3_256::digest(data).to_vec(),
+        };
+    }
+
+    pub fn hmac(self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        fn compute<M: Mac + hmac::digest::KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any size");
+            mac.update(data);
+            return mac.finalize().into_bytes().to_vec();
+        }
+
+        return match self {
+            Self::Md5 => compute::<Hmac<Md5>>(key, data),
+            Self::Sha1 => compute::<Hmac<Sha1>>(key, data),
+            Self::Sha224 => compute::<Hmac<Sha224>>(key, data),
+            Self::Sha256 => compute::<Hmac<Sha256>>(key, data),
+            Self::Sha384 => compute::<Hmac<Sha384>>(key, data),
+            Self::Sha512 => compute::<Hmac<Sha512>>(key, data),
+            Self::Sha3_256 => compute::<Hmac<Sha3_256>>(key, data),
+        };
+    }
+
+    /// Derive `output.len()` bytes with PBKDF2 using HMAC with this hash.
+    pub fn pbkdf2(self, password: &[u8], salt: &[u8], iterations: u32, output: &mut [u8]) {
+        match self {
+            Self::Md5 => pbkdf2::pbkdf2_hmac::<Md5>(password, salt, iterations, output),
+            Self::Sha1 => pbkdf2::pbkdf2_hmac::<Sha1>(password, salt, iterations, output),
+            Self::Sha224 => pbkdf2::pbkdf2_hmac::<Sha224>(password, salt, iterations, output),
+            Self::Sha256 => pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, iterations, output),
+            Self::Sha384 => pbkdf2::pbkdf2_hmac::<Sha384>(password, salt, iterations, output),
+            Self::Sha512 => pbkdf2::pbkdf2_hmac::<Sha512>(password, salt, iterations, output),
+            Self::Sha3_256 => pbkdf2::pbkdf2_hmac::<Sha3_256>(password, salt, iterations, output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn hmac_and_pbkdf2_vectors() {
+        // RFC 4231 test case 2.
+        let mac = Algorithm::Sha256.hmac(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(hex::hexlify(&mac), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+
+        // RFC 6070 test case 2.
+        let mut output = [0; 20];
+        Algorithm::Sha1.pbkdf2(b"password", b"salt", 2, &mut output);
+        assert_eq!(hex::hexlify(&output), "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957");
+    }
+
+    #[test]
+    fn digest_vectors() {
+        assert_eq!(hex::hexlify(&Algorithm::Md5.digest(b"abc")), "900150983cd24fb0d6963f7d28e17f72");
+        let sha3 = Algorithm::Sha3_256.digest(b"abc");
+        assert_eq!(hex::hexlify(&sha3), "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532");
+    }
+}
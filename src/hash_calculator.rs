@@ -0,0 +1,116 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use imgui::*;
+use crate::app::Selection;
+use crate::document::Document;
+use crate::hash::Algorithm;
+use crate::hex;
+
+const ALGORITHMS: [Algorithm; 6] = [
+    Algorithm::Md5,
+    Algorithm::Sha1,
+    Algorithm::Sha256,
+    Algorithm::Sha384,
+    Algorithm::Sha512,
+    Algorithm::Sha3_256,
+];
+
+/// Tool window hashing the selected element, either its full encoding or its
+/// content octets.
+pub struct HashCalculator {
+    pub opened: bool,
+    content_only: bool,
+    digests: Option<Digests>,
+}
+
+/// Digests of an element, kept until the selection or the documents change.
+struct Digests {
+    selection: Selection,
+    content_only: bool,
+    generation: usize,
+    values: Vec<(Algorithm, Vec<u8>)>,
+}
+
+impl HashCalculator {
+    pub fn new() -> Self {
+        return Self { opened: false, content_only: false, digests: None };
+    }
+
+    /// Draw the window.
+    ///
+    /// `generation` must change whenever `documents` changes.
+    pub fn draw(&mut self, ui: &Ui, documents: &[Document], selection: Option<&Selection>, generation: usize) {
+        if !self.opened {
+            return;
+        }
+
+        let mut opened = self.opened;
+        ui.window("Hash calculator")
+            .size([640.0, 260.0], Condition::FirstUseEver)
+            .opened(&mut opened)
+            .build(|| {
+                let found = selection.and_then(|selection| {
+                    let document = documents.get(selection.document)?;
+                    return Some((selection, document, document.node(&selection.path)?));
+                });
+                let (selection, document, node) = match found {
+                    Some(found) => found,
+                    None => {
+                        ui.text("Select an element in a document to hash it.");
+                        return;
+                    }
+                };
+
+                ui.text(format!("{:?} at offset {} in '{}'", node.tag, node.offset, document.name));
+                ui.radio_button("Full TLV", &mut self.content_only, false);
+                ui.same_line();
+                ui.radio_button("Content only", &mut self.content_only, true);
+
+                let outdated = match self.digests.as_ref() {
+                    Some(digests) => {
+                        digests.selection != *selection
+                            || digests.content_only != self.content_only
+                            || digests.generation != generation
+                    }
+                    None => true,
+                };
+                if outdated {
+                    let data = if self.content_only { node.content(&document.bytes) } else { node.raw(&document.bytes) };
+                    self.digests = Some(Digests {
+                        selection: selection.clone(),
+                        content_only: self.content_only,
+                        generation,
+                        values: ALGORITHMS.iter().map(|algorithm| (*algorithm, algorithm.digest(data))).collect(),
+                    });
+                }
+
+                let digests = match self.digests.as_ref() {
+                    Some(digests) => digests,
+                    None => return,
+                };
+                if let Some(_) = ui.begin_table_with_flags("digests", 3, TableFlags::BORDERS_INNER_V | TableFlags::SIZING_FIXED_FIT) {
+                    for (algorithm, digest) in digests.values.iter() {
+                        let _id = ui.push_id(algorithm.name());
+                        ui.table_next_row();
+                        ui.table_next_column();
+                        ui.text(algorithm.name());
+                        ui.table_next_column();
+                        ui.text(hex::hexlify(digest));
+                        ui.table_next_column();
+                        if ui.small_button("Hex") {
+                            ui.set_clipboard_text(hex::hexlify(digest));
+                        }
+                        ui.same_line();
+                        if ui.small_button("Colons") {
+                            ui.set_clipboard_text(hex::colon_separated(digest));
+                        }
+                        ui.same_line();
+                        if ui.small_button("Base64") {
+                            ui.set_clipboard_text(STANDARD.encode(digest));
+                        }
+                    }
+                }
+            });
+        self.opened = opened;
+    }
+}
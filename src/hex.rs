@@ -17,6 +17,16 @@ pub fn hexlify(bytes: &[u8]) -> String {
     return result;
 }
 
+/// Convert an array of bytes to uppercase hexadecimal characters separated by
+/// colons, as printed by OpenSSL for fingerprints.
+pub fn colon_separated(bytes: &[u8]) -> String {
+    return bytes
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<String>>()
+        .join(":");
+}
+
 /// Convert a string formed of lowecase hexadecimal characters to an array of bytes.
 ///
 /// If the string contains a odd number of characters, `InvalidLength` is returned.
@@ -56,6 +66,12 @@ mod tests {
         assert_eq!(hexlify(b"\x01\x11\x10\xAA\xA1\xA0\x0A"), "011110aaa1a00a");
     }
 
+    #[test]
+    fn colon_separated_all() {
+        assert_eq!(colon_separated(b""), "");
+        assert_eq!(colon_separated(b"\x01\xAB\x0a"), "01:AB:0A");
+    }
+
     #[test]
     fn unhexlify_valid() {
         assert_eq!(unhexlify("").unwrap(), b"");
@@ -0,0 +1,279 @@
+use chrono::SecondsFormat;
+use crate::der::{Class, Tag};
+use crate::document::Document;
+use crate::error::Error;
+use crate::node::Node;
+use crate::{hex, oid, oid_names, value};
+
+/// A JSON value, written with its members in order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    /// A number, already written.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<const N: usize>(members: [(&str, Json); N]) -> Self {
+        return Json::Object(members.into_iter().map(|(name, value)| (name.to_owned(), value)).collect());
+    }
+
+    pub fn string(value: impl Into<String>) -> Self {
+        return Json::String(value.into());
+    }
+
+    pub fn number(value: impl ToString) -> Self {
+        return Json::Number(value.to_string());
+    }
+
+    fn optional(value: Option<&String>) -> Self {
+        return value.map(Json::string).unwrap_or(Json::Null);
+    }
+
+    /// Write the value on several lines, indented by two spaces.
+    pub fn pretty(&self) -> String {
+        let mut output = String::new();
+        self.write(&mut output, 0);
+        output.push('\n');
+        return output;
+    }
+
+    fn write(&self, output: &mut String, depth: usize) {
+        let indent = |output: &mut String, depth: usize| {
+            output.push('\n');
+            output.push_str(&"  ".repeat(depth));
+        };
+        match self {
+            Json::Null => output.push_str("null"),
+            Json::Bool(value) => output.push_str(if *value { "true" } else { "false" }),
+            Json::Number(value) => output.push_str(value),
+            Json::String(value) => quote(output, value),
+            Json::Array(items) if items.is_empty() => output.push_str("[]"),
+            Json::Array(items) => {
+                output.push('[');
+                for (idx, item) in items.iter().enumerate() {
+                    if 0 < idx {
+                        output.push(',');
+                    }
+                    indent(output, depth + 1);
+                    item.write(output, depth + 1);
+                }
+                indent(output, depth);
+                output.push(']');
+            }
+            Json::Object(members) if members.is_empty() => output.push_str("{}"),
+            Json::Object(members) => {
+                output.push('{');
+                for (idx, (name, value)) in members.iter().enumerate() {
+                    if 0 < idx {
+                        output.push(',');
+                    }
+                    indent(output, depth + 1);
+                    quote(output, name);
+                    output.push_str(": ");
+                    value.write(output, depth + 1);
+                }
+                indent(output, depth);
+                output.push('}');
+            }
+        }
+    }
+}
+
+fn quote(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c < ' ' => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+fn error(offset: usize, err: &Error) -> Json {
+    return Json::object([("offset", Json::number(offset)), ("message", Json::string(err.to_string()))]);
+}
+
+fn tag(tag: Tag) -> Json {
+    let class = match tag.class {
+        Class::Universal => "universal",
+        Class::Application => "application",
+        Class::ContextSpecific => "context-specific",
+        Class::Private => "private",
+    };
+    return Json::object([
+        ("class", Json::string(class)),
+        ("number", Json::number(tag.number)),
+        ("constructed", Json::Bool(tag.constructed)),
+        ("name", tag.universal_name().map(Json::string).unwrap_or(Json::Null)),
+    ]);
+}
+
+/// The decoded content of a primitive element of a universal type, and its
+/// type, or None for the types which are only shown in hexadecimal.
+fn decoded(tag: Tag, content: &[u8]) -> Option<Json> {
+    let decoded = match tag {
+        Tag::BOOLEAN => {
+            value::boolean(content).map(|value| vec![("type", Json::string("boolean")), ("value", Json::Bool(value))])
+        }
+        Tag::INTEGER | Tag::ENUMERATED => value::integer(content).map(|value| {
+            // Numbers which don't fit in 64 bits are written as strings, for
+            // the tools reading them as doubles.
+            let value = match value.parse::<i64>() {
+                Ok(number) => Json::number(number),
+                Err(_) => Json::String(value),
+            };
+            let name = if tag == Tag::INTEGER { "integer" } else { "enumerated" };
+            vec![("type", Json::string(name)), ("value", value)]
+        }),
+        Tag::BIT_STRING => value::bit_string(content).map(|(unused, bytes)| {
+            vec![
+                ("type", Json::string("bit string")),
+                ("unused_bits", Json::number(unused)),
+                ("hex", Json::String(hex::hexlify(bytes))),
+            ]
+        }),
+        Tag::NULL => Ok(vec![("type", Json::string("null"))]),
+        Tag::OID => oid::stringify(content).map(|id| {
+            let name = oid_names::find(&id).map(|entry| entry.name.to_string());
+            vec![("type", Json::string("oid")), ("value", Json::String(id)), ("name", Json::optional(name.as_ref()))]
+        }),
+        Tag::UTC_TIME | Tag::GENERALIZED_TIME => value::time(tag, content).map(|time| {
+            let time = time.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+            vec![("type", Json::string("time")), ("value", Json::String(time))]
+        }),
+        tag if value::is_string(tag) => {
+            value::string(tag, content).map(|value| vec![("type", Json::string("string")), ("value", Json::String(value))])
+        }
+        _ => return None,
+    };
+    return Some(match decoded {
+        Ok(members) => Json::Object(members.into_iter().map(|(name, value)| (name.to_owned(), value)).collect()),
+        Err(err) => Json::object([("error", Json::string(err.to_string()))]),
+    });
+}
+
+/// The element and its descendants, with the content of every element that
+/// doesn't hold a private key component, or of all of them if
+/// `reveal_secrets` is set. The content of a constructed element is its
+/// encoded children.
+pub fn node(node: &Node, bytes: &[u8], reveal_secrets: bool) -> Json {
+    let mut members = vec![
+        ("tag", self::tag(node.tag)),
+        ("offset", Json::number(node.offset)),
+        ("header_length", Json::number(node.header_len)),
+        ("length", Json::number(node.len)),
+        ("indefinite", Json::Bool(node.indefinite)),
+        ("field", Json::optional(node.field.as_ref())),
+        ("type_name", Json::optional(node.type_name.as_ref())),
+        ("note", Json::optional(node.note.as_ref())),
+        ("secret", Json::Bool(node.secret)),
+    ];
+    if reveal_secrets || !node.contains_secret() {
+        let content = node.content(bytes);
+        members.push(("hex", Json::String(hex::hexlify(content))));
+        if !node.tag.constructed && node.tag.class == Class::Universal {
+            members.extend(decoded(node.tag, content).map(|value| ("value", value)));
+        }
+    }
+    members.push(("encapsulated", Json::Bool(node.encapsulated)));
+    members.push(("decrypted", Json::Bool(node.decrypted)));
+    members.push(("error", node.error.as_ref().map(|(offset, err)| error(*offset, err)).unwrap_or(Json::Null)));
+    let children = node.children.iter().map(|child| self::node(child, bytes, reveal_secrets)).collect();
+    members.push(("children", Json::Array(children)));
+    return Json::Object(members.into_iter().map(|(name, value)| (name.to_owned(), value)).collect());
+}
+
+/// The parsed tree of a document.
+pub fn document(document: &Document, reveal_secrets: bool) -> Json {
+    let nodes = document.nodes.iter().map(|node| self::node(node, &document.bytes, reveal_secrets)).collect();
+    return Json::object([
+        ("name", Json::string(&document.name)),
+        ("shape", document.detection.name().map(Json::string).unwrap_or(Json::Null)),
+        ("error", document.error.as_ref().map(|(offset, err)| error(*offset, err)).unwrap_or(Json::Null)),
+        ("nodes", Json::Array(nodes)),
+    ]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    #[test]
+    fn write_values() {
+        let json = Json::object([
+            ("text", Json::string("a \"quoted\"\\ line\n\u{1}")),
+            ("items", Json::Array(vec![Json::Null, Json::Bool(true), Json::number(-3)])),
+            ("empty", Json::Array(Vec::new())),
+            ("nested", Json::object([])),
+        ]);
+        assert_eq!(
+            json.pretty(),
+            "{\n  \"text\": \"a \\\"quoted\\\"\\\\ line\\n\\u0001\",\n  \"items\": [\n    null,\n    true,\n    -3\n  ],\n  \
+             \"empty\": [],\n  \"nested\": {}\n}\n"
+        );
+    }
+
+    fn member<'a>(json: &'a Json, name: &str) -> &'a Json {
+        return match json {
+            Json::Object(members) => &members.iter().find(|(member, _)| member == name).unwrap().1,
+            _ => panic!("not an object"),
+        };
+    }
+
+    #[test]
+    fn export_nodes() {
+        // SEQUENCE { OID rsaEncryption, INTEGER 2^64, UTF8String "é" }
+        let bytes = [
+            0x30, 0x1A, 0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01, 0x02, 0x09, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x02, 0xC3, 0xA9,
+        ];
+        let (mut nodes, _) = node::parse(&bytes);
+        nodes[0].children[2].set_field("name");
+        nodes[0].children[2].secret = true;
+        let json = node(&nodes[0], &bytes, false);
+        assert_eq!(member(member(&json, "tag"), "name"), &Json::string("Sequence"));
+        assert_eq!(member(&json, "length"), &Json::number(26));
+        assert!(!matches!(json, Json::Object(ref members) if members.iter().any(|(name, _)| name == "hex")));
+
+        let children = match member(&json, "children") {
+            Json::Array(children) => children,
+            _ => panic!("no children"),
+        };
+        let oid = member(&children[0], "value");
+        assert_eq!(member(oid, "value"), &Json::string("1.2.840.113549.1.1.1"));
+        assert_eq!(member(oid, "name"), &Json::string("rsaEncryption"));
+        assert_eq!(member(member(&children[1], "value"), "value"), &Json::string("18446744073709551616"));
+        assert_eq!(member(&children[2], "field"), &Json::string("name"));
+        assert!(matches!(&children[2], Json::Object(members) if !members.iter().any(|(name, _)| name == "value")));
+
+        assert_eq!(member(&children[0], "hex"), &Json::string("2a864886f70d010101"));
+
+        let json = node(&nodes[0], &bytes, true);
+        assert_eq!(member(&json, "hex"), &Json::string(hex::hexlify(&bytes[2..])));
+        let json = node(&nodes[0].children[2], &bytes, true);
+        assert_eq!(member(&json, "hex"), &Json::string("c3a9"));
+        assert_eq!(member(member(&json, "value"), "value"), &Json::string("é"));
+    }
+
+    #[test]
+    fn export_document() {
+        let document = Document::from_bytes("leaf.der".to_owned(), include_bytes!("../tests/leaf.der").to_vec());
+        let json = self::document(&document, false);
+        assert_eq!(member(&json, "shape"), &Json::string("Certificate"));
+        let text = json.pretty();
+        assert!(text.contains("\"field\": \"tbsCertificate\""));
+        assert!(text.contains("\"type_name\": \"PKIX1.Certificate\""));
+    }
+}
@@ -0,0 +1,404 @@
+use crate::der::{Class, Tag};
+use crate::error::{Error, Result};
+use crate::node::{self, Node};
+use crate::pbe::{self, Password};
+use crate::pkcs12::attach_plaintext;
+use crate::x509::annotate_algorithm;
+use crate::{oid_names, spki, value};
+
+const RSA_ENCRYPTION: &str = "1.2.840.113549.1.1.1";
+const RSASSA_PSS: &str = "1.2.840.113549.1.1.10";
+const EC_PUBLIC_KEY: &str = "1.2.840.10045.2.1";
+const DSA: &str = "1.2.840.10040.4.1";
+const DH: &str = "1.2.840.113549.1.3.1";
+
+/// The structures keys and their parameters are stored in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    RsaPrivateKey,
+    RsaPublicKey,
+    PrivateKeyInfo,
+    EncryptedPrivateKeyInfo,
+    EcPrivateKey,
+    DsaParameters,
+    DhParameters,
+}
+
+impl Format {
+    pub fn name(self) -> &'static str {
+        return match self {
+            Self::RsaPrivateKey => "RSAPrivateKey (PKCS#1)",
+            Self::RsaPublicKey => "RSAPublicKey (PKCS#1)",
+            Self::PrivateKeyInfo => "PrivateKeyInfo (PKCS#8)",
+            Self::EncryptedPrivateKeyInfo => "EncryptedPrivateKeyInfo (PKCS#8)",
+            Self::EcPrivateKey => "ECPrivateKey (SEC 1)",
+            Self::DsaParameters => "DSA parameters",
+            Self::DhParameters => "DH parameters (PKCS#3)",
+        };
+    }
+
+    /// Whether the structure holds private components.
+    pub fn is_private(self) -> bool {
+        return matches!(self, Self::RsaPrivateKey | Self::PrivateKeyInfo | Self::EcPrivateKey);
+    }
+}
+
+/// Summary of a key, or of domain parameters.
+#[derive(Clone, Debug)]
+pub struct Key {
+    pub format: Format,
+    /// Type and size of the key, if known.
+    pub description: Option<String>,
+    /// Password-based scheme protecting an EncryptedPrivateKeyInfo.
+    pub encryption: Option<String>,
+    pub decrypted: bool,
+}
+
+fn size(prefix: &str, node: &Node, bytes: &[u8]) -> Result<String> {
+    let (_, magnitude) = value::integer_magnitude(node.content(bytes))?;
+    return Ok(format!("{} {} bits", prefix, spki::bit_length(magnitude)));
+}
+
+/// Describe the key of a PrivateKeyInfo from its algorithm, and from the
+/// private key itself for RSA.
+fn describe_private_key_info(node: &Node, bytes: &[u8]) -> Result<String> {
+    let (algorithm, parameters) = spki::algorithm(&node.children[1], bytes)?;
+    return match algorithm.as_str() {
+        RSA_ENCRYPTION | RSASSA_PSS => {
+            let rsa_key = node.children[2].children.first().ok_or(Error("Missing RSAPrivateKey"))?;
+            size("RSA", rsa_key.child(1, Tag::INTEGER)?, bytes)
+        }
+        EC_PUBLIC_KEY => spki::describe_curve(parameters, bytes),
+        DSA => size("DSA", parameters.ok_or(Error("Missing DSA parameters"))?.child(0, Tag::INTEGER)?, bytes),
+        DH => size("DH", parameters.ok_or(Error("Missing DH parameters"))?.child(0, Tag::INTEGER)?, bytes),
+        _ => Ok(oid_names::name_or_oid(algorithm)),
+    };
+}
+
+/// Describe the scheme of an EncryptedPrivateKeyInfo, with the cipher of PBES2.
+fn describe_encryption(node: &Node, bytes: &[u8]) -> Result<String> {
+    let (algorithm, parameters) = spki::algorithm(node, bytes)?;
+    let name = oid_names::name_or_oid(algorithm.clone());
+    let cipher = parameters.and_then(|parameters| parameters.children.get(1));
+    return match cipher.map(|cipher| spki::algorithm(cipher, bytes)) {
+        Some(Ok((cipher, _))) => Ok(format!("{} with {}", name, oid_names::name_or_oid(cipher))),
+        _ => Ok(name),
+    };
+}
+
+fn has_tags(node: &Node, tags: &[Tag]) -> bool {
+    return node.children.len() >= tags.len() && node.children.iter().zip(tags.iter()).all(|(child, tag)| child.tag == *tag);
+}
+
+/// Whether every child from `idx` on is context-specific.
+fn optional_fields(node: &Node, idx: usize) -> bool {
+    return node.children.iter().skip(idx).all(|child| child.tag.class == Class::ContextSpecific);
+}
+
+impl Key {
+    fn new(format: Format, description: String) -> Self {
+        return Self { format, description: Some(description), encryption: None, decrypted: false };
+    }
+
+    /// Decode one of the key structures, failing if the node doesn't have
+    /// their shape. RSAPublicKey and the parameters are only made of INTEGERs,
+    /// so they're only recognized at the top level of a document.
+    pub fn parse(node: &Node, bytes: &[u8], top_level: bool) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.is_empty() {
+            return Err(Error("Keys must be non-empty SEQUENCEs"));
+        }
+        let children = &node.children;
+        let integers = |count| children.len() == count && children.iter().all(|child| child.tag == Tag::INTEGER);
+        let version = match children[0].tag {
+            Tag::INTEGER => value::unsigned(children[0].content(bytes)).ok(),
+            _ => None,
+        };
+
+        if (version == Some(0) && integers(9))
+            || (version == Some(1) && children.len() == 10 && has_tags(node, &[Tag::INTEGER; 9]) && children[9].tag == Tag::SEQUENCE)
+        {
+            return Ok(Self::new(Format::RsaPrivateKey, size("RSA", &children[1], bytes)?));
+        }
+
+        if version == Some(1) && children.len() <= 4 && has_tags(node, &[Tag::INTEGER, Tag::OCTET_STRING]) && optional_fields(node, 2) {
+            let parameters = children.iter().find(|child| child.tag == Tag::context_specific(0).into_constructed());
+            let description = match parameters {
+                Some(parameters) => spki::describe_curve(parameters.children.first(), bytes)?,
+                None => "EC".to_owned(),
+            };
+            return Ok(Self::new(Format::EcPrivateKey, description));
+        }
+
+        if matches!(version, Some(0 | 1))
+            && children.len() <= 5
+            && has_tags(node, &[Tag::INTEGER, Tag::SEQUENCE, Tag::OCTET_STRING])
+            && optional_fields(node, 3)
+        {
+            spki::algorithm(&children[1], bytes)?;
+            let description = describe_private_key_info(node, bytes).ok();
+            return Ok(Self { format: Format::PrivateKeyInfo, description, encryption: None, decrypted: false });
+        }
+
+        if children.len() == 2 && has_tags(node, &[Tag::SEQUENCE, Tag::OCTET_STRING]) {
+            let (algorithm, _) = spki::algorithm(&children[0], bytes)?;
+            if pbe::is_password_based(&algorithm) {
+                return Ok(Self {
+                    format: Format::EncryptedPrivateKeyInfo,
+                    description: None,
+                    encryption: Some(describe_encryption(&children[0], bytes)?),
+                    decrypted: children[1].decrypted,
+                });
+            }
+        }
+
+        if top_level && (integers(2) || integers(3)) {
+            let (_, first) = value::integer_magnitude(children[0].content(bytes))?;
+            let (_, second) = value::integer_magnitude(children[1].content(bytes))?;
+            let large = |magnitude: &[u8]| 512 <= spki::bit_length(magnitude);
+            // DH generators are small, RSA exponents are odd and DSA generators are large.
+            if children.len() == 2 && large(first) && second == [2] {
+                return Ok(Self::new(Format::DhParameters, size("DH", &children[0], bytes)?));
+            }
+            if children.len() == 2 && large(first) && first.last().is_some_and(|byte| byte & 1 == 1) {
+                return Ok(Self::new(Format::RsaPublicKey, size("RSA", &children[0], bytes)?));
+            }
+            if children.len() == 3 && large(first) {
+                let (_, generator) = value::integer_magnitude(children[2].content(bytes))?;
+                if large(generator) {
+                    return Ok(Self::new(Format::DsaParameters, size("DSA", &children[0], bytes)?));
+                }
+                return Ok(Self::new(Format::DhParameters, size("DH", &children[0], bytes)?));
+            }
+        }
+
+        return Err(Error("Not a key"));
+    }
+
+    pub fn needs_password(&self) -> bool {
+        return self.format == Format::EncryptedPrivateKeyInfo && !self.decrypted;
+    }
+
+    /// Name every field of a node previously accepted by `parse`, and mark the
+    /// private components as secret.
+    pub fn annotate(&self, node: &mut Node, bytes: &[u8]) {
+        match self.format {
+            Format::RsaPrivateKey => annotate_rsa_private_key(node),
+            Format::RsaPublicKey => {
+                node.set_child_field(0, "modulus");
+                node.set_child_field(1, "publicExponent");
+            }
+            Format::PrivateKeyInfo => annotate_private_key_info(node, bytes),
+            Format::EncryptedPrivateKeyInfo => {
+                if let Some(algorithm) = node.set_child_field(0, "encryptionAlgorithm") {
+                    annotate_algorithm(algorithm);
+                }
+                if let Some(data) = node.set_child_field(1, "encryptedData") {
+                    if data.decrypted {
+                        data.set_child_field(0, "privateKeyInfo");
+                    }
+                }
+            }
+            Format::EcPrivateKey => annotate_ec_private_key(node),
+            Format::DsaParameters => {
+                node.set_child_field(0, "p");
+                node.set_child_field(1, "q");
+                node.set_child_field(2, "g");
+            }
+            Format::DhParameters => {
+                node.set_child_field(0, "prime");
+                node.set_child_field(1, "base");
+                node.set_child_field(2, "privateValueLength");
+            }
+        }
+    }
+
+    /// Decrypt an EncryptedPrivateKeyInfo previously accepted by `parse`. The
+    /// plaintext is appended to `bytes` and its elements become the children
+    /// of the encryptedData.
+    pub fn decrypt(&mut self, node: &mut Node, bytes: &mut Vec<u8>, password: &str) -> Result<()> {
+        if self.format != Format::EncryptedPrivateKeyInfo {
+            return Err(Error("Only encrypted keys can be decrypted"));
+        }
+        if self.decrypted {
+            return Ok(());
+        }
+
+        let data = node.children[1].content(bytes);
+        let plaintext = pbe::decrypt(&node.children[0], bytes, &Password::new(password), data, pbe::MAX_ITERATIONS)?;
+        // A wrong password still produces a valid padding once in a while.
+        let (nodes, error) = node::parse(&plaintext);
+        let format = nodes.first().and_then(|key| Self::parse(key, &plaintext, false).ok()).map(|key| key.format);
+        if error.is_some() || nodes.len() != 1 || format != Some(Format::PrivateKeyInfo) {
+            return Err(Error("Wrong password or corrupted data"));
+        }
+
+        attach_plaintext(&mut node.children[1], bytes, plaintext);
+        self.decrypted = true;
+        self.annotate(node, bytes);
+        return Ok(());
+    }
+}
+
+fn annotate_rsa_private_key(node: &mut Node) {
+    let fields = [
+        "version",
+        "modulus",
+        "publicExponent",
+        "privateExponent",
+        "prime1",
+        "prime2",
+        "exponent1",
+        "exponent2",
+        "coefficient",
+        "otherPrimeInfos",
+    ];
+    for (idx, field) in fields.iter().enumerate() {
+        if let Some(child) = node.set_child_field(idx, field) {
+            if 3 <= idx {
+                child.set_secret();
+            }
+        }
+    }
+}
+
+fn annotate_ec_private_key(node: &mut Node) {
+    node.set_child_field(0, "version");
+    if let Some(private_key) = node.set_child_field(1, "privateKey") {
+        private_key.set_secret();
+    }
+    for child in node.children.iter_mut().skip(2) {
+        match child.tag.number {
+            0 => child.set_field("parameters"),
+            1 => child.set_field("publicKey"),
+            _ => {}
+        }
+    }
+}
+
+fn annotate_private_key_info(node: &mut Node, bytes: &[u8]) {
+    let algorithm = spki::algorithm(&node.children[1], bytes).map(|(algorithm, _)| algorithm).unwrap_or_default();
+    node.set_child_field(0, "version");
+    if let Some(algorithm) = node.set_child_field(1, "privateKeyAlgorithm") {
+        annotate_algorithm(algorithm);
+    }
+    for child in node.children.iter_mut().skip(3) {
+        match child.tag.number {
+            0 => child.set_field("attributes"),
+            1 => child.set_field("publicKey"),
+            _ => {}
+        }
+    }
+
+    let private_key = match node.set_child_field(2, "privateKey") {
+        Some(private_key) => private_key,
+        None => return,
+    };
+    // The encapsulated key chooses which of its components are private.
+    private_key.secret = true;
+    let inner = match private_key.children.first_mut() {
+        Some(inner) => inner,
+        None => return,
+    };
+    match algorithm.as_str() {
+        RSA_ENCRYPTION | RSASSA_PSS => {
+            inner.set_field("rsaPrivateKey");
+            annotate_rsa_private_key(inner);
+        }
+        EC_PUBLIC_KEY => {
+            inner.set_field("ecPrivateKey");
+            annotate_ec_private_key(inner);
+        }
+        DSA | DH => {
+            inner.set_field("privateValue");
+            inner.set_secret();
+        }
+        _ if inner.tag == Tag::OCTET_STRING => {
+            inner.set_field("curvePrivateKey");
+            inner.set_secret();
+        }
+        _ => inner.set_secret(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(bytes: &[u8]) -> (Vec<Node>, Key) {
+        let (nodes, _) = node::parse(bytes);
+        let key = Key::parse(&nodes[0], bytes, true).unwrap();
+        return (nodes, key);
+    }
+
+    #[test]
+    fn parse_pkcs1_and_sec1() {
+        let bytes = include_bytes!("../tests/rsa-pkcs1.der");
+        let (mut nodes, key) = parse(bytes);
+        assert_eq!(key.format, Format::RsaPrivateKey);
+        assert_eq!(key.description.as_deref(), Some("RSA 2048 bits"));
+        key.annotate(&mut nodes[0], bytes);
+        assert_eq!(nodes[0].children[1].field.as_deref(), Some("modulus"));
+        assert!(!nodes[0].children[2].secret);
+        assert!(nodes[0].children[3].secret);
+
+        let bytes = include_bytes!("../tests/rsa-public.der");
+        assert_eq!(parse(bytes).1.format, Format::RsaPublicKey);
+
+        let bytes = include_bytes!("../tests/ec-sec1.der");
+        let (_, key) = parse(bytes);
+        assert_eq!(key.format, Format::EcPrivateKey);
+        assert_eq!(key.description.as_deref(), Some("EC prime256v1 (256 bits)"));
+    }
+
+    #[test]
+    fn parse_parameters() {
+        let bytes = include_bytes!("../tests/dsa-params.der");
+        let (_, key) = parse(bytes);
+        assert_eq!(key.format, Format::DsaParameters);
+        assert_eq!(key.description.as_deref(), Some("DSA 2048 bits"));
+
+        let bytes = include_bytes!("../tests/dh-params.der");
+        let (nodes, key) = parse(bytes);
+        assert_eq!(key.format, Format::DhParameters);
+        assert!(Key::parse(&nodes[0], bytes, false).is_err());
+    }
+
+    #[test]
+    fn parse_pkcs8() {
+        let bytes = include_bytes!("../tests/leaf.p8");
+        let (mut nodes, key) = parse(bytes);
+        assert_eq!(key.format, Format::PrivateKeyInfo);
+        assert_eq!(key.description.as_deref(), Some("EC secp384r1 (384 bits)"));
+        key.annotate(&mut nodes[0], bytes);
+        let private_key = &nodes[0].children[2];
+        assert!(private_key.secret);
+        assert_eq!(private_key.children[0].field.as_deref(), Some("ecPrivateKey"));
+        assert!(private_key.children[0].children[1].secret);
+        assert_eq!(private_key.children[0].children[2].field.as_deref(), Some("publicKey"));
+        assert!(!private_key.children[0].children[2].secret);
+    }
+
+    #[test]
+    fn decrypt_pkcs8() {
+        let mut bytes = include_bytes!("../tests/leaf-pbes2.p8").to_vec();
+        let (mut nodes, mut key) = parse(&bytes);
+        assert_eq!(key.encryption.as_deref(), Some("pbes2 with aes256-CBC"));
+        assert!(key.needs_password());
+        assert!(key.decrypt(&mut nodes[0], &mut bytes, "wrong").is_err());
+        key.decrypt(&mut nodes[0], &mut bytes, "secret").unwrap();
+        let private_key_info = &nodes[0].children[1].children[0];
+        assert_eq!(private_key_info.field.as_deref(), Some("privateKeyInfo"));
+        assert_eq!(Key::parse(private_key_info, &bytes, false).unwrap().format, Format::PrivateKeyInfo);
+
+        let mut bytes = include_bytes!("../tests/inter-pbes1.p8").to_vec();
+        let (mut nodes, mut key) = parse(&bytes);
+        key.decrypt(&mut nodes[0], &mut bytes, "secret").unwrap();
+        let private_key_info = Key::parse(&nodes[0].children[1].children[0], &bytes, false).unwrap();
+        assert_eq!(private_key_info.description.as_deref(), Some("RSA 2048 bits"));
+        private_key_info.annotate(&mut nodes[0].children[1].children[0], &bytes);
+        let rsa_private_key = &nodes[0].children[1].children[0].children[2].children[0];
+        assert_eq!(rsa_private_key.children[1].field.as_deref(), Some("modulus"));
+        assert!(!rsa_private_key.children[1].secret);
+        assert!(rsa_private_key.children[3].secret);
+    }
+}
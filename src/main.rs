@@ -1,14 +1,63 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
 mod app;
+mod cards;
+mod chain;
+mod cli;
 mod clipboard;
+mod cms;
+mod config;
+mod crl;
+mod csr;
 mod der;
+mod detect;
+mod document;
 mod error;
+mod extension;
+mod hash;
+mod hash_calculator;
 mod hex;
+mod json;
+mod key;
+mod name;
+mod node;
+mod notation;
+mod notation_window;
+mod ocsp;
 mod oid;
+mod oid_browser;
 mod oid_names;
+mod path_validator;
+mod pbe;
+mod pem;
+mod pkcs12;
+mod schema;
+mod schema_window;
+mod signature;
+mod spki;
+mod tsp;
+mod value;
 mod window;
+mod x509;
 
 fn main() {
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
+    let paths: Vec<PathBuf> = args.into_iter().map(PathBuf::from).collect();
+
+    let mut app = app::App::new();
+    if paths.is_empty() {
+        app.open_sample();
+    } else {
+        for path in paths.iter() {
+            app.open_file(path);
+        }
+    }
+
     let system = window::init("Explo-DER");
-    let app = app::App::new();
     system.main_loop(app);
 }
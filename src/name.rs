@@ -0,0 +1,220 @@
+use crate::der::Tag;
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::{hex, oid, oid_names, value};
+
+/// One attribute of a relative distinguished name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attribute {
+    pub oid: String,
+    /// The decoded string, or the complete DER encoding of the value when it's
+    /// not a string.
+    pub value: std::result::Result<String, Vec<u8>>,
+}
+
+/// A relative distinguished name, usually made of a single attribute.
+pub type Rdn = Vec<Attribute>;
+
+/// Short names of the attribute types, from RFC 4514 and the ones commonly
+/// used by OpenSSL.
+pub fn short_name(oid: &str) -> Option<&'static str> {
+    return match oid {
+        "2.5.4.3" => Some("CN"),
+        "2.5.4.4" => Some("SN"),
+        "2.5.4.5" => Some("serialNumber"),
+        "2.5.4.6" => Some("C"),
+        "2.5.4.7" => Some("L"),
+        "2.5.4.8" => Some("ST"),
+        "2.5.4.9" => Some("STREET"),
+        "2.5.4.10" => Some("O"),
+        "2.5.4.11" => Some("OU"),
+        "2.5.4.12" => Some("title"),
+        "2.5.4.42" => Some("GN"),
+        "2.5.4.97" => Some("organizationIdentifier"),
+        "0.9.2342.19200300.100.1.1" => Some("UID"),
+        "0.9.2342.19200300.100.1.25" => Some("DC"),
+        "1.2.840.113549.1.9.1" => Some("emailAddress"),
+        _ => None,
+    };
+}
+
+/// Decode a Name, that is a SEQUENCE OF RelativeDistinguishedName, in the
+/// order of the encoding.
+pub fn parse(node: &Node, bytes: &[u8]) -> Result<Vec<Rdn>> {
+    if node.tag != Tag::SEQUENCE {
+        return Err(Error("Name must be a SEQUENCE"));
+    }
+
+    let mut rdns = Vec::with_capacity(node.children.len());
+    for rdn in node.children.iter() {
+        if rdn.tag != Tag::SET || rdn.children.is_empty() {
+            return Err(Error("RelativeDistinguishedName must be a non-empty SET"));
+        }
+
+        let mut attributes = Vec::with_capacity(rdn.children.len());
+        for attribute in rdn.children.iter() {
+            if attribute.tag != Tag::SEQUENCE || attribute.children.len() != 2 {
+                return Err(Error("AttributeTypeAndValue must be a SEQUENCE of two elements"));
+            }
+
+            let oid = oid::stringify(attribute.child(0, Tag::OID)?.content(bytes))?;
+            let value_node = &attribute.children[1];
+            let value = match value::string(value_node.tag, value_node.content(bytes)) {
+                Ok(value) => Ok(value),
+                Err(_) => Err(value_node.raw(bytes).to_vec()),
+            };
+            attributes.push(Attribute { oid, value });
+        }
+        rdns.push(attributes);
+    }
+
+    return Ok(rdns);
+}
+
+/// Escape an attribute value as described in RFC 4514 section 2.4.
+fn escape(value: &str, output: &mut String) {
+    let last = value.chars().count().saturating_sub(1);
+    for (idx, c) in value.chars().enumerate() {
+        match c {
+            '"' | '+' | ',' | ';' | '<' | '>' | '\\' => {
+                output.push('\\');
+                output.push(c);
+            }
+            ' ' if idx == 0 || idx == last => output.push_str("\\ "),
+            '#' if idx == 0 => output.push_str("\\#"),
+            '\0' => output.push_str("\\00"),
+            _ => output.push(c),
+        }
+    }
+}
+
+/// How to render a Name as a string.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    /// Keep the RDNs in the order of the encoding and separate them with
+    /// ", " like OpenSSL does, instead of the reversed order of RFC 4514.
+    pub openssl_order: bool,
+    /// Show every attribute of a multi-valued RDN as its own RDN instead of
+    /// joining them with '+'.
+    pub split_multi_valued: bool,
+}
+
+/// The keyword of an attribute type: its short name, its name if it's a valid
+/// RFC 4514 keystring, or its dotted form.
+fn keyword(oid: &str) -> String {
+    if let Some(name) = short_name(oid) {
+        return name.to_owned();
+    }
+
+    return match oid_names::find(oid) {
+        Some(entry) if entry.name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && entry.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') => entry.name.to_string(),
+        _ => oid.to_owned(),
+    };
+}
+
+fn format_attribute(attribute: &Attribute, output: &mut String) {
+    output.push_str(&keyword(&attribute.oid));
+    output.push('=');
+    match &attribute.value {
+        Ok(value) => escape(value, output),
+        Err(der) => {
+            output.push('#');
+            output.push_str(&hex::hexlify(der));
+        }
+    }
+}
+
+/// Format a Name in the given style.
+pub fn to_string(rdns: &[Rdn], style: Style) -> String {
+    let (rdn_separator, attribute_separator) = match (style.openssl_order, style.split_multi_valued) {
+        (false, false) => (",", "+"),
+        (false, true) => (",", ","),
+        (true, false) => (", ", " + "),
+        (true, true) => (", ", ", "),
+    };
+
+    let ordered: Vec<&Rdn> = if style.openssl_order {
+        rdns.iter().collect()
+    } else {
+        rdns.iter().rev().collect()
+    };
+
+    let mut output = String::new();
+    for (idx, rdn) in ordered.into_iter().enumerate() {
+        if idx != 0 {
+            output.push_str(rdn_separator);
+        }
+        for (idx, attribute) in rdn.iter().enumerate() {
+            if idx != 0 {
+                output.push_str(attribute_separator);
+            }
+            format_attribute(attribute, &mut output);
+        }
+    }
+    return output;
+}
+
+/// Format a Name as a RFC 4514 string, where the last RDN comes first.
+pub fn to_rfc4514(rdns: &[Rdn]) -> String {
+    return to_string(rdns, Style::default());
+}
+
+/// Decode a Name and format it as a RFC 4514 string.
+pub fn format(node: &Node, bytes: &[u8]) -> Result<String> {
+    return Ok(to_rfc4514(&parse(node, bytes)?));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    fn attribute(oid: &str, value: &str) -> Attribute {
+        return Attribute { oid: oid.to_owned(), value: Ok(value.to_owned()) };
+    }
+
+    #[test]
+    fn format_simple_name() {
+        let rdns = vec![
+            vec![attribute("2.5.4.6", "US")],
+            vec![attribute("2.5.4.10", "Acme")],
+            vec![attribute("2.5.4.3", "example.com")],
+        ];
+        assert_eq!(to_rfc4514(&rdns), "CN=example.com,O=Acme,C=US");
+    }
+
+    #[test]
+    fn format_escaped_and_multi_valued() {
+        let rdns = vec![
+            vec![attribute("2.5.4.10", "Example, Inc.")],
+            vec![attribute("2.5.4.3", " #lead"), attribute("0.9.2342.19200300.100.1.1", "a+b ")],
+            vec![Attribute { oid: "1.2.3.4".to_owned(), value: Err(vec![0x02, 0x01, 0x05]) }],
+        ];
+        assert_eq!(to_rfc4514(&rdns), "1.2.3.4=#020105,CN=\\ #lead+UID=a\\+b\\ ,O=Example\\, Inc.");
+    }
+
+    #[test]
+    fn format_styles() {
+        let rdns = vec![
+            vec![attribute("2.5.4.6", "US")],
+            vec![attribute("2.5.4.3", "a"), attribute("2.5.4.15", "b")],
+        ];
+        let style = |openssl_order, split_multi_valued| Style { openssl_order, split_multi_valued };
+        assert_eq!(to_string(&rdns, style(false, false)), "CN=a+businessCategory=b,C=US");
+        assert_eq!(to_string(&rdns, style(false, true)), "CN=a,businessCategory=b,C=US");
+        assert_eq!(to_string(&rdns, style(true, false)), "C=US, CN=a + businessCategory=b");
+        assert_eq!(to_string(&rdns, style(true, true)), "C=US, CN=a, businessCategory=b");
+    }
+
+    #[test]
+    fn parse_certificate_subject() {
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (nodes, _) = node::parse(bytes);
+        let subject = &nodes[0].children[0].children[5];
+        assert_eq!(
+            format(subject, bytes).unwrap(),
+            "CN=www.example.com,O=Example\\, Inc.,L=San Francisco,ST=California,C=US"
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use crate::der::{self, Tag};
+use crate::error::{Error, Result};
+
+/// Maximum nesting of elements, to avoid exhausting the stack on malicious
+/// inputs.
+const MAX_DEPTH: usize = 64;
+
+/// An element of a parsed document. It doesn't own or borrow the bytes of the
+/// document, every position is an offset from the start of the document.
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub tag: Tag,
+    /// Offset of the identifier octets.
+    pub offset: usize,
+    /// Number of identifier and length octets.
+    pub header_len: usize,
+    /// Number of content octets, without the end-of-contents octets of the
+    /// indefinite length form.
+    pub len: usize,
+    pub indefinite: bool,
+    /// The children were decoded from the content of a primitive OCTET STRING
+    /// or BIT STRING.
+    pub encapsulated: bool,
+    /// The children were decoded from the decrypted content of the element,
+    /// which is appended to the document.
+    pub decrypted: bool,
+    pub children: Vec<Node>,
+    /// Error, and its offset, that stopped the parsing of the children.
+    pub error: Option<(usize, Error)>,
+    /// Name of the field in the structure the element was recognized as.
+    pub field: Option<String>,
+    /// Short decoded meaning of the element, in the context of that structure.
+    pub note: Option<String>,
+    /// Name of the type of the element in the applied ASN.1 schema.
+    pub type_name: Option<String>,
+    /// Alternative of the CHOICE the element was recognized as, in the
+    /// applied ASN.1 schema.
+    pub alternative: Option<String>,
+    /// Universal type replaced by the implicit tag of the element, in the
+    /// applied ASN.1 schema.
+    pub implicit: Option<Tag>,
+    /// The element is a private key component, hidden unless revealed.
+    pub secret: bool,
+}
+
+impl Node {
+    pub fn content_offset(&self) -> usize {
+        return self.offset + self.header_len;
+    }
+
+    /// Offset of the first byte after the element.
+    pub fn end(&self) -> usize {
+        let eoc = if self.indefinite { 2 } else { 0 };
+        return self.content_offset() + self.len + eoc;
+    }
+
+    /// The content octets of the element.
+    pub fn content<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        return &bytes[self.content_offset()..self.content_offset() + self.len];
+    }
+
+    /// The complete encoding of the element, identifier and length included.
+    pub fn raw<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        return &bytes[self.offset..self.end()];
+    }
+
+    /// Returns the child at the given index if it has the expected tag.
+    pub fn child(&self, idx: usize, tag: Tag) -> Result<&Node> {
+        return match self.children.get(idx) {
+            Some(node) if node.tag == tag => Ok(node),
+            Some(_) => Err(Error("Unexpected tag")),
+            None => Err(Error("Missing element")),
+        };
+    }
+
+    pub fn set_field(&mut self, field: &str) {
+        self.field = Some(field.to_owned());
+    }
+
+    /// Mark the element and its descendants as private key components.
+    pub fn set_secret(&mut self) {
+        self.secret = true;
+        for child in self.children.iter_mut() {
+            child.set_secret();
+        }
+    }
+
+    /// Whether the element or one of its descendants is a private key
+    /// component.
+    pub fn contains_secret(&self) -> bool {
+        return self.secret || self.children.iter().any(Node::contains_secret);
+    }
+
+    /// Name the child at the given index, if it exists.
+    pub fn set_child_field(&mut self, idx: usize, field: &str) -> Option<&mut Node> {
+        let child = self.children.get_mut(idx)?;
+        child.set_field(field);
+        return Some(child);
+    }
+}
+
+/// Parse every element of `bytes`. Parsing stops at the first error, which is
+/// returned with its offset alongside the elements parsed until then.
+pub fn parse(bytes: &[u8]) -> (Vec<Node>, Option<(usize, Error)>) {
+    return parse_all(bytes, 0, 0);
+}
+
+/// Parse the elements of `bytes` that start at `start`, keeping their offsets
+/// relative to the beginning of `bytes`.
+pub fn parse_from(bytes: &[u8], start: usize) -> (Vec<Node>, Option<(usize, Error)>) {
+    return parse_all(&bytes[start..], start, 0);
+}
+
+fn parse_all(content: &[u8], base: usize, depth: usize) -> (Vec<Node>, Option<(usize, Error)>) {
+    let mut nodes = Vec::new();
+    let mut reader = der::Reader::new(content);
+    while !reader.at_end() {
+        let position = base + reader.position();
+        match parse_element(&mut reader, base, depth) {
+            Ok(node) => nodes.push(node),
+            Err(err) => return (nodes, Some((position, err))),
+        }
+    }
+    return (nodes, None);
+}
+
+fn parse_element(reader: &mut der::Reader, base: usize, depth: usize) -> Result<Node> {
+    if MAX_DEPTH <= depth {
+        return Err(Error("Elements are nested too deeply"));
+    }
+
+    let offset = base + reader.position();
+    let tag = der::read_tag(reader)?;
+    let length = der::read_length(reader)?;
+    let header_len = base + reader.position() - offset;
+
+    let mut node = Node {
+        tag,
+        offset,
+        header_len,
+        len: 0,
+        indefinite: length.is_none(),
+        encapsulated: false,
+        decrypted: false,
+        children: Vec::new(),
+        error: None,
+        field: None,
+        note: None,
+        type_name: None,
+        alternative: None,
+        implicit: None,
+        secret: false,
+    };
+
+    match length {
+        Some(len) => {
+            let content = reader.read_bytes(len)?;
+            node.len = len;
+            if tag.constructed {
+                (node.children, node.error) = parse_all(content, node.content_offset(), depth + 1);
+            } else if let Some((skip, inner)) = encapsulated_content(tag, content) {
+                let (children, error) = parse_all(inner, node.content_offset() + skip, depth + 1);
+                if error.is_none() && !children.is_empty() {
+                    node.children = children;
+                    node.encapsulated = true;
+                }
+            }
+        }
+        None => {
+            if !tag.constructed {
+                return Err(Error("Indefinite length on a primitive element"));
+            }
+
+            loop {
+                if reader.at_end() {
+                    return Err(Error("Missing end-of-contents octets"));
+                }
+
+                if reader.peek_eq(0) && reader.peek_at(1) == Some(0) {
+                    node.len = base + reader.position() - node.content_offset();
+                    reader.read_bytes(2)?;
+                    break;
+                }
+
+                node.children.push(parse_element(reader, base, depth + 1)?);
+            }
+        }
+    }
+
+    return Ok(node);
+}
+
+/// Returns the bytes of a primitive element which may contain DER, and how many
+/// bytes precede them in the content.
+fn encapsulated_content(tag: Tag, content: &[u8]) -> Option<(usize, &[u8])> {
+    return match tag {
+        Tag::OCTET_STRING => Some((0, content)),
+        Tag::BIT_STRING => match content {
+            [0, rest @ ..] => Some((1, rest)),
+            _ => None,
+        },
+        _ => None,
+    };
+}
+
+/// Call `callback` on every node, in depth-first order, with its path.
+pub fn walk<'a, F>(nodes: &'a [Node], callback: &mut F)
+where
+    F: FnMut(&[usize], &'a Node),
+{
+    fn helper<'a, F>(nodes: &'a [Node], path: &mut Vec<usize>, callback: &mut F)
+    where
+        F: FnMut(&[usize], &'a Node),
+    {
+        for (idx, node) in nodes.iter().enumerate() {
+            path.push(idx);
+            callback(path, node);
+            helper(&node.children, path, callback);
+            path.pop();
+        }
+    }
+
+    helper(nodes, &mut Vec::new(), callback);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_nested_elements() {
+        let bytes = b"\x30\x08\x02\x01\x05\x04\x03\x01\x02\x03";
+        let (nodes, error) = parse(bytes);
+        assert!(error.is_none());
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].tag, Tag::SEQUENCE);
+        assert_eq!(nodes[0].children.len(), 2);
+
+        let octet_string = &nodes[0].children[1];
+        assert_eq!(octet_string.offset, 5);
+        assert_eq!(octet_string.content(bytes), b"\x01\x02\x03");
+        assert!(!octet_string.encapsulated);
+
+        let bytes = b"\x04\x02\x05\x00";
+        let (nodes, _) = parse(bytes);
+        assert!(nodes[0].encapsulated);
+        assert_eq!(nodes[0].children[0].tag, Tag::NULL);
+        assert_eq!(nodes[0].children[0].offset, 2);
+    }
+
+    #[test]
+    fn parse_indefinite_length() {
+        let bytes = b"\x30\x80\x02\x01\x01\x00\x00\x05\x00";
+        let (nodes, error) = parse(bytes);
+        assert!(error.is_none());
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes[0].indefinite);
+        assert_eq!(nodes[0].len, 3);
+        assert_eq!(nodes[0].end(), 7);
+        assert_eq!(nodes[0].raw(bytes), b"\x30\x80\x02\x01\x01\x00\x00");
+    }
+
+    #[test]
+    fn parse_reports_errors() {
+        let (nodes, error) = parse(b"\x05\x00\x30\x05\x02\x01");
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(error.unwrap().0, 2);
+
+        let (nodes, _) = parse(b"\x30\x03\x02\x05\x00");
+        assert_eq!(nodes[0].error.as_ref().unwrap().0, 2);
+    }
+
+    #[test]
+    fn walk_nodes() {
+        let (nodes, _) = parse(b"\x30\x06\x30\x02\x05\x00\x05\x00");
+
+        let mut paths = Vec::new();
+        walk(&nodes, &mut |path, _| paths.push(path.to_vec()));
+        assert_eq!(paths, vec![vec![0], vec![0, 0], vec![0, 0, 0], vec![0, 1]]);
+    }
+}
@@ -0,0 +1,445 @@
+use crate::der::{Class, Tag};
+use crate::error::Result;
+use crate::json::Json;
+use crate::node::Node;
+use crate::{hex, oid, oid_names, value};
+
+/// Text notations an element can be shown in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Notation {
+    /// ASN.1 value notation, from X.680.
+    Value,
+    /// XML Encoding Rules, from X.693.
+    Xer,
+    /// JSON Encoding Rules, from X.697.
+    Jer,
+}
+
+impl Notation {
+    pub const ALL: [Notation; 3] = [Notation::Value, Notation::Xer, Notation::Jer];
+
+    pub fn name(self) -> &'static str {
+        return match self {
+            Notation::Value => "ASN.1 value notation",
+            Notation::Xer => "XER",
+            Notation::Jer => "JER",
+        };
+    }
+}
+
+/// The decoded value of an element, with the names given by the applied
+/// schema.
+enum Value<'a> {
+    Boolean(bool),
+    /// Decimal number.
+    Integer(String),
+    /// Identifier of the value, if the schema names it, and the number.
+    Enumerated(Option<String>, String),
+    /// Number of unused bits and the bytes.
+    BitString(u8, &'a [u8]),
+    /// Also the content of the elements which aren't decoded.
+    OctetString(&'a [u8]),
+    Null,
+    /// Dotted identifier and its name.
+    Oid(String, Option<String>),
+    String(String),
+    /// A private key component which isn't revealed.
+    Hidden,
+    /// Value encoded in an OCTET STRING or a BIT STRING.
+    Containing(Box<Value<'a>>),
+    /// Alternative of a CHOICE and its value.
+    Choice(String, Box<Value<'a>>),
+    /// Components of a SEQUENCE or a SET with their names.
+    Sequence(Vec<(String, Value<'a>)>),
+    /// Items of a SEQUENCE OF or a SET OF, or elements without a schema, with
+    /// the XML name of their type.
+    SequenceOf(Vec<(String, Value<'a>)>),
+}
+
+/// Decode an INTEGER of any size as a decimal number.
+fn decimal(content: &[u8]) -> Result<String> {
+    let (negative, magnitude) = value::integer_magnitude(content)?;
+    let mut magnitude = magnitude.to_vec();
+    if negative {
+        // The magnitude of a two's complement number is its complement plus one.
+        for byte in magnitude.iter_mut() {
+            *byte = !*byte;
+        }
+        for byte in magnitude.iter_mut().rev() {
+            let (sum, carry) = byte.overflowing_add(1);
+            *byte = sum;
+            if !carry {
+                break;
+            }
+        }
+    }
+
+    let mut digits = Vec::new();
+    while magnitude.iter().any(|byte| *byte != 0) {
+        let mut remainder = 0;
+        for byte in magnitude.iter_mut() {
+            let current = (remainder << 8) | u32::from(*byte);
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(char::from(b'0' + remainder as u8));
+    }
+    if digits.is_empty() {
+        digits.push('0');
+    }
+    if negative {
+        digits.push('-');
+    }
+    return Ok(digits.iter().rev().collect());
+}
+
+fn is_identifier(text: &str) -> bool {
+    return text.starts_with(|c: char| c.is_ascii_lowercase())
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+}
+
+fn hexadecimal(bytes: &[u8]) -> String {
+    return hex::hexlify(bytes).to_ascii_uppercase();
+}
+
+fn bits(unused: u8, bytes: &[u8]) -> String {
+    let mut bits: String = bytes.iter().map(|byte| format!("{:08b}", byte)).collect();
+    bits.truncate(bits.len().saturating_sub(usize::from(unused)));
+    return bits;
+}
+
+/// The element an explicit tag holds. Every constructed element of another
+/// class than universal holding a single element without a name is taken as
+/// an explicit tag, unless the schema tells it is implicit.
+fn explicit(node: &Node) -> Option<&Node> {
+    let tagged = node.tag.class != Class::Universal && node.implicit.is_none();
+    if !node.tag.constructed || !tagged || node.children.len() != 1 {
+        return None;
+    }
+    let child = &node.children[0];
+    return match component(child) {
+        Some(_) => None,
+        None => Some(child),
+    };
+}
+
+/// Name of the component of a SEQUENCE or SET the element is, which isn't
+/// the name of the CHOICE alternative it is.
+fn component(node: &Node) -> Option<&String> {
+    return node.field.as_ref().filter(|field| node.alternative.as_ref() != Some(*field));
+}
+
+/// Name of the type of the element in XER: its name in the schema, its field
+/// when it has no type name, or the name of its universal type.
+fn xml_name(node: &Node) -> String {
+    if let Some(type_name) = node.type_name.as_ref() {
+        return type_name.rsplit('.').next().unwrap_or(type_name).to_owned();
+    }
+    if let Some(field) = component(node) {
+        return field.clone();
+    }
+    if let Some(inner) = explicit(node) {
+        return xml_name(inner);
+    }
+    let name = match node.implicit.unwrap_or(node.tag) {
+        Tag::BOOLEAN => "BOOLEAN",
+        Tag::INTEGER => "INTEGER",
+        Tag::BIT_STRING => "BIT_STRING",
+        Tag::NULL => "NULL",
+        Tag::OID => "OBJECT_IDENTIFIER",
+        Tag::REAL => "REAL",
+        Tag::ENUMERATED => "ENUMERATED",
+        Tag::RELATIVE_OID => "RELATIVE_OID",
+        Tag::SET => "SET",
+        Tag::UTF8_STRING => "UTF8String",
+        tag if value::is_string(tag) || tag == Tag::UTC_TIME || tag == Tag::GENERALIZED_TIME => {
+            tag.universal_name().unwrap_or("OCTET_STRING")
+        }
+        tag if tag.constructed => "SEQUENCE",
+        _ => "OCTET_STRING",
+    };
+    return name.to_owned();
+}
+
+fn decoded<'a>(tag: Tag, node: &Node, content: &'a [u8]) -> Result<Value<'a>> {
+    return Ok(match tag {
+        Tag::BOOLEAN => Value::Boolean(value::boolean(content)?),
+        Tag::INTEGER => Value::Integer(decimal(content)?),
+        Tag::ENUMERATED => {
+            let name = node.note.clone().filter(|note| is_identifier(note));
+            Value::Enumerated(name, decimal(content)?)
+        }
+        Tag::BIT_STRING => {
+            let (unused, bytes) = value::bit_string(content)?;
+            Value::BitString(unused, bytes)
+        }
+        Tag::NULL => Value::Null,
+        Tag::OID => {
+            let id = oid::stringify(content)?;
+            let name = oid_names::find(&id).map(|entry| entry.name.to_string());
+            Value::Oid(id, name)
+        }
+        Tag::UTC_TIME | Tag::GENERALIZED_TIME => Value::String(value::ascii_string(content)?),
+        tag if value::is_string(tag) => Value::String(value::string(tag, content)?),
+        _ => Value::OctetString(content),
+    });
+}
+
+fn value<'a>(node: &Node, bytes: &'a [u8], reveal_secrets: bool) -> Value<'a> {
+    let value = content(node, bytes, reveal_secrets);
+    return match node.alternative.as_ref() {
+        Some(alternative) => Value::Choice(alternative.clone(), Box::new(value)),
+        None => value,
+    };
+}
+
+fn content<'a>(node: &Node, bytes: &'a [u8], reveal_secrets: bool) -> Value<'a> {
+    if let Some(inner) = explicit(node) {
+        return value(inner, bytes, reveal_secrets);
+    }
+    if node.tag.constructed {
+        let members: Vec<(String, Value)> = node
+            .children
+            .iter()
+            .filter_map(|child| Some((component(child)?.clone(), value(child, bytes, reveal_secrets))))
+            .collect();
+        // The items of a SEQUENCE OF can be named too, but never all differently.
+        let distinct =
+            members.iter().enumerate().all(|(idx, (name, _))| members[..idx].iter().all(|(other, _)| other != name));
+        if !members.is_empty() && members.len() == node.children.len() && distinct {
+            return Value::Sequence(members);
+        }
+        let items = node.children.iter().map(|child| (xml_name(child), value(child, bytes, reveal_secrets)));
+        return Value::SequenceOf(items.collect());
+    }
+    if node.encapsulated && node.children.len() == 1 {
+        return Value::Containing(Box::new(value(&node.children[0], bytes, reveal_secrets)));
+    }
+    if node.secret && !reveal_secrets {
+        return Value::Hidden;
+    }
+
+    let content = node.content(bytes);
+    let tag = node.implicit.unwrap_or(node.tag);
+    if tag.class != Class::Universal {
+        return Value::OctetString(content);
+    }
+    return decoded(tag, node, content).unwrap_or(Value::OctetString(content));
+}
+
+fn write_value(output: &mut String, value: &Value, depth: usize) {
+    match value {
+        Value::Boolean(value) => output.push_str(if *value { "TRUE" } else { "FALSE" }),
+        Value::Integer(number) | Value::Enumerated(None, number) => output.push_str(number),
+        Value::Enumerated(Some(name), _) => output.push_str(name),
+        Value::BitString(0, bytes) | Value::OctetString(bytes) => output.push_str(&format!("'{}'H", hexadecimal(bytes))),
+        Value::BitString(unused, bytes) => output.push_str(&format!("'{}'B", bits(*unused, bytes))),
+        Value::Null => output.push_str("NULL"),
+        Value::Oid(id, name) => {
+            output.push_str(&format!("{{ {}", id.replace('.', " ")));
+            if let Some(name) = name {
+                output.push_str(&format!(" /* {} */", name));
+            }
+            output.push_str(" }");
+        }
+        Value::String(text) => output.push_str(&format!("\"{}\"", text.replace('"', "\"\""))),
+        Value::Hidden => output.push_str("/* hidden */"),
+        Value::Containing(inner) => {
+            output.push_str("CONTAINING ");
+            write_value(output, inner, depth);
+        }
+        Value::Choice(alternative, inner) => {
+            output.push_str(&format!("{} : ", alternative));
+            write_value(output, inner, depth);
+        }
+        Value::Sequence(members) | Value::SequenceOf(members) if members.is_empty() => output.push_str("{}"),
+        Value::Sequence(members) | Value::SequenceOf(members) => {
+            let named = matches!(value, Value::Sequence(_));
+            output.push('{');
+            for (idx, (name, member)) in members.iter().enumerate() {
+                if 0 < idx {
+                    output.push(',');
+                }
+                output.push('\n');
+                output.push_str(&"  ".repeat(depth + 1));
+                if named {
+                    output.push_str(name);
+                    output.push(' ');
+                }
+                write_value(output, member, depth + 1);
+            }
+            output.push('\n');
+            output.push_str(&"  ".repeat(depth));
+            output.push('}');
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+}
+
+fn write_xer(output: &mut String, name: &str, value: &Value, depth: usize) {
+    if let Value::Containing(inner) = value {
+        return write_xer(output, name, inner, depth);
+    }
+
+    output.push_str(&"  ".repeat(depth));
+    match value {
+        Value::Null => output.push_str(&format!("<{}/>", name)),
+        Value::Boolean(value) => output.push_str(&format!("<{0}><{1}/></{0}>", name, value)),
+        Value::Enumerated(Some(identifier), _) => output.push_str(&format!("<{0}><{1}/></{0}>", name, identifier)),
+        Value::Hidden => output.push_str(&format!("<{0}><!-- hidden --></{0}>", name)),
+        Value::Sequence(members) | Value::SequenceOf(members) if members.is_empty() => {
+            output.push_str(&format!("<{}/>", name));
+        }
+        Value::Choice(..) | Value::Sequence(_) | Value::SequenceOf(_) => {
+            output.push_str(&format!("<{}>\n", name));
+            match value {
+                Value::Choice(alternative, inner) => write_xer(output, alternative, inner, depth + 1),
+                Value::Sequence(members) | Value::SequenceOf(members) => {
+                    for (member_name, member) in members.iter() {
+                        write_xer(output, member_name, member, depth + 1);
+                    }
+                }
+                _ => {}
+            }
+            output.push_str(&"  ".repeat(depth));
+            output.push_str(&format!("</{}>", name));
+        }
+        value => {
+            let text = match value {
+                Value::Integer(number) | Value::Enumerated(_, number) => number.clone(),
+                Value::BitString(unused, bytes) => bits(*unused, bytes),
+                Value::OctetString(bytes) => hexadecimal(bytes),
+                Value::Oid(id, _) => id.clone(),
+                Value::String(text) => escape(text),
+                _ => String::new(),
+            };
+            output.push_str(&format!("<{0}>{1}</{0}>", name, text));
+        }
+    }
+    output.push('\n');
+}
+
+fn jer(value: &Value) -> Json {
+    return match value {
+        Value::Boolean(value) => Json::Bool(*value),
+        Value::Integer(number) | Value::Enumerated(None, number) => Json::number(number),
+        Value::Enumerated(Some(name), _) => Json::string(name),
+        Value::BitString(unused, bytes) => Json::object([
+            ("value", Json::String(hexadecimal(bytes))),
+            ("length", Json::number(bytes.len() * 8 - usize::from(*unused))),
+        ]),
+        Value::OctetString(bytes) => Json::String(hexadecimal(bytes)),
+        Value::Null | Value::Hidden => Json::Null,
+        Value::Oid(id, _) => Json::string(id),
+        Value::String(text) => Json::string(text),
+        Value::Containing(inner) => jer(inner),
+        Value::Choice(alternative, inner) => Json::Object(vec![(alternative.clone(), jer(inner))]),
+        Value::Sequence(members) => Json::Object(members.iter().map(|(name, member)| (name.clone(), jer(member))).collect()),
+        Value::SequenceOf(items) => Json::Array(items.iter().map(|(_, item)| jer(item)).collect()),
+    };
+}
+
+/// Write the element and its descendants in the notation, naming them after
+/// the applied schema. The private key components are hidden unless
+/// `reveal_secrets` is set.
+pub fn render(notation: Notation, node: &Node, bytes: &[u8], reveal_secrets: bool) -> String {
+    let value = value(node, bytes, reveal_secrets);
+    let mut output = String::new();
+    match notation {
+        Notation::Value => {
+            if let Some(type_name) = node.type_name.as_ref() {
+                output.push_str(&format!("value {} ::= ", type_name));
+            }
+            write_value(&mut output, &value, 0);
+            output.push('\n');
+        }
+        Notation::Xer => write_xer(&mut output, &xml_name(node), &value, 0),
+        Notation::Jer => output = jer(&value).pretty(),
+    }
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+    use crate::node;
+    use crate::schema::{self, Schema};
+
+    #[test]
+    fn decimal_integers() {
+        assert_eq!(decimal(&[0x00]).unwrap(), "0");
+        assert_eq!(decimal(&[0x7F]).unwrap(), "127");
+        assert_eq!(decimal(&[0x80]).unwrap(), "-128");
+        assert_eq!(decimal(&[0xFF]).unwrap(), "-1");
+        assert_eq!(decimal(&[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]).unwrap(), "18446744073709551616");
+        let mut big = vec![0x00];
+        big.extend([0xFF; 20]);
+        assert_eq!(decimal(&big).unwrap(), "1461501637330902918203684832716283019655932542975");
+        assert!(decimal(&[]).is_err());
+    }
+
+    #[test]
+    fn render_choice() {
+        let (modules, _) = schema::parse(
+            "Auto DEFINITIONS AUTOMATIC TAGS ::= BEGIN \
+             Pair ::= SEQUENCE { first INTEGER OPTIONAL, second Choice } \
+             Choice ::= CHOICE { number INTEGER, flag BOOLEAN } END",
+        );
+        let mut schema = Schema::default();
+        schema.add(modules);
+        // SEQUENCE { [0] -300, [1] { [1] TRUE } }
+        let bytes = [0x30, 0x09, 0x80, 0x02, 0xFE, 0xD4, 0xA1, 0x03, 0x81, 0x01, 0xFF];
+        let (mut nodes, _) = node::parse(&bytes);
+        assert_eq!(schema.apply("Auto.Pair", &mut nodes[0], &bytes), vec![]);
+
+        assert_eq!(
+            render(Notation::Value, &nodes[0], &bytes, false),
+            "value Auto.Pair ::= {\n  first -300,\n  second flag : TRUE\n}\n"
+        );
+        assert_eq!(
+            render(Notation::Xer, &nodes[0], &bytes, false),
+            "<Pair>\n  <first>-300</first>\n  <second>\n    <flag><true/></flag>\n  </second>\n</Pair>\n"
+        );
+        assert_eq!(
+            render(Notation::Jer, &nodes[0], &bytes, false),
+            "{\n  \"first\": -300,\n  \"second\": {\n    \"flag\": true\n  }\n}\n"
+        );
+
+        // Without the schema, the implicitly tagged elements are shown in
+        // hexadecimal and the explicit tags are removed.
+        schema::clear(&mut nodes[0]);
+        nodes[0].children[0].field = None;
+        nodes[0].children[1].field = None;
+        nodes[0].children[1].children[0].field = None;
+        assert_eq!(render(Notation::Value, &nodes[0], &bytes, false), "{\n  'FED4'H,\n  'FF'H\n}\n");
+        assert_eq!(
+            render(Notation::Xer, &nodes[0], &bytes, false),
+            "<SEQUENCE>\n  <OCTET_STRING>FED4</OCTET_STRING>\n  <OCTET_STRING>FF</OCTET_STRING>\n</SEQUENCE>\n"
+        );
+    }
+
+    #[test]
+    fn render_certificate() {
+        let document = Document::from_bytes("leaf.der".to_owned(), include_bytes!("../tests/leaf.der").to_vec());
+        let certificate = &document.nodes[0];
+
+        let text = render(Notation::Value, certificate, &document.bytes, false);
+        assert!(text.starts_with("value PKIX1.Certificate ::= {\n  tbsCertificate {\n    version 2,\n"));
+        assert!(text.contains("algorithm { 1 2 840 113549 1 1 11 /* sha256WithRSAEncryption */ }"));
+        assert!(text.contains("notBefore utcTime : \""));
+        assert!(text.contains("extnValue CONTAINING {"));
+
+        let text = render(Notation::Xer, certificate, &document.bytes, false);
+        assert!(text.starts_with("<Certificate>\n  <tbsCertificate>\n    <version>2</version>\n"));
+        assert!(text.contains("<algorithm>1.2.840.113549.1.1.11</algorithm>"));
+        assert!(text.ends_with("</Certificate>\n"));
+
+        let text = render(Notation::Jer, certificate, &document.bytes, false);
+        assert!(text.starts_with("{\n  \"tbsCertificate\": {\n    \"version\": 2,\n"));
+        assert!(text.contains("\"notBefore\": {\n        \"utcTime\": \""));
+    }
+}
@@ -0,0 +1,84 @@
+use imgui::*;
+use crate::app::Selection;
+use crate::document::Document;
+use crate::notation::{self, Notation};
+
+/// Tool window showing an element in ASN.1 value notation, XER or JER.
+pub struct NotationWindow {
+    pub opened: bool,
+    notation: Notation,
+    /// Element shown, forgotten when the documents change.
+    selection: Option<Selection>,
+    generation: usize,
+    text: String,
+    /// Notation, secrets revealed and revision of the document `text` was
+    /// rendered with, or None when the element changed since.
+    rendered: Option<(Notation, bool, usize)>,
+}
+
+impl NotationWindow {
+    pub fn new() -> Self {
+        return Self { opened: false, notation: Notation::Value, selection: None, generation: 0, text: String::new(), rendered: None };
+    }
+
+    pub fn show(&mut self, selection: Selection, notation: Notation, generation: usize) {
+        self.opened = true;
+        self.notation = notation;
+        self.selection = Some(selection);
+        self.generation = generation;
+        self.rendered = None;
+    }
+
+    /// Draw the window.
+    ///
+    /// `generation` must change whenever `documents` changes.
+    pub fn draw(&mut self, ui: &Ui, documents: &[Document], generation: usize, reveal_secrets: bool) {
+        if !self.opened {
+            return;
+        }
+        if self.generation != generation {
+            self.selection = None;
+            self.rendered = None;
+        }
+
+        let mut opened = self.opened;
+        ui.window("Show as")
+            .size([640.0, 480.0], Condition::FirstUseEver)
+            .opened(&mut opened)
+            .build(|| {
+                let found = self.selection.as_ref().and_then(|selection| {
+                    let document = documents.get(selection.document)?;
+                    return Some((document, document.node(&selection.path)?));
+                });
+                let (document, node) = match found {
+                    Some(found) => found,
+                    None => {
+                        ui.text("Right-click an element and choose 'Show as' to show it here.");
+                        return;
+                    }
+                };
+
+                ui.text(format!("{:?} at offset {} in '{}'", node.tag, node.offset, document.name));
+                if let Some(_) = ui.begin_combo("Notation", self.notation.name()) {
+                    for notation in Notation::ALL {
+                        if ui.selectable_config(notation.name()).selected(notation == self.notation).build() {
+                            self.notation = notation;
+                        }
+                    }
+                }
+                ui.same_line();
+                // Rendered again when the schemas applied or the secrets
+                // revealed change.
+                let key = (self.notation, reveal_secrets, document.revision);
+                if self.rendered != Some(key) {
+                    self.text = notation::render(self.notation, node, &document.bytes, reveal_secrets);
+                    self.rendered = Some(key);
+                }
+                if ui.button("Copy") {
+                    ui.set_clipboard_text(&self.text);
+                }
+                ui.input_text_multiline("##text", &mut self.text, [-1.0, -1.0]).read_only(true).build();
+            });
+        self.opened = opened;
+    }
+}
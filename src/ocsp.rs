@@ -0,0 +1,534 @@
+use chrono::{DateTime, Utc};
+use crate::chain::Candidate;
+use crate::cms::{self, EmbeddedCertificate};
+use crate::der::{Class, Tag};
+use crate::error::{Error, Result};
+use crate::hash;
+use crate::node::{self, Node};
+use crate::signature::{self, PublicKey};
+use crate::{extension, hex, name, oid, oid_names, spki, value, x509};
+
+const BASIC_RESPONSE: &str = "1.3.6.1.5.5.7.48.1.1";
+const NONCE: &str = "1.3.6.1.5.5.7.48.1.2";
+
+const RESPONSE_STATUSES: &[&str] = &[
+    "successful",
+    "malformedRequest",
+    "internalError",
+    "tryLater",
+    "unused",
+    "sigRequired",
+    "unauthorized",
+];
+
+/// Identifies a certificate by its serial number and the hashes of its
+/// issuer's name and key.
+#[derive(Clone, Debug)]
+pub struct CertId {
+    pub hash_algorithm: String,
+    pub issuer_name_hash: Vec<u8>,
+    pub issuer_key_hash: Vec<u8>,
+    pub serial: Vec<u8>,
+    /// The loaded certificate, or issuer, it was matched with.
+    pub certificate: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SingleResponse {
+    pub cert_id: CertId,
+    pub status: String,
+    pub this_update: DateTime<Utc>,
+    pub next_update: Option<DateTime<Utc>>,
+}
+
+/// Summary of a RFC 6960 OCSPRequest.
+#[derive(Clone, Debug)]
+pub struct OcspRequest {
+    pub requestor: Option<String>,
+    pub requests: Vec<CertId>,
+    pub nonce: Option<Vec<u8>>,
+    pub signed: bool,
+}
+
+/// Summary of a RFC 6960 OCSPResponse.
+#[derive(Clone, Debug)]
+pub struct OcspResponse {
+    pub status: &'static str,
+    pub response_type: Option<String>,
+    pub responder: Option<String>,
+    pub produced_at: Option<DateTime<Utc>>,
+    pub responses: Vec<SingleResponse>,
+    pub nonce: Option<Vec<u8>>,
+    pub signature_algorithm: Option<String>,
+    pub certificates: Vec<EmbeddedCertificate>,
+    /// The subject of the responder certificate that verified the signature.
+    pub verification: Option<Result<String>>,
+}
+
+/// The child at `idx` if it has the given context-specific tag.
+fn explicit(node: &Node, idx: usize, number: u32) -> Option<&Node> {
+    return node.children.get(idx).filter(|child| child.tag == Tag::context_specific(number).into_constructed());
+}
+
+/// The content of the nonce extension, if the Extensions hold one.
+fn nonce(extensions: &Node, bytes: &[u8]) -> Option<Vec<u8>> {
+    for extension in extensions.children.iter() {
+        if oid::stringify(extension.child(0, Tag::OID).ok()?.content(bytes)).ok()? != NONCE {
+            continue;
+        }
+        let extn_value = extension.children.last()?;
+        return Some(match extn_value.children.first() {
+            Some(inner) if extn_value.encapsulated && inner.tag == Tag::OCTET_STRING => inner.content(bytes).to_vec(),
+            _ => extn_value.content(bytes).to_vec(),
+        });
+    }
+    return None;
+}
+
+fn annotate_extensions(node: &mut Node, field: &str, bytes: &[u8]) {
+    node.set_field(field);
+    for extensions in node.children.iter_mut() {
+        for extension in extensions.children.iter_mut() {
+            extension::annotate(extension, bytes);
+        }
+    }
+}
+
+/// The content of the BIT STRING of a SubjectPublicKeyInfo, that the key
+/// hashes are computed on.
+fn key_bits<'a>(spki: &Node, bytes: &'a [u8]) -> Option<&'a [u8]> {
+    let (_, bits) = value::bit_string(spki.child(1, Tag::BIT_STRING).ok()?.content(bytes)).ok()?;
+    return Some(bits);
+}
+
+impl CertId {
+    fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() != 4 {
+            return Err(Error("CertID must be a SEQUENCE of four elements"));
+        }
+        let (hash_algorithm, _) = spki::algorithm(node.child(0, Tag::SEQUENCE)?, bytes)?;
+        let (_, serial) = value::integer_magnitude(node.child(3, Tag::INTEGER)?.content(bytes))?;
+        return Ok(Self {
+            hash_algorithm: oid_names::name_or_oid(hash_algorithm),
+            issuer_name_hash: node.child(1, Tag::OCTET_STRING)?.content(bytes).to_vec(),
+            issuer_key_hash: node.child(2, Tag::OCTET_STRING)?.content(bytes).to_vec(),
+            serial: serial.to_vec(),
+            certificate: None,
+        });
+    }
+
+    fn annotate(node: &mut Node) {
+        node.set_field("certID");
+        if let Some(algorithm) = node.set_child_field(0, "hashAlgorithm") {
+            x509::annotate_algorithm(algorithm);
+        }
+        node.set_child_field(1, "issuerNameHash");
+        node.set_child_field(2, "issuerKeyHash");
+        node.set_child_field(3, "serialNumber");
+    }
+
+    /// Describe the certificate identified by the CertID `node` among the
+    /// candidates, or its issuer if the certificate isn't loaded.
+    fn identify(node: &Node, bytes: &[u8], candidates: &[Candidate]) -> Option<String> {
+        let cert_id = Self::parse(node, bytes).ok()?;
+        let (algorithm, _) = spki::algorithm(&node.children[0], bytes).ok()?;
+        let algorithm = hash::Algorithm::from_oid(&algorithm)?;
+
+        let issuer = candidates.iter().find_map(|candidate| {
+            let fields = x509::Fields::new(candidate.node).ok()?;
+            let name_hash = algorithm.digest(fields.subject.raw(candidate.bytes));
+            let key_hash = algorithm.digest(key_bits(fields.spki, candidate.bytes)?);
+            if name_hash != cert_id.issuer_name_hash || key_hash != cert_id.issuer_key_hash {
+                return None;
+            }
+            return Some((fields.subject.raw(candidate.bytes), name::format(fields.subject, candidate.bytes).ok()?));
+        })?;
+
+        let subject = candidates.iter().find_map(|candidate| {
+            let fields = x509::Fields::new(candidate.node).ok()?;
+            let (_, serial) = value::integer_magnitude(fields.serial.content(candidate.bytes)).ok()?;
+            if fields.issuer.raw(candidate.bytes) != issuer.0 || serial != cert_id.serial.as_slice() {
+                return None;
+            }
+            return name::format(fields.subject, candidate.bytes).ok();
+        });
+        return Some(match subject {
+            Some(subject) => subject,
+            None => format!("unknown certificate issued by {}", issuer.1),
+        });
+    }
+}
+
+/// Parse the DER of the loaded certificates, to use them as candidates.
+fn parse_certificates(certificates: &[Vec<u8>]) -> Vec<(&[u8], Vec<Node>)> {
+    return certificates.iter().map(|der| (der.as_slice(), node::parse(der).0)).collect();
+}
+
+fn candidates<'a>(parsed: &'a [(&'a [u8], Vec<Node>)]) -> Vec<Candidate<'a>> {
+    return parsed
+        .iter()
+        .filter_map(|(bytes, nodes)| Some(Candidate { node: nodes.first()?, bytes }))
+        .collect();
+}
+
+impl OcspRequest {
+    /// Decode an OCSPRequest, failing if the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.is_empty() || 2 < node.children.len() {
+            return Err(Error("OCSPRequest must be a SEQUENCE of one or two elements"));
+        }
+        let tbs = node.child(0, Tag::SEQUENCE)?;
+        let signed = match node.children.get(1) {
+            Some(child) if child.tag == Tag::context_specific(0).into_constructed() => true,
+            Some(_) => return Err(Error("Unexpected element in OCSPRequest")),
+            None => false,
+        };
+
+        let mut idx = 0;
+        if explicit(tbs, idx, 0).is_some() {
+            idx += 1;
+        }
+        let mut requestor = None;
+        if let Some(requestor_name) = explicit(tbs, idx, 1) {
+            let mut general_name = requestor_name.children.first().ok_or(Error("Missing requestorName"))?.clone();
+            requestor = Some(extension::general_name(&mut general_name, bytes)?);
+            idx += 1;
+        }
+
+        let mut requests = Vec::new();
+        for request in tbs.child(idx, Tag::SEQUENCE)?.children.iter() {
+            requests.push(CertId::parse(request.child(0, Tag::SEQUENCE)?, bytes)?);
+        }
+        if requests.is_empty() {
+            return Err(Error("OCSPRequest must hold at least one request"));
+        }
+
+        let extensions = explicit(tbs, idx + 1, 2).and_then(|node| node.children.first());
+        if tbs.children.len() != idx + 1 + usize::from(extensions.is_some()) {
+            return Err(Error("Unexpected element in TBSRequest"));
+        }
+        return Ok(Self { requestor, requests, nonce: extensions.and_then(|node| nonce(node, bytes)), signed });
+    }
+
+    /// Name every field of a node previously accepted by `parse`.
+    pub fn annotate(node: &mut Node, bytes: &[u8]) {
+        node.set_child_field(1, "optionalSignature");
+        let tbs = match node.set_child_field(0, "tbsRequest") {
+            Some(tbs) => tbs,
+            None => return,
+        };
+        for child in tbs.children.iter_mut() {
+            match (child.tag.class, child.tag.number) {
+                (Class::ContextSpecific, 0) => child.set_field("version"),
+                (Class::ContextSpecific, 1) => {
+                    child.set_field("requestorName");
+                    if let Some(general_name) = child.children.first_mut() {
+                        let _ = extension::general_name(general_name, bytes);
+                    }
+                }
+                (Class::ContextSpecific, 2) => annotate_extensions(child, "requestExtensions", bytes),
+                _ => {
+                    child.set_field("requestList");
+                    for request in child.children.iter_mut() {
+                        request.set_field("request");
+                        if let Some(cert_id) = request.children.first_mut() {
+                            CertId::annotate(cert_id);
+                        }
+                        if let Some(extensions) = request.children.get_mut(1) {
+                            annotate_extensions(extensions, "singleRequestExtensions", bytes);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Match the requested CertIDs with the loaded certificates, given as DER.
+    pub fn check(&mut self, node: &Node, bytes: &[u8], certificates: &[Vec<u8>]) -> Result<()> {
+        let parsed = parse_certificates(certificates);
+        let candidates = candidates(&parsed);
+        let tbs = node.child(0, Tag::SEQUENCE)?;
+        let requests = tbs.children.iter().find(|child| child.tag == Tag::SEQUENCE).ok_or(Error("Missing requestList"))?;
+        for (cert_id, request) in self.requests.iter_mut().zip(requests.children.iter()) {
+            let cert_id_node = request.child(0, Tag::SEQUENCE)?;
+            cert_id.certificate = CertId::identify(cert_id_node, bytes, &candidates);
+        }
+        return Ok(());
+    }
+}
+
+/// The BasicOCSPResponse of an OCSPResponse, if it holds one.
+fn basic_response<'a>(node: &'a Node, bytes: &[u8]) -> Option<&'a Node> {
+    let response_bytes = explicit(node, 1, 0)?.children.first()?;
+    let response_type = oid::stringify(response_bytes.child(0, Tag::OID).ok()?.content(bytes)).ok()?;
+    if response_type != BASIC_RESPONSE {
+        return None;
+    }
+    let response = response_bytes.child(1, Tag::OCTET_STRING).ok()?;
+    return response.children.first().filter(|_| response.encapsulated);
+}
+
+/// The index of the producedAt in a ResponseData.
+fn produced_at_index(response_data: &Node) -> usize {
+    return if explicit(response_data, 0, 0).is_some() { 2 } else { 1 };
+}
+
+fn single_response(node: &Node, bytes: &[u8]) -> Result<SingleResponse> {
+    let cert_id = CertId::parse(node.child(0, Tag::SEQUENCE)?, bytes)?;
+    let cert_status = node.children.get(1).ok_or(Error("Missing certStatus"))?;
+    let status = match (cert_status.tag.class, cert_status.tag.number) {
+        (Class::ContextSpecific, 0) => "good".to_owned(),
+        (Class::ContextSpecific, 1) => {
+            let time = cert_status.child(0, Tag::GENERALIZED_TIME)?;
+            let time = value::time(time.tag, time.content(bytes))?;
+            let reason = explicit(cert_status, 1, 0)
+                .and_then(|reason| reason.children.first())
+                .and_then(|reason| extension::crl_reason(reason.content(bytes)).ok());
+            match reason {
+                Some(reason) => format!("revoked at {} ({})", time, reason),
+                None => format!("revoked at {}", time),
+            }
+        }
+        (Class::ContextSpecific, 2) => "unknown".to_owned(),
+        _ => return Err(Error("Invalid certStatus")),
+    };
+    let this_update = node.child(2, Tag::GENERALIZED_TIME)?;
+    let next_update = explicit(node, 3, 0).and_then(|next_update| next_update.children.first());
+    return Ok(SingleResponse {
+        cert_id,
+        status,
+        this_update: value::time(this_update.tag, this_update.content(bytes))?,
+        next_update: next_update.map(|node| value::time(node.tag, node.content(bytes))).transpose()?,
+    });
+}
+
+impl OcspResponse {
+    /// Decode an OCSPResponse, failing if the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.is_empty() || 2 < node.children.len() {
+            return Err(Error("OCSPResponse must be a SEQUENCE of one or two elements"));
+        }
+        let status = value::unsigned(node.child(0, Tag::ENUMERATED)?.content(bytes))? as usize;
+        let mut response = Self {
+            status: RESPONSE_STATUSES.get(status).copied().ok_or(Error("Invalid OCSPResponseStatus"))?,
+            response_type: None,
+            responder: None,
+            produced_at: None,
+            responses: Vec::new(),
+            nonce: None,
+            signature_algorithm: None,
+            certificates: Vec::new(),
+            verification: None,
+        };
+
+        let response_bytes = match node.children.get(1) {
+            Some(explicit) => explicit.children.first().filter(|_| explicit.tag == Tag::context_specific(0).into_constructed()),
+            None => return Ok(response),
+        };
+        let response_bytes = response_bytes.ok_or(Error("Invalid responseBytes"))?;
+        let response_type = oid::stringify(response_bytes.child(0, Tag::OID)?.content(bytes))?;
+        response_bytes.child(1, Tag::OCTET_STRING)?;
+        response.response_type = Some(oid_names::name_or_oid(response_type));
+
+        let basic = match basic_response(node, bytes) {
+            Some(basic) => basic,
+            None => return Ok(response),
+        };
+        let response_data = basic.child(0, Tag::SEQUENCE)?;
+        let (signature_algorithm, _) = spki::algorithm(basic.child(1, Tag::SEQUENCE)?, bytes)?;
+        response.signature_algorithm = Some(oid_names::name_or_oid(signature_algorithm));
+        if let Some(certs) = explicit(basic, 3, 0).and_then(|certs| certs.children.first()) {
+            response.certificates = cms::certificates(certs, bytes);
+        }
+
+        let idx = produced_at_index(response_data);
+        let responder_id = response_data.children.get(idx - 1).ok_or(Error("Missing responderID"))?;
+        let responder = responder_id.children.first().ok_or(Error("Missing responderID"))?;
+        response.responder = Some(match responder_id.tag.number {
+            1 => name::format(responder, bytes)?,
+            _ => format!("key {}", hex::colon_separated(responder.content(bytes))),
+        });
+        let produced_at = response_data.child(idx, Tag::GENERALIZED_TIME)?;
+        response.produced_at = Some(value::time(produced_at.tag, produced_at.content(bytes))?);
+        for single in response_data.child(idx + 1, Tag::SEQUENCE)?.children.iter() {
+            response.responses.push(single_response(single, bytes)?);
+        }
+        if let Some(extensions) = explicit(response_data, idx + 2, 1).and_then(|node| node.children.first()) {
+            response.nonce = nonce(extensions, bytes);
+        }
+
+        return Ok(response);
+    }
+
+    /// Name every field of a node previously accepted by `parse`.
+    pub fn annotate(node: &mut Node, bytes: &[u8]) {
+        node.set_child_field(0, "responseStatus");
+        let response_bytes = node.set_child_field(1, "responseBytes").and_then(|node| node.children.first_mut());
+        if let Some(response_bytes) = response_bytes {
+            response_bytes.set_child_field(0, "responseType");
+            response_bytes.set_child_field(1, "response");
+        }
+
+        let basic = match node.children.get_mut(1).and_then(|node| node.children.first_mut()).and_then(|node| node.children.get_mut(1)) {
+            Some(response) if response.encapsulated => response.children.first_mut(),
+            _ => None,
+        };
+        let basic = match basic {
+            Some(basic) => basic,
+            None => return,
+        };
+        basic.set_field("BasicOCSPResponse");
+        if let Some(algorithm) = basic.set_child_field(1, "signatureAlgorithm") {
+            x509::annotate_algorithm(algorithm);
+        }
+        basic.set_child_field(2, "signature");
+        basic.set_child_field(3, "certs");
+
+        let response_data = match basic.set_child_field(0, "tbsResponseData") {
+            Some(response_data) => response_data,
+            None => return,
+        };
+        let idx = produced_at_index(response_data);
+        if idx == 2 {
+            response_data.set_child_field(0, "version");
+        }
+        if let Some(responder_id) = response_data.set_child_field(idx - 1, "responderID") {
+            let field = if responder_id.tag.number == 1 { "byName" } else { "byKey" };
+            responder_id.set_child_field(0, field);
+        }
+        response_data.set_child_field(idx, "producedAt");
+        if let Some(responses) = response_data.set_child_field(idx + 1, "responses") {
+            for single in responses.children.iter_mut() {
+                single.set_field("singleResponse");
+                if let Some(cert_id) = single.children.first_mut() {
+                    CertId::annotate(cert_id);
+                }
+                if let Some(cert_status) = single.set_child_field(1, "certStatus") {
+                    cert_status.note = Some(match cert_status.tag.number {
+                        0 => "good".to_owned(),
+                        1 => "revoked".to_owned(),
+                        _ => "unknown".to_owned(),
+                    });
+                    cert_status.set_child_field(0, "revocationTime");
+                    cert_status.set_child_field(1, "revocationReason");
+                }
+                single.set_child_field(2, "thisUpdate");
+                for child in single.children.iter_mut().skip(3) {
+                    match child.tag.number {
+                        0 => child.set_field("nextUpdate"),
+                        _ => annotate_extensions(child, "singleExtensions", bytes),
+                    }
+                }
+            }
+        }
+        if let Some(extensions) = response_data.children.get_mut(idx + 2) {
+            annotate_extensions(extensions, "responseExtensions", bytes);
+        }
+    }
+
+    /// Verify the signature with the embedded or loaded responder certificate,
+    /// and match the CertIDs with the loaded certificates, given as DER.
+    pub fn check(&mut self, node: &Node, bytes: &[u8], certificates: &[Vec<u8>]) -> Result<()> {
+        let basic = basic_response(node, bytes).ok_or(Error("The response doesn't hold a BasicOCSPResponse"))?;
+        let parsed = parse_certificates(certificates);
+        let mut candidates = candidates(&parsed);
+        if let Some(certs) = explicit(basic, 3, 0).and_then(|certs| certs.children.first()) {
+            let embedded = certs.children.iter().map(|node| Candidate { node, bytes });
+            candidates.splice(0..0, embedded);
+        }
+
+        let response_data = basic.child(0, Tag::SEQUENCE)?;
+        let idx = produced_at_index(response_data);
+        let responder_id = response_data.children.get(idx - 1).ok_or(Error("Missing responderID"))?;
+        let responder = responder_id.children.first().ok_or(Error("Missing responderID"))?;
+        let signer = candidates.iter().find(|candidate| {
+            let fields = match x509::Fields::new(candidate.node) {
+                Ok(fields) => fields,
+                Err(_) => return false,
+            };
+            return match responder_id.tag.number {
+                1 => fields.subject.raw(candidate.bytes) == responder.raw(bytes),
+                _ => key_bits(fields.spki, candidate.bytes).map(|bits| hash::Algorithm::Sha1.digest(bits))
+                    == Some(responder.content(bytes).to_vec()),
+            };
+        });
+        self.verification = Some(match signer {
+            Some(signer) => (|| {
+                let fields = x509::Fields::new(signer.node)?;
+                let key = PublicKey::parse(fields.spki, signer.bytes)?;
+                let (_, signature) = value::bit_string(basic.child(2, Tag::BIT_STRING)?.content(bytes))?;
+                signature::verify(&key, &basic.children[1], bytes, None, response_data.raw(bytes), signature)?;
+                return name::format(fields.subject, signer.bytes);
+            })(),
+            None => Err(Error("The responder certificate isn't loaded")),
+        });
+
+        let responses = response_data.child(idx + 1, Tag::SEQUENCE)?;
+        for (single, single_node) in self.responses.iter_mut().zip(responses.children.iter()) {
+            single.cert_id.certificate = CertId::identify(single_node.child(0, Tag::SEQUENCE)?, bytes, &candidates);
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_match_request() {
+        let bytes = include_bytes!("../tests/ocsp-request.der");
+        let (mut nodes, _) = node::parse(bytes);
+        let mut request = OcspRequest::parse(&nodes[0], bytes).unwrap();
+        assert!(!request.signed);
+        assert!(request.requestor.is_none());
+        assert_eq!(request.requests.len(), 1);
+        assert_eq!(request.requests[0].hash_algorithm, "sha1");
+        assert_eq!(hex::hexlify(&request.requests[0].serial), "0123456789abcdef0123");
+        assert_eq!(request.nonce.as_ref().map(Vec::len), Some(16));
+        assert!(OcspResponse::parse(&nodes[0], bytes).is_err());
+
+        let inter = include_bytes!("../tests/inter.der").to_vec();
+        request.check(&nodes[0], bytes, std::slice::from_ref(&inter)).unwrap();
+        let issuer = "unknown certificate issued by CN=Explo-DER Test Intermediate CA,OU=Issuing,O=Explo-DER Test,C=US";
+        assert_eq!(request.requests[0].certificate.as_deref(), Some(issuer));
+        request.check(&nodes[0], bytes, &[inter, include_bytes!("../tests/leaf.der").to_vec()]).unwrap();
+        assert!(request.requests[0].certificate.as_ref().is_some_and(|subject| subject.starts_with("CN=www.example.com")));
+
+        OcspRequest::annotate(&mut nodes[0], bytes);
+        let cert_id = &nodes[0].children[0].children[0].children[0].children[0];
+        assert_eq!(cert_id.field.as_deref(), Some("certID"));
+        assert_eq!(cert_id.children[2].field.as_deref(), Some("issuerKeyHash"));
+    }
+
+    #[test]
+    fn parse_and_verify_response() {
+        let bytes = include_bytes!("../tests/ocsp-response.der");
+        let (mut nodes, _) = node::parse(bytes);
+        let mut response = OcspResponse::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(response.status, "successful");
+        assert_eq!(response.responder.as_deref(), Some("CN=Explo-DER Test Intermediate CA,OU=Issuing,O=Explo-DER Test,C=US"));
+        assert_eq!(response.signature_algorithm.as_deref(), Some("sha256WithRSAEncryption"));
+        assert_eq!(response.certificates.len(), 1);
+        assert_eq!(response.responses.len(), 1);
+        assert!(response.responses[0].status.ends_with("(keyCompromise)"));
+        assert!(response.responses[0].next_update.is_some());
+        assert_eq!(response.nonce.as_ref().map(Vec::len), Some(16));
+        assert!(OcspRequest::parse(&nodes[0], bytes).is_err());
+
+        response.check(&nodes[0], bytes, &[]).unwrap();
+        assert!(response.verification.as_ref().is_some_and(|verification| verification.is_ok()));
+
+        let mut tampered = bytes.to_vec();
+        let serial = tampered.windows(3).position(|window| window == [0x01, 0x23, 0x45]).unwrap();
+        tampered[serial] = 0x02;
+        let (tampered_nodes, _) = node::parse(&tampered);
+        response.check(&tampered_nodes[0], &tampered, &[]).unwrap();
+        assert_eq!(response.verification, Some(Err(Error("Invalid signature"))));
+
+        OcspResponse::annotate(&mut nodes[0], bytes);
+        let basic = &nodes[0].children[1].children[0].children[1].children[0];
+        assert_eq!(basic.field.as_deref(), Some("BasicOCSPResponse"));
+        assert_eq!(basic.children[0].children[1].field.as_deref(), Some("producedAt"));
+    }
+}
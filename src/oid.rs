@@ -33,6 +33,10 @@ pub fn stringify(input: &[u8]) -> Result<String> {
         }
     }
 
+    if (input[input.len() - 1] & 0x80) != 0 {
+        return Err(Error("Last arc is truncated"));
+    }
+
     Ok(result)
 }
 
@@ -64,22 +68,17 @@ pub fn parse_oid(input: &str) -> Result<Vec<u8>> {
 
             // 7 bits are encoded (big-endian style) and all the groups except
             // the last group is written to the output with the highest order
-            // bit set.
+            // bit set. Every group below the most significant non-zero group
+            // must be written, even if it's zero, otherwise the value changes.
             // (32, 28], (28, 21], (21, 14], (14, 7], (7, 0]
-            if (value & 0xf0000000) != 0 {
-                result.push((((value & 0xf0000000) >> 28) as u8) | 0x80);
+            let mut shift = 28;
+            while (shift != 0) && ((value >> shift) == 0) {
+                shift -= 7;
             }
 
-            if (value & 0xfe00000) != 0 {
-                result.push((((value & 0xfe00000) >> 21) as u8) | 0x80);
-            }
-
-            if (value & 0x1fc000) != 0 {
-                result.push((((value & 0x1fc000) >> 14) as u8) | 0x80);
-            }
-
-            if (value & 0x3f80) != 0 {
-                result.push((((value & 0x3f80) >> 7) as u8) | 0x80);
+            while shift != 0 {
+                result.push((((value >> shift) & 0x7F) as u8) | 0x80);
+                shift -= 7;
             }
 
             // Last 7 bits are not serialized with the highest-order bits set.
@@ -139,6 +138,11 @@ mod tests {
         assert!(stringify(b"\x2B\x80\x7F").is_err());
     }
 
+    #[test]
+    fn stringify_truncated_oid() {
+        assert!(stringify(b"\x2A\x86").is_err());
+    }
+
     #[test]
     fn parse_empty_oid() {
         assert!(&parse_oid("").is_err());
@@ -166,4 +170,42 @@ mod tests {
     fn parse_oid_with_node_too_large() {
         assert!(&parse_oid("1.3.42949672956").is_err());
     }
+
+    #[test]
+    fn parse_oid_with_inner_zero_groups() {
+        assert_eq!(&parse_oid("1.3.16384").unwrap(), b"\x2B\x81\x80\x00");
+        assert_eq!(&parse_oid("1.3.2097152").unwrap(), b"\x2B\x81\x80\x80\x00");
+        assert_eq!(&parse_oid("1.3.268435456").unwrap(), b"\x2B\x81\x80\x80\x80\x00");
+        assert_eq!(&parse_oid("1.3.0").unwrap(), b"\x2B\x00");
+    }
+
+    #[test]
+    fn parse_and_stringify_roundtrip() {
+        // Small xorshift generator, so the test is reproducible without
+        // pulling a dependency.
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            return state;
+        };
+
+        for _ in 0..10000 {
+            let mut oid = format!("{}.{}", next() % 3, next() % 40);
+            for _ in 0..(next() % 8) {
+                // Bias toward values with a few 7-bit groups, including zeroes.
+                let value = match next() % 4 {
+                    0 => (next() % 128) as u32,
+                    1 => (1u32 << (7 * (next() % 5))) * ((next() % 2) as u32),
+                    2 => (next() as u32) & 0xFFFFC07F,
+                    _ => next() as u32,
+                };
+                oid.push_str(&format!(".{}", value));
+            }
+
+            let encoded = parse_oid(&oid).unwrap();
+            assert_eq!(stringify(&encoded).unwrap(), oid);
+        }
+    }
 }
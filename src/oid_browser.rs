@@ -0,0 +1,262 @@
+use imgui::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::app::Selection;
+use crate::document::Document;
+use crate::der::{self, Tag};
+use crate::error::{Error, Result};
+use crate::oid_names::{self, OidEntry};
+use crate::{hex, node, oid};
+
+/// Names of the three root arcs, which can't be stored in `oid_names` since a
+/// valid OID has at least two arcs.
+const ROOT_NAMES: [&str; 3] = ["itu-t", "iso", "joint-iso-itu-t"];
+
+/// Tool window converting OIDs between their dotted and DER forms, finding
+/// them in the loaded documents and browsing the known OIDs.
+pub struct OidBrowser {
+    pub opened: bool,
+    input: String,
+    arcs: Option<ArcNode>,
+    occurrences: Option<Occurrences>,
+}
+
+struct ArcNode {
+    entry: Option<Arc<OidEntry>>,
+    children: BTreeMap<u32, ArcNode>,
+}
+
+struct Occurrences {
+    oid: Vec<u8>,
+    generation: usize,
+    found: Vec<(Selection, usize)>,
+}
+
+impl OidBrowser {
+    pub fn new() -> Self {
+        return Self {
+            opened: false,
+            input: String::new(),
+            arcs: None,
+            occurrences: None,
+        };
+    }
+
+    /// Must be called when the known OIDs changed.
+    pub fn invalidate(&mut self) {
+        self.arcs = None;
+    }
+
+    /// Open the window with the given OID.
+    pub fn show(&mut self, oid: &str) {
+        self.opened = true;
+        self.input = oid.to_owned();
+    }
+
+    /// Draw the window and returns the node to navigate to, if any.
+    ///
+    /// `generation` must change whenever `documents` changes.
+    pub fn draw(&mut self, ui: &Ui, documents: &[Document], generation: usize) -> Option<Selection> {
+        if !self.opened {
+            return None;
+        }
+
+        let mut opened = self.opened;
+        let mut navigate = None;
+        ui.window("OID browser")
+            .size([520.0, 600.0], Condition::FirstUseEver)
+            .opened(&mut opened)
+            .build(|| {
+                ui.input_text("OID", &mut self.input)
+                    .hint("1.2.840.113549.1.1.11 or 06 09 2A 86 48 86 F7 0D 01 01 0B")
+                    .build();
+
+                match convert(&self.input) {
+                    Ok(content) => navigate = self.draw_conversion(ui, &content, documents, generation),
+                    Err(_) if self.input.trim().is_empty() => (),
+                    Err(err) => ui.text_colored([1.0, 0.0, 0.0, 1.0], err.0),
+                }
+
+                ui.separator();
+                if ui.collapsing_header("Known OIDs", TreeNodeFlags::empty()) {
+                    let arcs = self.arcs.get_or_insert_with(build_arcs);
+                    if let Some(oid) = draw_arcs(ui, arcs, "") {
+                        self.input = oid;
+                    }
+                }
+            });
+
+        self.opened = opened;
+        return navigate;
+    }
+
+    fn draw_conversion(&mut self, ui: &Ui, content: &[u8], documents: &[Document], generation: usize) -> Option<Selection> {
+        let dotted = match oid::stringify(content) {
+            Ok(dotted) => dotted,
+            Err(err) => {
+                ui.text_colored([1.0, 0.0, 0.0, 1.0], err.0);
+                return None;
+            }
+        };
+
+        let der = der::encode(Tag::OID, content);
+
+        ui.text(format!("Dotted: {}", dotted));
+        ui.text(format!("DER:    {}", hex::hexlify(&der)));
+        if let Some(entry) = oid_names::find(&dotted) {
+            ui.text(format!("Name:   {}", entry.name));
+            ui.text(format!("Desc:   {}", entry.description));
+            ui.text(format!("Ref:    {}", entry.reference));
+        } else {
+            ui.text("Name:   unknown");
+        }
+
+        if ui.small_button("Copy dotted") {
+            ui.set_clipboard_text(&dotted);
+        }
+        ui.same_line();
+        if ui.small_button("Copy DER") {
+            ui.set_clipboard_text(hex::hexlify(&der));
+        }
+
+        ui.separator();
+        ui.text("Arcs:");
+        let arcs: Vec<&str> = dotted.split('.').collect();
+        for count in 1..=arcs.len() {
+            let prefix = arcs[..count].join(".");
+            let name = if count == 1 {
+                arcs[0].parse::<usize>().ok().and_then(|arc| ROOT_NAMES.get(arc)).map(|name| name.to_string())
+            } else {
+                oid_names::find(&prefix).map(|entry| entry.name.to_string())
+            };
+            ui.text(format!("{}{} {}", "  ".repeat(count - 1), prefix, name.unwrap_or_default()));
+        }
+
+        ui.separator();
+        let occurrences = match self.occurrences.take() {
+            Some(occurrences) if occurrences.oid == content && occurrences.generation == generation => occurrences,
+            _ => find_occurrences(content, documents, generation),
+        };
+
+        let mut navigate = None;
+        ui.text(format!("{} occurrence(s) in the loaded documents", occurrences.found.len()));
+        for (selection, offset) in occurrences.found.iter() {
+            let label = format!("{} @ offset {}##{:?}", documents[selection.document].name, offset, selection);
+            if ui.selectable(label) {
+                navigate = Some(selection.clone());
+            }
+        }
+
+        self.occurrences = Some(occurrences);
+        return navigate;
+    }
+}
+
+/// Parse an OID typed either in the dotted form, or as hexadecimal DER, with or
+/// without the identifier and length octets. Returns the content octets.
+pub fn convert(input: &str) -> Result<Vec<u8>> {
+    let input = input.trim();
+    if input.contains('.') {
+        return oid::parse_oid(input);
+    }
+
+    let digits: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect::<String>()
+        .to_lowercase();
+    let bytes = hex::unhexlify(&digits).map_err(|_| Error("Expected a dotted OID or hexadecimal bytes"))?;
+
+    let content = match bytes.as_slice() {
+        [0x06, len, rest @ ..] if usize::from(*len) == rest.len() => rest.to_vec(),
+        _ => bytes,
+    };
+
+    oid::stringify(&content)?;
+    return Ok(content);
+}
+
+fn find_occurrences(content: &[u8], documents: &[Document], generation: usize) -> Occurrences {
+    let mut found = Vec::new();
+    for (idx, document) in documents.iter().enumerate() {
+        node::walk(&document.nodes, &mut |path, node| {
+            if node.tag == Tag::OID && node.content(&document.bytes) == content {
+                found.push((Selection { document: idx, path: path.to_vec() }, node.offset));
+            }
+        });
+    }
+
+    return Occurrences { oid: content.to_vec(), generation, found };
+}
+
+fn build_arcs() -> ArcNode {
+    let mut root = ArcNode { entry: None, children: BTreeMap::new() };
+    for entry in oid_names::entries() {
+        let mut node = &mut root;
+        for arc in entry.oid.split('.') {
+            let arc: u32 = arc.parse().unwrap_or_default();
+            node = node.children.entry(arc).or_insert_with(|| ArcNode { entry: None, children: BTreeMap::new() });
+        }
+        node.entry = Some(entry);
+    }
+    return root;
+}
+
+/// Draw the arcs below `node` and returns the dotted OID that was clicked, if any.
+fn draw_arcs(ui: &Ui, node: &ArcNode, prefix: &str) -> Option<String> {
+    let mut clicked = None;
+    for (arc, child) in node.children.iter() {
+        let dotted = if prefix.is_empty() { arc.to_string() } else { format!("{}.{}", prefix, arc) };
+        let name = match child.entry.as_ref() {
+            Some(entry) => entry.name.to_string(),
+            None if prefix.is_empty() => ROOT_NAMES.get(*arc as usize).copied().unwrap_or_default().to_owned(),
+            None => String::new(),
+        };
+
+        let _id = ui.push_id_usize(*arc as usize);
+        let token = ui
+            .tree_node_config(format!("{} {}", arc, name))
+            .leaf(child.children.is_empty())
+            .open_on_arrow(true)
+            .push();
+
+        if ui.is_item_clicked() && !ui.is_item_toggled_open() && child.entry.is_some() {
+            clicked = Some(dotted.clone());
+        }
+
+        if let Some(entry) = child.entry.as_ref() {
+            if ui.is_item_hovered() {
+                ui.tooltip_text(format!("{}\n{} ({})", dotted, entry.description, entry.reference));
+            }
+        }
+
+        if let Some(_) = token {
+            if let Some(oid) = draw_arcs(ui, child, &dotted) {
+                clicked = Some(oid);
+            }
+        }
+    }
+    return clicked;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_dotted_and_hex() {
+        let expected = b"\x2A\x86\x48\x86\xF7\x0D\x01\x01\x0B".to_vec();
+        assert_eq!(convert("1.2.840.113549.1.1.11").unwrap(), expected);
+        assert_eq!(convert("06 09 2A 86 48 86 F7 0D 01 01 0B").unwrap(), expected);
+        assert_eq!(convert("2a:86:48:86:f7:0d:01:01:0b").unwrap(), expected);
+        assert!(convert("2A 86").is_err());
+        assert!(convert("hello").is_err());
+    }
+
+    #[test]
+    fn arcs_contain_known_oids() {
+        let arcs = build_arcs();
+        let rsa = &arcs.children[&1].children[&2].children[&840].children[&113549];
+        assert_eq!(rsa.entry.as_ref().unwrap().name, "rsadsi");
+    }
+}
@@ -1,26 +1,820 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 use lazy_static::lazy_static;
+use crate::{config, hex, oid};
+
+/// A known OID, with a short name, a longer description and the document
+/// defining it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OidEntry {
+    pub oid: Cow<'static, str>,
+    pub name: Cow<'static, str>,
+    pub description: Cow<'static, str>,
+    pub reference: Cow<'static, str>,
+}
+
+const fn entry(
+    oid: &'static str,
+    name: &'static str,
+    description: &'static str,
+    reference: &'static str,
+) -> OidEntry {
+    return OidEntry {
+        oid: Cow::Borrowed(oid),
+        name: Cow::Borrowed(name),
+        description: Cow::Borrowed(description),
+        reference: Cow::Borrowed(reference),
+    };
+}
+
+#[rustfmt::skip]
+static ENTRIES: &[OidEntry] = &[
+    // Arcs
+    entry("1.2.840", "us", "ISO member body: United States", "X.660"),
+    entry("1.2.840.10045", "ansi-X9-62", "ANSI X9.62 elliptic curve cryptography", "RFC 3279"),
+    entry("1.2.840.113549", "rsadsi", "RSA Data Security, Inc.", "RFC 8017"),
+    entry("1.2.840.113549.1", "pkcs", "Public-Key Cryptography Standards", "RFC 8017"),
+    entry("1.2.840.113549.1.1", "pkcs-1", "PKCS #1 algorithms", "RFC 8017"),
+    entry("1.2.840.113549.1.5", "pkcs-5", "PKCS #5 password-based cryptography", "RFC 8018"),
+    entry("1.2.840.113549.1.7", "pkcs-7", "PKCS #7 content types", "RFC 2315"),
+    entry("1.2.840.113549.1.9", "pkcs-9", "PKCS #9 attributes", "RFC 2985"),
+    entry("1.2.840.113549.1.12", "pkcs-12", "PKCS #12 personal information exchange", "RFC 7292"),
+    entry("1.3.6.1", "internet", "Internet", "RFC 1155"),
+    entry("1.3.6.1.4.1", "enterprise", "Private enterprise numbers", "RFC 1155"),
+    entry("1.3.6.1.4.1.311", "microsoft", "Microsoft", "IANA PEN 311"),
+    entry("1.3.6.1.5.5.7", "pkix", "Public-Key Infrastructure using X.509", "RFC 5280"),
+    entry("1.3.132.0", "certicom-curve", "SEC 2 elliptic curves", "SEC 2"),
+    entry("2.5.4", "attributeType", "X.500 attribute types", "X.520"),
+    entry("2.5.29", "certificateExtension", "X.509 certificate extensions", "RFC 5280"),
+    entry("2.16.840.1.101.3.4", "nistAlgorithm", "NIST algorithms", "NIST CSOR"),
+    entry("2.23.140", "ca-browser-forum", "CA/Browser Forum", "CA/B Forum Baseline Requirements"),
+
+    // X.500 attribute types
+    entry("2.5.4.0", "objectClass", "Object class", "X.520"),
+    entry("2.5.4.1", "aliasedEntryName", "Aliased entry name", "X.520"),
+    entry("2.5.4.2", "knowledgeInformation", "Knowledge information", "X.520"),
+    entry("2.5.4.3", "commonName", "Common name (CN)", "RFC 5280"),
+    entry("2.5.4.4", "surname", "Surname (SN)", "RFC 5280"),
+    entry("2.5.4.5", "serialNumber", "Serial number of the subject", "RFC 5280"),
+    entry("2.5.4.6", "countryName", "Country name (C)", "RFC 5280"),
+    entry("2.5.4.7", "localityName", "Locality name (L)", "RFC 5280"),
+    entry("2.5.4.8", "stateOrProvinceName", "State or province name (ST)", "RFC 5280"),
+    entry("2.5.4.9", "streetAddress", "Street address", "RFC 4519"),
+    entry("2.5.4.10", "organizationName", "Organization name (O)", "RFC 5280"),
+    entry("2.5.4.11", "organizationalUnitName", "Organizational unit name (OU)", "RFC 5280"),
+    entry("2.5.4.12", "title", "Title", "RFC 5280"),
+    entry("2.5.4.13", "description", "Description", "RFC 4519"),
+    entry("2.5.4.14", "searchGuide", "Search guide", "RFC 4519"),
+    entry("2.5.4.15", "businessCategory", "Business category", "RFC 4519"),
+    entry("2.5.4.16", "postalAddress", "Postal address", "RFC 4519"),
+    entry("2.5.4.17", "postalCode", "Postal code", "RFC 4519"),
+    entry("2.5.4.18", "postOfficeBox", "Post office box", "RFC 4519"),
+    entry("2.5.4.19", "physicalDeliveryOfficeName", "Physical delivery office name", "RFC 4519"),
+    entry("2.5.4.20", "telephoneNumber", "Telephone number", "RFC 4519"),
+    entry("2.5.4.23", "facsimileTelephoneNumber", "Facsimile telephone number", "RFC 4519"),
+    entry("2.5.4.26", "registeredAddress", "Registered address", "RFC 4519"),
+    entry("2.5.4.31", "member", "Member", "RFC 4519"),
+    entry("2.5.4.32", "owner", "Owner", "RFC 4519"),
+    entry("2.5.4.34", "seeAlso", "See also", "RFC 4519"),
+    entry("2.5.4.35", "userPassword", "User password", "RFC 4519"),
+    entry("2.5.4.36", "userCertificate", "User certificate", "RFC 4523"),
+    entry("2.5.4.37", "cACertificate", "CA certificate", "RFC 4523"),
+    entry("2.5.4.38", "authorityRevocationList", "Authority revocation list", "RFC 4523"),
+    entry("2.5.4.39", "certificateRevocationList", "Certificate revocation list", "RFC 4523"),
+    entry("2.5.4.40", "crossCertificatePair", "Cross certificate pair", "RFC 4523"),
+    entry("2.5.4.41", "name", "Name", "RFC 5280"),
+    entry("2.5.4.42", "givenName", "Given name (GN)", "RFC 5280"),
+    entry("2.5.4.43", "initials", "Initials", "RFC 5280"),
+    entry("2.5.4.44", "generationQualifier", "Generation qualifier", "RFC 5280"),
+    entry("2.5.4.45", "x500UniqueIdentifier", "X.500 unique identifier", "RFC 4519"),
+    entry("2.5.4.46", "dnQualifier", "Distinguished name qualifier", "RFC 5280"),
+    entry("2.5.4.49", "distinguishedName", "Distinguished name", "RFC 4519"),
+    entry("2.5.4.50", "uniqueMember", "Unique member", "RFC 4519"),
+    entry("2.5.4.51", "houseIdentifier", "House identifier", "RFC 4519"),
+    entry("2.5.4.54", "dmdName", "Directory management domain name", "X.520"),
+    entry("2.5.4.58", "attributeCertificate", "Attribute certificate", "X.509"),
+    entry("2.5.4.65", "pseudonym", "Pseudonym", "RFC 5280"),
+    entry("2.5.4.72", "role", "Role", "X.520"),
+    entry("2.5.4.97", "organizationIdentifier", "Organization identifier", "X.520"),
+    entry("0.9.2342.19200300.100.1.1", "userId", "User identifier (UID)", "RFC 4519"),
+    entry("0.9.2342.19200300.100.1.3", "mail", "RFC 822 mailbox", "RFC 4524"),
+    entry("0.9.2342.19200300.100.1.25", "domainComponent", "Domain component (DC)", "RFC 4519"),
+    entry("1.3.6.1.4.1.311.60.2.1.1", "jurisdictionLocalityName", "EV jurisdiction of incorporation, locality", "CA/B Forum EV Guidelines"),
+    entry("1.3.6.1.4.1.311.60.2.1.2", "jurisdictionStateOrProvinceName", "EV jurisdiction of incorporation, state or province", "CA/B Forum EV Guidelines"),
+    entry("1.3.6.1.4.1.311.60.2.1.3", "jurisdictionCountryName", "EV jurisdiction of incorporation, country", "CA/B Forum EV Guidelines"),
+    entry("1.3.6.1.5.5.7.9.1", "dateOfBirth", "Date of birth", "RFC 3739"),
+    entry("1.3.6.1.5.5.7.9.2", "placeOfBirth", "Place of birth", "RFC 3739"),
+    entry("1.3.6.1.5.5.7.9.3", "gender", "Gender", "RFC 3739"),
+    entry("1.3.6.1.5.5.7.9.4", "countryOfCitizenship", "Country of citizenship", "RFC 3739"),
+    entry("1.3.6.1.5.5.7.9.5", "countryOfResidence", "Country of residence", "RFC 3739"),
+
+    // X.509v3 certificate and CRL extensions
+    entry("2.5.29.9", "subjectDirectoryAttributes", "Subject directory attributes", "RFC 5280"),
+    entry("2.5.29.14", "subjectKeyIdentifier", "Subject key identifier", "RFC 5280"),
+    entry("2.5.29.15", "keyUsage", "Key usage", "RFC 5280"),
+    entry("2.5.29.16", "privateKeyUsagePeriod", "Private key usage period", "RFC 3280"),
+    entry("2.5.29.17", "subjectAltName", "Subject alternative name", "RFC 5280"),
+    entry("2.5.29.18", "issuerAltName", "Issuer alternative name", "RFC 5280"),
+    entry("2.5.29.19", "basicConstraints", "Basic constraints", "RFC 5280"),
+    entry("2.5.29.20", "cRLNumber", "CRL number", "RFC 5280"),
+    entry("2.5.29.21", "cRLReason", "CRL entry reason code", "RFC 5280"),
+    entry("2.5.29.23", "holdInstructionCode", "CRL entry hold instruction code", "RFC 5280"),
+    entry("2.5.29.24", "invalidityDate", "CRL entry invalidity date", "RFC 5280"),
+    entry("2.5.29.27", "deltaCRLIndicator", "Delta CRL indicator", "RFC 5280"),
+    entry("2.5.29.28", "issuingDistributionPoint", "Issuing distribution point", "RFC 5280"),
+    entry("2.5.29.29", "certificateIssuer", "CRL entry certificate issuer", "RFC 5280"),
+    entry("2.5.29.30", "nameConstraints", "Name constraints", "RFC 5280"),
+    entry("2.5.29.31", "cRLDistributionPoints", "CRL distribution points", "RFC 5280"),
+    entry("2.5.29.32", "certificatePolicies", "Certificate policies", "RFC 5280"),
+    entry("2.5.29.32.0", "anyPolicy", "Any certificate policy", "RFC 5280"),
+    entry("2.5.29.33", "policyMappings", "Policy mappings", "RFC 5280"),
+    entry("2.5.29.35", "authorityKeyIdentifier", "Authority key identifier", "RFC 5280"),
+    entry("2.5.29.36", "policyConstraints", "Policy constraints", "RFC 5280"),
+    entry("2.5.29.37", "extKeyUsage", "Extended key usage", "RFC 5280"),
+    entry("2.5.29.37.0", "anyExtendedKeyUsage", "Any extended key usage", "RFC 5280"),
+    entry("2.5.29.46", "freshestCRL", "Freshest CRL (delta CRL distribution points)", "RFC 5280"),
+    entry("2.5.29.54", "inhibitAnyPolicy", "Inhibit any policy", "RFC 5280"),
+    entry("2.5.29.55", "targetInformation", "Attribute certificate targeting", "RFC 5755"),
+    entry("2.5.29.56", "noRevAvail", "No revocation available", "RFC 5755"),
+
+    // PKIX private extensions (id-pe)
+    entry("1.3.6.1.5.5.7.1.1", "authorityInfoAccess", "Authority information access", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.1.2", "biometricInfo", "Biometric information", "RFC 3739"),
+    entry("1.3.6.1.5.5.7.1.3", "qcStatements", "Qualified certificate statements", "RFC 3739"),
+    entry("1.3.6.1.5.5.7.1.11", "subjectInfoAccess", "Subject information access", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.1.12", "logotype", "Logotype", "RFC 9399"),
+    entry("1.3.6.1.5.5.7.1.24", "tlsFeature", "TLS feature (OCSP must-staple)", "RFC 7633"),
+
+    // PKIX policy qualifiers (id-qt)
+    entry("1.3.6.1.5.5.7.2.1", "cps", "Certification practice statement pointer", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.2.2", "unotice", "User notice", "RFC 5280"),
+
+    // PKIX extended key usages (id-kp)
+    entry("1.3.6.1.5.5.7.3.1", "serverAuth", "TLS web server authentication", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.3.2", "clientAuth", "TLS web client authentication", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.3.3", "codeSigning", "Code signing", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.3.4", "emailProtection", "E-mail protection", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.3.5", "ipsecEndSystem", "IPsec end system", "RFC 2459"),
+    entry("1.3.6.1.5.5.7.3.6", "ipsecTunnel", "IPsec tunnel", "RFC 2459"),
+    entry("1.3.6.1.5.5.7.3.7", "ipsecUser", "IPsec user", "RFC 2459"),
+    entry("1.3.6.1.5.5.7.3.8", "timeStamping", "Time stamping", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.3.9", "OCSPSigning", "OCSP signing", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.3.17", "ipsecIKE", "IPsec Internet Key Exchange", "RFC 4945"),
+    entry("1.3.6.1.5.5.7.3.21", "secureShellClient", "SSH client", "RFC 6187"),
+    entry("1.3.6.1.5.5.7.3.22", "secureShellServer", "SSH server", "RFC 6187"),
+
+    // PKIX other names (id-on)
+    entry("1.3.6.1.5.5.7.8.3", "permanentIdentifier", "Permanent identifier", "RFC 4043"),
+    entry("1.3.6.1.5.5.7.8.4", "hardwareModuleName", "Hardware module name", "RFC 4108"),
+    entry("1.3.6.1.5.5.7.8.9", "SmtpUTF8Mailbox", "Internationalized e-mail address", "RFC 9598"),
+
+    // PKIX access descriptors (id-ad)
+    entry("1.3.6.1.5.5.7.48.1", "ocsp", "Online Certificate Status Protocol", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.48.2", "caIssuers", "CA issuers", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.48.3", "timeStamping", "Time stamping service", "RFC 5280"),
+    entry("1.3.6.1.5.5.7.48.5", "caRepository", "CA repository", "RFC 5280"),
+
+    // OCSP
+    entry("1.3.6.1.5.5.7.48.1.1", "basicOCSPResponse", "Basic OCSP response", "RFC 6960"),
+    entry("1.3.6.1.5.5.7.48.1.2", "ocspNonce", "OCSP nonce", "RFC 6960"),
+    entry("1.3.6.1.5.5.7.48.1.3", "ocspCrlId", "OCSP CRL reference", "RFC 6960"),
+    entry("1.3.6.1.5.5.7.48.1.4", "acceptableResponses", "OCSP acceptable responses", "RFC 6960"),
+    entry("1.3.6.1.5.5.7.48.1.5", "ocspNoCheck", "OCSP no check", "RFC 6960"),
+    entry("1.3.6.1.5.5.7.48.1.6", "ocspArchiveCutoff", "OCSP archive cutoff", "RFC 6960"),
+    entry("1.3.6.1.5.5.7.48.1.7", "ocspServiceLocator", "OCSP service locator", "RFC 6960"),
+    entry("1.3.6.1.5.5.7.48.1.8", "ocspPrefSigAlgs", "OCSP preferred signature algorithms", "RFC 6960"),
+    entry("1.3.6.1.5.5.7.48.1.9", "ocspExtendedRevoke", "OCSP extended revoked definition", "RFC 6960"),
+
+    // PKIX qualified certificate statements
+    entry("1.3.6.1.5.5.7.11.1", "qcs-pkixQCSyntax-v1", "Qualified certificate syntax, version 1", "RFC 3039"),
+    entry("1.3.6.1.5.5.7.11.2", "qcs-pkixQCSyntax-v2", "Qualified certificate syntax, version 2", "RFC 3739"),
+
+    // PKCS #1
+    entry("1.2.840.113549.1.1.1", "rsaEncryption", "RSA encryption", "RFC 8017"),
+    entry("1.2.840.113549.1.1.2", "md2WithRSAEncryption", "RSA signature with MD2", "RFC 8017"),
+    entry("1.2.840.113549.1.1.4", "md5WithRSAEncryption", "RSA signature with MD5", "RFC 8017"),
+    entry("1.2.840.113549.1.1.5", "sha1WithRSAEncryption", "RSA PKCS #1 v1.5 signature with SHA-1", "RFC 8017"),
+    entry("1.2.840.113549.1.1.7", "rsaesOaep", "RSAES-OAEP encryption", "RFC 8017"),
+    entry("1.2.840.113549.1.1.8", "mgf1", "Mask generation function 1", "RFC 8017"),
+    entry("1.2.840.113549.1.1.9", "pSpecified", "OAEP encoding parameters", "RFC 8017"),
+    entry("1.2.840.113549.1.1.10", "rsassaPss", "RSASSA-PSS signature", "RFC 8017"),
+    entry("1.2.840.113549.1.1.11", "sha256WithRSAEncryption", "RSA PKCS #1 v1.5 signature with SHA-256", "RFC 8017"),
+    entry("1.2.840.113549.1.1.12", "sha384WithRSAEncryption", "RSA PKCS #1 v1.5 signature with SHA-384", "RFC 8017"),
+    entry("1.2.840.113549.1.1.13", "sha512WithRSAEncryption", "RSA PKCS #1 v1.5 signature with SHA-512", "RFC 8017"),
+    entry("1.2.840.113549.1.1.14", "sha224WithRSAEncryption", "RSA PKCS #1 v1.5 signature with SHA-224", "RFC 8017"),
+    entry("1.2.840.113549.1.1.15", "sha512-224WithRSAEncryption", "RSA PKCS #1 v1.5 signature with SHA-512/224", "RFC 8017"),
+    entry("1.2.840.113549.1.1.16", "sha512-256WithRSAEncryption", "RSA PKCS #1 v1.5 signature with SHA-512/256", "RFC 8017"),
+
+    // PKCS #3
+    entry("1.2.840.113549.1.3.1", "dhKeyAgreement", "Diffie-Hellman key agreement", "PKCS #3"),
+
+    // PKCS #5
+    entry("1.2.840.113549.1.5.1", "pbeWithMD2AndDES-CBC", "PBES1 with MD2 and DES-CBC", "RFC 8018"),
+    entry("1.2.840.113549.1.5.3", "pbeWithMD5AndDES-CBC", "PBES1 with MD5 and DES-CBC", "RFC 8018"),
+    entry("1.2.840.113549.1.5.4", "pbeWithMD2AndRC2-CBC", "PBES1 with MD2 and RC2-CBC", "RFC 8018"),
+    entry("1.2.840.113549.1.5.6", "pbeWithMD5AndRC2-CBC", "PBES1 with MD5 and RC2-CBC", "RFC 8018"),
+    entry("1.2.840.113549.1.5.10", "pbeWithSHA1AndDES-CBC", "PBES1 with SHA-1 and DES-CBC", "RFC 8018"),
+    entry("1.2.840.113549.1.5.11", "pbeWithSHA1AndRC2-CBC", "PBES1 with SHA-1 and RC2-CBC", "RFC 8018"),
+    entry("1.2.840.113549.1.5.12", "pbkdf2", "Password-based key derivation function 2", "RFC 8018"),
+    entry("1.2.840.113549.1.5.13", "pbes2", "Password-based encryption scheme 2", "RFC 8018"),
+    entry("1.2.840.113549.1.5.14", "pbmac1", "Password-based message authentication scheme 1", "RFC 8018"),
+
+    // PKCS #7 / CMS content types
+    entry("1.2.840.113549.1.7.1", "data", "Arbitrary octet string content", "RFC 5652"),
+    entry("1.2.840.113549.1.7.2", "signedData", "Signed data", "RFC 5652"),
+    entry("1.2.840.113549.1.7.3", "envelopedData", "Enveloped data", "RFC 5652"),
+    entry("1.2.840.113549.1.7.4", "signedAndEnvelopedData", "Signed and enveloped data", "RFC 2315"),
+    entry("1.2.840.113549.1.7.5", "digestedData", "Digested data", "RFC 5652"),
+    entry("1.2.840.113549.1.7.6", "encryptedData", "Encrypted data", "RFC 5652"),
+
+    // PKCS #9
+    entry("1.2.840.113549.1.9.1", "emailAddress", "E-mail address", "RFC 2985"),
+    entry("1.2.840.113549.1.9.2", "unstructuredName", "Unstructured name", "RFC 2985"),
+    entry("1.2.840.113549.1.9.3", "contentType", "CMS content type attribute", "RFC 5652"),
+    entry("1.2.840.113549.1.9.4", "messageDigest", "CMS message digest attribute", "RFC 5652"),
+    entry("1.2.840.113549.1.9.5", "signingTime", "CMS signing time attribute", "RFC 5652"),
+    entry("1.2.840.113549.1.9.6", "countersignature", "CMS countersignature attribute", "RFC 5652"),
+    entry("1.2.840.113549.1.9.7", "challengePassword", "Challenge password", "RFC 2985"),
+    entry("1.2.840.113549.1.9.8", "unstructuredAddress", "Unstructured address", "RFC 2985"),
+    entry("1.2.840.113549.1.9.9", "extendedCertificateAttributes", "Extended certificate attributes", "RFC 2985"),
+    entry("1.2.840.113549.1.9.14", "extensionRequest", "Certificate extension request", "RFC 2985"),
+    entry("1.2.840.113549.1.9.15", "smimeCapabilities", "S/MIME capabilities", "RFC 8551"),
+    entry("1.2.840.113549.1.9.16", "smime", "S/MIME arc", "RFC 5652"),
+    entry("1.2.840.113549.1.9.20", "friendlyName", "Friendly name", "RFC 2985"),
+    entry("1.2.840.113549.1.9.21", "localKeyID", "Local key identifier", "RFC 2985"),
+    entry("1.2.840.113549.1.9.22.1", "x509Certificate", "X.509 certificate (PKCS #12 certBag type)", "RFC 7292"),
+    entry("1.2.840.113549.1.9.22.2", "sdsiCertificate", "SDSI certificate (PKCS #12 certBag type)", "RFC 7292"),
+    entry("1.2.840.113549.1.9.23.1", "x509Crl", "X.509 CRL (PKCS #12 crlBag type)", "RFC 7292"),
+    entry("1.2.840.113549.1.9.52", "cmsAlgorithmProtection", "CMS algorithm protection attribute", "RFC 6211"),
+
+    // S/MIME content types (id-ct)
+    entry("1.2.840.113549.1.9.16.1.1", "receipt", "Signed receipt", "RFC 2634"),
+    entry("1.2.840.113549.1.9.16.1.2", "authData", "Authenticated data", "RFC 5652"),
+    entry("1.2.840.113549.1.9.16.1.4", "TSTInfo", "Time-stamp token information", "RFC 3161"),
+    entry("1.2.840.113549.1.9.16.1.9", "compressedData", "Compressed data", "RFC 3274"),
+    entry("1.2.840.113549.1.9.16.1.23", "authEnvelopedData", "Authenticated enveloped data", "RFC 5083"),
+
+    // S/MIME attributes (id-aa)
+    entry("1.2.840.113549.1.9.16.2.1", "receiptRequest", "Receipt request", "RFC 2634"),
+    entry("1.2.840.113549.1.9.16.2.4", "contentHint", "Content hint", "RFC 2634"),
+    entry("1.2.840.113549.1.9.16.2.12", "signingCertificate", "Signing certificate", "RFC 2634"),
+    entry("1.2.840.113549.1.9.16.2.14", "timeStampToken", "Signature time-stamp token", "RFC 3161"),
+    entry("1.2.840.113549.1.9.16.2.47", "signingCertificateV2", "Signing certificate v2", "RFC 5035"),
+
+    // S/MIME algorithms (id-alg)
+    entry("1.2.840.113549.1.9.16.3.6", "cms3DESwrap", "Triple-DES key wrap", "RFC 3217"),
+    entry("1.2.840.113549.1.9.16.3.9", "pwriKek", "Password recipient key encryption key", "RFC 3211"),
+    entry("1.2.840.113549.1.9.16.3.18", "chacha20Poly1305", "ChaCha20-Poly1305 content encryption", "RFC 8103"),
+
+    // PKCS #12
+    entry("1.2.840.113549.1.12.1.1", "pbeWithSHAAnd128BitRC4", "PKCS #12 PBE with SHA-1 and 128-bit RC4", "RFC 7292"),
+    entry("1.2.840.113549.1.12.1.2", "pbeWithSHAAnd40BitRC4", "PKCS #12 PBE with SHA-1 and 40-bit RC4", "RFC 7292"),
+    entry("1.2.840.113549.1.12.1.3", "pbeWithSHAAnd3-KeyTripleDES-CBC", "PKCS #12 PBE with SHA-1 and 3-key Triple DES", "RFC 7292"),
+    entry("1.2.840.113549.1.12.1.4", "pbeWithSHAAnd2-KeyTripleDES-CBC", "PKCS #12 PBE with SHA-1 and 2-key Triple DES", "RFC 7292"),
+    entry("1.2.840.113549.1.12.1.5", "pbeWithSHAAnd128BitRC2-CBC", "PKCS #12 PBE with SHA-1 and 128-bit RC2", "RFC 7292"),
+    entry("1.2.840.113549.1.12.1.6", "pbeWithSHAAnd40BitRC2-CBC", "PKCS #12 PBE with SHA-1 and 40-bit RC2", "RFC 7292"),
+    entry("1.2.840.113549.1.12.10.1.1", "keyBag", "PKCS #12 key bag", "RFC 7292"),
+    entry("1.2.840.113549.1.12.10.1.2", "pkcs8ShroudedKeyBag", "PKCS #12 shrouded key bag", "RFC 7292"),
+    entry("1.2.840.113549.1.12.10.1.3", "certBag", "PKCS #12 certificate bag", "RFC 7292"),
+    entry("1.2.840.113549.1.12.10.1.4", "crlBag", "PKCS #12 CRL bag", "RFC 7292"),
+    entry("1.2.840.113549.1.12.10.1.5", "secretBag", "PKCS #12 secret bag", "RFC 7292"),
+    entry("1.2.840.113549.1.12.10.1.6", "safeContentsBag", "PKCS #12 nested safe contents bag", "RFC 7292"),
+
+    // RSADSI digest and MAC algorithms
+    entry("1.2.840.113549.2.2", "md2", "MD2 hash", "RFC 1319"),
+    entry("1.2.840.113549.2.5", "md5", "MD5 hash", "RFC 1321"),
+    entry("1.2.840.113549.2.7", "hmacWithSHA1", "HMAC with SHA-1", "RFC 8018"),
+    entry("1.2.840.113549.2.8", "hmacWithSHA224", "HMAC with SHA-224", "RFC 8018"),
+    entry("1.2.840.113549.2.9", "hmacWithSHA256", "HMAC with SHA-256", "RFC 8018"),
+    entry("1.2.840.113549.2.10", "hmacWithSHA384", "HMAC with SHA-384", "RFC 8018"),
+    entry("1.2.840.113549.2.11", "hmacWithSHA512", "HMAC with SHA-512", "RFC 8018"),
+    entry("1.2.840.113549.2.12", "hmacWithSHA512-224", "HMAC with SHA-512/224", "RFC 8018"),
+    entry("1.2.840.113549.2.13", "hmacWithSHA512-256", "HMAC with SHA-512/256", "RFC 8018"),
+
+    // RSADSI encryption algorithms
+    entry("1.2.840.113549.3.2", "rc2CBC", "RC2 in CBC mode", "RFC 8018"),
+    entry("1.2.840.113549.3.4", "rc4", "RC4 stream cipher", "RFC 2313"),
+    entry("1.2.840.113549.3.7", "des-ede3-cbc", "Triple DES in CBC mode", "RFC 8018"),
+    entry("1.2.840.113549.3.9", "rc5-CBC-PAD", "RC5 in CBC mode with padding", "RFC 8018"),
+
+    // OIW
+    entry("1.3.14.3.2.7", "desCBC", "DES in CBC mode", "RFC 8018"),
+    entry("1.3.14.3.2.26", "sha1", "SHA-1 hash", "RFC 3279"),
+    entry("1.3.14.3.2.29", "sha1WithRSASignature", "RSA signature with SHA-1 (OIW)", "RFC 3279"),
+
+    // ANSI X9.57 / X9.42 / X9.62
+    entry("1.2.840.10040.4.1", "dsa", "Digital Signature Algorithm", "RFC 3279"),
+    entry("1.2.840.10040.4.3", "dsa-with-sha1", "DSA signature with SHA-1", "RFC 3279"),
+    entry("1.2.840.10046.2.1", "dhpublicnumber", "Diffie-Hellman public key", "RFC 3279"),
+    entry("1.2.840.10045.1.1", "prime-field", "Prime field", "RFC 3279"),
+    entry("1.2.840.10045.1.2", "characteristic-two-field", "Characteristic two field", "RFC 3279"),
+    entry("1.2.840.10045.2.1", "ecPublicKey", "Elliptic curve public key", "RFC 5480"),
+    entry("1.2.840.10045.3.1.1", "prime192v1", "NIST P-192 elliptic curve", "RFC 5480"),
+    entry("1.2.840.10045.3.1.7", "prime256v1", "NIST P-256 elliptic curve", "RFC 5480"),
+    entry("1.2.840.10045.4.1", "ecdsa-with-SHA1", "ECDSA signature with SHA-1", "RFC 3279"),
+    entry("1.2.840.10045.4.3.1", "ecdsa-with-SHA224", "ECDSA signature with SHA-224", "RFC 5758"),
+    entry("1.2.840.10045.4.3.2", "ecdsa-with-SHA256", "ECDSA signature with SHA-256", "RFC 5758"),
+    entry("1.2.840.10045.4.3.3", "ecdsa-with-SHA384", "ECDSA signature with SHA-384", "RFC 5758"),
+    entry("1.2.840.10045.4.3.4", "ecdsa-with-SHA512", "ECDSA signature with SHA-512", "RFC 5758"),
+
+    // NIST hash algorithms
+    entry("2.16.840.1.101.3.4.2.1", "sha256", "SHA-256 hash", "RFC 5754"),
+    entry("2.16.840.1.101.3.4.2.2", "sha384", "SHA-384 hash", "RFC 5754"),
+    entry("2.16.840.1.101.3.4.2.3", "sha512", "SHA-512 hash", "RFC 5754"),
+    entry("2.16.840.1.101.3.4.2.4", "sha224", "SHA-224 hash", "RFC 5754"),
+    entry("2.16.840.1.101.3.4.2.5", "sha512-224", "SHA-512/224 hash", "FIPS 180-4"),
+    entry("2.16.840.1.101.3.4.2.6", "sha512-256", "SHA-512/256 hash", "FIPS 180-4"),
+    entry("2.16.840.1.101.3.4.2.7", "sha3-224", "SHA3-224 hash", "FIPS 202"),
+    entry("2.16.840.1.101.3.4.2.8", "sha3-256", "SHA3-256 hash", "FIPS 202"),
+    entry("2.16.840.1.101.3.4.2.9", "sha3-384", "SHA3-384 hash", "FIPS 202"),
+    entry("2.16.840.1.101.3.4.2.10", "sha3-512", "SHA3-512 hash", "FIPS 202"),
+    entry("2.16.840.1.101.3.4.2.11", "shake128", "SHAKE128 extendable-output function", "RFC 8702"),
+    entry("2.16.840.1.101.3.4.2.12", "shake256", "SHAKE256 extendable-output function", "RFC 8702"),
+    entry("2.16.840.1.101.3.4.2.13", "hmacWithSHA3-224", "HMAC with SHA3-224", "FIPS 202"),
+    entry("2.16.840.1.101.3.4.2.14", "hmacWithSHA3-256", "HMAC with SHA3-256", "FIPS 202"),
+    entry("2.16.840.1.101.3.4.2.15", "hmacWithSHA3-384", "HMAC with SHA3-384", "FIPS 202"),
+    entry("2.16.840.1.101.3.4.2.16", "hmacWithSHA3-512", "HMAC with SHA3-512", "FIPS 202"),
+
+    // NIST AES
+    entry("2.16.840.1.101.3.4.1.1", "aes128-ECB", "AES-128 in ECB mode", "FIPS 197"),
+    entry("2.16.840.1.101.3.4.1.2", "aes128-CBC", "AES-128 in CBC mode", "RFC 3565"),
+    entry("2.16.840.1.101.3.4.1.3", "aes128-OFB", "AES-128 in OFB mode", "FIPS 197"),
+    entry("2.16.840.1.101.3.4.1.4", "aes128-CFB", "AES-128 in CFB mode", "FIPS 197"),
+    entry("2.16.840.1.101.3.4.1.5", "aes128-wrap", "AES-128 key wrap", "RFC 3394"),
+    entry("2.16.840.1.101.3.4.1.6", "aes128-GCM", "AES-128 in GCM mode", "RFC 5084"),
+    entry("2.16.840.1.101.3.4.1.7", "aes128-CCM", "AES-128 in CCM mode", "RFC 5084"),
+    entry("2.16.840.1.101.3.4.1.8", "aes128-wrap-pad", "AES-128 key wrap with padding", "RFC 5649"),
+    entry("2.16.840.1.101.3.4.1.21", "aes192-ECB", "AES-192 in ECB mode", "FIPS 197"),
+    entry("2.16.840.1.101.3.4.1.22", "aes192-CBC", "AES-192 in CBC mode", "RFC 3565"),
+    entry("2.16.840.1.101.3.4.1.25", "aes192-wrap", "AES-192 key wrap", "RFC 3394"),
+    entry("2.16.840.1.101.3.4.1.26", "aes192-GCM", "AES-192 in GCM mode", "RFC 5084"),
+    entry("2.16.840.1.101.3.4.1.27", "aes192-CCM", "AES-192 in CCM mode", "RFC 5084"),
+    entry("2.16.840.1.101.3.4.1.28", "aes192-wrap-pad", "AES-192 key wrap with padding", "RFC 5649"),
+    entry("2.16.840.1.101.3.4.1.41", "aes256-ECB", "AES-256 in ECB mode", "FIPS 197"),
+    entry("2.16.840.1.101.3.4.1.42", "aes256-CBC", "AES-256 in CBC mode", "RFC 3565"),
+    entry("2.16.840.1.101.3.4.1.45", "aes256-wrap", "AES-256 key wrap", "RFC 3394"),
+    entry("2.16.840.1.101.3.4.1.46", "aes256-GCM", "AES-256 in GCM mode", "RFC 5084"),
+    entry("2.16.840.1.101.3.4.1.47", "aes256-CCM", "AES-256 in CCM mode", "RFC 5084"),
+    entry("2.16.840.1.101.3.4.1.48", "aes256-wrap-pad", "AES-256 key wrap with padding", "RFC 5649"),
+
+    // NIST signature algorithms
+    entry("2.16.840.1.101.3.4.3.1", "dsa-with-sha224", "DSA signature with SHA-224", "RFC 5758"),
+    entry("2.16.840.1.101.3.4.3.2", "dsa-with-sha256", "DSA signature with SHA-256", "RFC 5758"),
+    entry("2.16.840.1.101.3.4.3.9", "ecdsa-with-SHA3-224", "ECDSA signature with SHA3-224", "RFC 9688"),
+    entry("2.16.840.1.101.3.4.3.10", "ecdsa-with-SHA3-256", "ECDSA signature with SHA3-256", "RFC 9688"),
+    entry("2.16.840.1.101.3.4.3.11", "ecdsa-with-SHA3-384", "ECDSA signature with SHA3-384", "RFC 9688"),
+    entry("2.16.840.1.101.3.4.3.12", "ecdsa-with-SHA3-512", "ECDSA signature with SHA3-512", "RFC 9688"),
+    entry("2.16.840.1.101.3.4.3.13", "rsassa-pkcs1-v1_5-with-sha3-224", "RSA PKCS #1 v1.5 signature with SHA3-224", "RFC 9688"),
+    entry("2.16.840.1.101.3.4.3.14", "rsassa-pkcs1-v1_5-with-sha3-256", "RSA PKCS #1 v1.5 signature with SHA3-256", "RFC 9688"),
+    entry("2.16.840.1.101.3.4.3.15", "rsassa-pkcs1-v1_5-with-sha3-384", "RSA PKCS #1 v1.5 signature with SHA3-384", "RFC 9688"),
+    entry("2.16.840.1.101.3.4.3.16", "rsassa-pkcs1-v1_5-with-sha3-512", "RSA PKCS #1 v1.5 signature with SHA3-512", "RFC 9688"),
+
+    // NIST post-quantum algorithms
+    entry("2.16.840.1.101.3.4.3.17", "ml-dsa-44", "ML-DSA-44 signature", "FIPS 204"),
+    entry("2.16.840.1.101.3.4.3.18", "ml-dsa-65", "ML-DSA-65 signature", "FIPS 204"),
+    entry("2.16.840.1.101.3.4.3.19", "ml-dsa-87", "ML-DSA-87 signature", "FIPS 204"),
+    entry("2.16.840.1.101.3.4.3.20", "slh-dsa-sha2-128s", "SLH-DSA-SHA2-128s signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.21", "slh-dsa-sha2-128f", "SLH-DSA-SHA2-128f signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.22", "slh-dsa-sha2-192s", "SLH-DSA-SHA2-192s signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.23", "slh-dsa-sha2-192f", "SLH-DSA-SHA2-192f signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.24", "slh-dsa-sha2-256s", "SLH-DSA-SHA2-256s signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.25", "slh-dsa-sha2-256f", "SLH-DSA-SHA2-256f signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.26", "slh-dsa-shake-128s", "SLH-DSA-SHAKE-128s signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.27", "slh-dsa-shake-128f", "SLH-DSA-SHAKE-128f signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.28", "slh-dsa-shake-192s", "SLH-DSA-SHAKE-192s signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.29", "slh-dsa-shake-192f", "SLH-DSA-SHAKE-192f signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.30", "slh-dsa-shake-256s", "SLH-DSA-SHAKE-256s signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.31", "slh-dsa-shake-256f", "SLH-DSA-SHAKE-256f signature", "FIPS 205"),
+    entry("2.16.840.1.101.3.4.3.32", "hash-ml-dsa-44-with-sha512", "HashML-DSA-44 signature with SHA-512", "FIPS 204"),
+    entry("2.16.840.1.101.3.4.3.33", "hash-ml-dsa-65-with-sha512", "HashML-DSA-65 signature with SHA-512", "FIPS 204"),
+    entry("2.16.840.1.101.3.4.3.34", "hash-ml-dsa-87-with-sha512", "HashML-DSA-87 signature with SHA-512", "FIPS 204"),
+    entry("2.16.840.1.101.3.4.4.1", "ml-kem-512", "ML-KEM-512 key encapsulation", "FIPS 203"),
+    entry("2.16.840.1.101.3.4.4.2", "ml-kem-768", "ML-KEM-768 key encapsulation", "FIPS 203"),
+    entry("2.16.840.1.101.3.4.4.3", "ml-kem-1024", "ML-KEM-1024 key encapsulation", "FIPS 203"),
+
+    // SEC 2 curves
+    entry("1.3.132.0.1", "sect163k1", "SEC 2 binary curve sect163k1 (NIST K-163)", "SEC 2"),
+    entry("1.3.132.0.2", "sect163r1", "SEC 2 binary curve sect163r1", "SEC 2"),
+    entry("1.3.132.0.8", "secp160r1", "SEC 2 prime curve secp160r1", "SEC 2"),
+    entry("1.3.132.0.9", "secp160k1", "SEC 2 prime curve secp160k1", "SEC 2"),
+    entry("1.3.132.0.10", "secp256k1", "SEC 2 prime curve secp256k1", "SEC 2"),
+    entry("1.3.132.0.15", "sect163r2", "SEC 2 binary curve sect163r2 (NIST B-163)", "SEC 2"),
+    entry("1.3.132.0.16", "sect283k1", "SEC 2 binary curve sect283k1 (NIST K-283)", "SEC 2"),
+    entry("1.3.132.0.17", "sect283r1", "SEC 2 binary curve sect283r1 (NIST B-283)", "SEC 2"),
+    entry("1.3.132.0.26", "sect233k1", "SEC 2 binary curve sect233k1 (NIST K-233)", "SEC 2"),
+    entry("1.3.132.0.27", "sect233r1", "SEC 2 binary curve sect233r1 (NIST B-233)", "SEC 2"),
+    entry("1.3.132.0.30", "secp160r2", "SEC 2 prime curve secp160r2", "SEC 2"),
+    entry("1.3.132.0.31", "secp192k1", "SEC 2 prime curve secp192k1", "SEC 2"),
+    entry("1.3.132.0.32", "secp224k1", "SEC 2 prime curve secp224k1", "SEC 2"),
+    entry("1.3.132.0.33", "secp224r1", "NIST P-224 elliptic curve", "RFC 5480"),
+    entry("1.3.132.0.34", "secp384r1", "NIST P-384 elliptic curve", "RFC 5480"),
+    entry("1.3.132.0.35", "secp521r1", "NIST P-521 elliptic curve", "RFC 5480"),
+    entry("1.3.132.0.36", "sect409k1", "SEC 2 binary curve sect409k1 (NIST K-409)", "SEC 2"),
+    entry("1.3.132.0.37", "sect409r1", "SEC 2 binary curve sect409r1 (NIST B-409)", "SEC 2"),
+    entry("1.3.132.0.38", "sect571k1", "SEC 2 binary curve sect571k1 (NIST K-571)", "SEC 2"),
+    entry("1.3.132.0.39", "sect571r1", "SEC 2 binary curve sect571r1 (NIST B-571)", "SEC 2"),
+    entry("1.3.132.1.12", "ecDH", "Elliptic curve Diffie-Hellman", "RFC 5480"),
+    entry("1.3.132.1.13", "ecMQV", "Elliptic curve MQV", "RFC 5480"),
+
+    // Brainpool curves
+    entry("1.3.36.3.3.2.8.1.1.1", "brainpoolP160r1", "Brainpool P160r1 elliptic curve", "RFC 5639"),
+    entry("1.3.36.3.3.2.8.1.1.3", "brainpoolP192r1", "Brainpool P192r1 elliptic curve", "RFC 5639"),
+    entry("1.3.36.3.3.2.8.1.1.5", "brainpoolP224r1", "Brainpool P224r1 elliptic curve", "RFC 5639"),
+    entry("1.3.36.3.3.2.8.1.1.7", "brainpoolP256r1", "Brainpool P256r1 elliptic curve", "RFC 5639"),
+    entry("1.3.36.3.3.2.8.1.1.9", "brainpoolP320r1", "Brainpool P320r1 elliptic curve", "RFC 5639"),
+    entry("1.3.36.3.3.2.8.1.1.11", "brainpoolP384r1", "Brainpool P384r1 elliptic curve", "RFC 5639"),
+    entry("1.3.36.3.3.2.8.1.1.13", "brainpoolP512r1", "Brainpool P512r1 elliptic curve", "RFC 5639"),
+
+    // EdDSA and ECDH over Montgomery curves
+    entry("1.3.101.110", "X25519", "X25519 key agreement", "RFC 8410"),
+    entry("1.3.101.111", "X448", "X448 key agreement", "RFC 8410"),
+    entry("1.3.101.112", "Ed25519", "Ed25519 signature", "RFC 8410"),
+    entry("1.3.101.113", "Ed448", "Ed448 signature", "RFC 8410"),
+
+    // Kerberos PKINIT
+    entry("1.3.6.1.5.2.2", "pkinit-san", "Kerberos principal name", "RFC 4556"),
+    entry("1.3.6.1.5.2.3.4", "pkinit-KPClientAuth", "PKINIT client authentication", "RFC 4556"),
+    entry("1.3.6.1.5.2.3.5", "pkinit-KPKdc", "PKINIT KDC", "RFC 4556"),
+
+    // Certificate Transparency
+    entry("1.3.6.1.4.1.11129.2.4.2", "ct-precert-scts", "Embedded signed certificate timestamp list", "RFC 6962"),
+    entry("1.3.6.1.4.1.11129.2.4.3", "ct-precert-poison", "Precertificate poison", "RFC 6962"),
+    entry("1.3.6.1.4.1.11129.2.4.4", "ct-precert-signer", "Precertificate signing certificate", "RFC 6962"),
+    entry("1.3.6.1.4.1.11129.2.4.5", "ct-ocsp-scts", "OCSP signed certificate timestamp list", "RFC 6962"),
+
+    // Microsoft
+    entry("1.3.6.1.4.1.311.2.1.4", "spcIndirectDataContext", "Authenticode indirect data content", "Microsoft Authenticode"),
+    entry("1.3.6.1.4.1.311.2.1.11", "spcStatementType", "Authenticode statement type", "Microsoft Authenticode"),
+    entry("1.3.6.1.4.1.311.2.1.12", "spcSpOpusInfo", "Authenticode program information", "Microsoft Authenticode"),
+    entry("1.3.6.1.4.1.311.2.1.15", "spcPEImageData", "Authenticode PE image data", "Microsoft Authenticode"),
+    entry("1.3.6.1.4.1.311.2.1.21", "individualCodeSigning", "Authenticode individual code signing", "Microsoft Authenticode"),
+    entry("1.3.6.1.4.1.311.2.1.22", "commercialCodeSigning", "Authenticode commercial code signing", "Microsoft Authenticode"),
+    entry("1.3.6.1.4.1.311.3.3.1", "timestampCountersignature", "Authenticode RFC 3161 countersignature", "Microsoft Authenticode"),
+    entry("1.3.6.1.4.1.311.10.3.1", "certTrustListSigning", "Microsoft trust list signing", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.10.3.2", "timeStampSigning", "Microsoft time stamp signing", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.10.3.3", "serverGatedCrypto", "Microsoft server gated cryptography", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.10.3.4", "encryptedFileSystem", "Microsoft encrypting file system", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.10.3.12", "documentSigning", "Microsoft document signing", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.10.3.13", "lifetimeSigning", "Microsoft lifetime signing", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.13.2.1", "enrollmentNameValuePair", "Microsoft enrollment name/value pair", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.13.2.2", "enrollmentCSP", "Microsoft enrollment cryptographic service provider", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.13.2.3", "osVersion", "Microsoft OS version", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.17.1", "pkcs12KeyProviderName", "Microsoft key provider name (PKCS #12 attribute)", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.17.2", "localMachineKeyset", "Microsoft local machine keyset (PKCS #12 attribute)", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.20.2", "certificateTemplateName", "Microsoft certificate template name", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.20.2.2", "smartcardLogon", "Microsoft smart card logon", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.20.2.3", "userPrincipalName", "Microsoft user principal name", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.21.1", "caVersion", "Microsoft CA version", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.21.2", "certSrvPreviousCertHash", "Microsoft previous CA certificate hash", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.21.7", "certificateTemplate", "Microsoft certificate template information", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.21.10", "applicationCertPolicies", "Microsoft application policies", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.21.20", "requestClientInfo", "Microsoft request client information", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.25.2", "ntdsCaSecurityExt", "Microsoft NTDS CA security extension", "Microsoft PKI"),
+    entry("1.3.6.1.4.1.311.61.1.1", "kernelModeCodeSigning", "Microsoft kernel mode code signing", "Microsoft PKI"),
+
+    // Netscape
+    entry("2.16.840.1.113730.1.1", "nsCertType", "Netscape certificate type", "Netscape"),
+    entry("2.16.840.1.113730.1.2", "nsBaseUrl", "Netscape base URL", "Netscape"),
+    entry("2.16.840.1.113730.1.3", "nsRevocationUrl", "Netscape revocation URL", "Netscape"),
+    entry("2.16.840.1.113730.1.4", "nsCaRevocationUrl", "Netscape CA revocation URL", "Netscape"),
+    entry("2.16.840.1.113730.1.7", "nsRenewalUrl", "Netscape certificate renewal URL", "Netscape"),
+    entry("2.16.840.1.113730.1.8", "nsCaPolicyUrl", "Netscape CA policy URL", "Netscape"),
+    entry("2.16.840.1.113730.1.12", "nsSslServerName", "Netscape SSL server name", "Netscape"),
+    entry("2.16.840.1.113730.1.13", "nsComment", "Netscape comment", "Netscape"),
+    entry("2.16.840.1.113730.4.1", "nsSGC", "Netscape server gated cryptography", "Netscape"),
+
+    // CA/Browser Forum certificate policies
+    entry("2.23.140.1.1", "ev-guidelines", "Extended validation TLS certificate", "CA/B Forum EV Guidelines"),
+    entry("2.23.140.1.2.1", "domain-validated", "Domain validated TLS certificate", "CA/B Forum Baseline Requirements"),
+    entry("2.23.140.1.2.2", "organization-validated", "Organization validated TLS certificate", "CA/B Forum Baseline Requirements"),
+    entry("2.23.140.1.2.3", "individual-validated", "Individual validated TLS certificate", "CA/B Forum Baseline Requirements"),
+    entry("2.23.140.1.3", "ev-codesigning", "Extended validation code signing certificate", "CA/B Forum Code Signing Requirements"),
+    entry("2.23.140.1.4.1", "codesigning", "Non-EV code signing certificate", "CA/B Forum Code Signing Requirements"),
+    entry("2.23.140.1.4.2", "timestamping", "Code signing time stamp certificate", "CA/B Forum Code Signing Requirements"),
+    entry("2.23.140.1.31", "onion-EV", "Onion service EV certificate", "CA/B Forum EV Guidelines"),
+    entry("2.23.140.1.5.1.1", "mailbox-validated-legacy", "Mailbox validated S/MIME certificate, legacy", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.1.2", "mailbox-validated-multipurpose", "Mailbox validated S/MIME certificate, multipurpose", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.1.3", "mailbox-validated-strict", "Mailbox validated S/MIME certificate, strict", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.2.1", "organization-validated-legacy", "Organization validated S/MIME certificate, legacy", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.2.2", "organization-validated-multipurpose", "Organization validated S/MIME certificate, multipurpose", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.2.3", "organization-validated-strict", "Organization validated S/MIME certificate, strict", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.3.1", "sponsor-validated-legacy", "Sponsor validated S/MIME certificate, legacy", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.3.2", "sponsor-validated-multipurpose", "Sponsor validated S/MIME certificate, multipurpose", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.3.3", "sponsor-validated-strict", "Sponsor validated S/MIME certificate, strict", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.4.1", "individual-validated-legacy", "Individual validated S/MIME certificate, legacy", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.4.2", "individual-validated-multipurpose", "Individual validated S/MIME certificate, multipurpose", "CA/B Forum S/MIME Requirements"),
+    entry("2.23.140.1.5.4.3", "individual-validated-strict", "Individual validated S/MIME certificate, strict", "CA/B Forum S/MIME Requirements"),
+];
+
+/// Name of the file, in the user config directory, using the format of Peter
+/// Gutmann's `dumpasn1.cfg`.
+pub const DUMPASN1_FILE_NAME: &str = "dumpasn1.cfg";
+
+/// Name of the file, in the user config directory, containing one OID per
+/// line as `oid<TAB>name[<TAB>description[<TAB>reference]]`.
+pub const TSV_FILE_NAME: &str = "oids.tsv";
 
 lazy_static! {
-    static ref HASHMAP: HashMap<&'static str, &'static str> = {
-        let mut m = HashMap::new();
-        m.insert("1.2.840.113549.1.1.11", "sha256WithRSAEncryption");
-        m.insert("1.2.840.113549.1.12.1.3", "pbeWithSHAAnd3-KeyTripleDES-CBC");
-        m.insert("1.2.840.113549.1.12.10.1.2", "pkcs-8ShroudedKeyBag");
-        m.insert("1.2.840.113549.1.12.10.1.3", "pkcs-12-certBag (PKCS #12 BagIds)");
-        m.insert("1.2.840.113549.1.7.1", "data (PKCS #7)");
-        m.insert("1.2.840.113549.1.7.6", "encryptedData");
-        m.insert("1.2.840.113549.1.9.20", "friendlyName");
-        m.insert("1.2.840.113549.1.9.21", "localKeyID");
-        m.insert("1.2.840.113549.1.9.22.1", "x509Certificate (for PKCS #12)");
-        m.insert("1.3.14.3.2.26", "sha1");
-        m.insert("2.5.4.10", "organizationName");
-        m.insert("2.5.4.6", "countryName");
-        m.insert("2.5.4.8", "stateOrProvinceName");
-        return m;
+    static ref HASHMAP: RwLock<HashMap<String, Arc<OidEntry>>> = RwLock::new(builtin_map());
+}
+
+fn builtin_map() -> HashMap<String, Arc<OidEntry>> {
+    let mut m = HashMap::with_capacity(ENTRIES.len());
+    for entry in ENTRIES {
+        m.insert(entry.oid.to_string(), Arc::new(entry.clone()));
+    }
+    return m;
+}
+
+/// Returns everything known about a dotted OID, without copying it.
+pub fn find(oid: &str) -> Option<Arc<OidEntry>> {
+    return HASHMAP.read().unwrap().get(oid).cloned();
+}
+
+/// Returns the short name of a dotted OID, or the OID itself if unknown.
+pub fn name_or_oid(oid: String) -> String {
+    return match find(&oid) {
+        Some(entry) => entry.name.to_string(),
+        None => oid,
+    };
+}
+
+/// Returns every known OID, built-in and user defined, sorted by OID.
+pub fn entries() -> Vec<Arc<OidEntry>> {
+    let mut result: Vec<_> = HASHMAP.read().unwrap().values().cloned().collect();
+    result.sort_by_cached_key(|entry| oid::parse_oid(&entry.oid).unwrap_or_default());
+    return result;
+}
+
+/// Outcome of loading the user OID files.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub loaded: usize,
+    pub files: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Reset the table to the built-in OIDs and load the user files from the
+/// config directory on top of them, so user entries override the built-ins.
+pub fn reload() -> LoadReport {
+    let mut report = LoadReport::default();
+    let mut map = builtin_map();
+
+    if let Some(dir) = config::config_dir() {
+        let loaders: [(&str, Parser); 2] = [
+            (DUMPASN1_FILE_NAME, parse_dumpasn1),
+            (TSV_FILE_NAME, parse_tsv),
+        ];
+
+        for (file_name, parse) in loaders {
+            let path = dir.join(file_name);
+            if let Some(content) = read_optional_file(&path, &mut report) {
+                let source = path.display().to_string();
+                for result in parse(&content, file_name) {
+                    match result {
+                        Ok(entry) => {
+                            map.insert(entry.oid.to_string(), Arc::new(entry));
+                            report.loaded += 1;
+                        }
+                        Err((line, err)) => report.errors.push(format!("{}:{}: {}", source, line, err)),
+                    }
+                }
+                report.files.push(source);
+            }
+        }
+    }
+
+    *HASHMAP.write().unwrap() = map;
+    return report;
+}
+
+fn read_optional_file(path: &Path, report: &mut LoadReport) -> Option<String> {
+    return match std::fs::read(path) {
+        Ok(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => {
+            report.errors.push(format!("{}: {}", path.display(), err));
+            None
+        }
     };
 }
 
-pub fn find(oid: &str) -> Option<&'static str> {
-    return HASHMAP.get(oid).copied();
+/// Either a parsed entry, or the line number and the reason it was rejected.
+pub type ParseResult = std::result::Result<OidEntry, (usize, &'static str)>;
+
+type Parser = fn(&str, &str) -> Vec<ParseResult>;
+
+/// Parse the content of a `dumpasn1.cfg` file.
+///
+/// Every entry starts with an `OID = 06 03 55 04 03` line containing the
+/// DER-encoded OID, followed by optional `Comment`, `Description` and
+/// `Warning` lines. The description is used as the name, minus the trailing
+/// arcs dumpasn1 appends to it.
+pub fn parse_dumpasn1(content: &str, reference: &str) -> Vec<ParseResult> {
+    fn finish(current: Option<(usize, OidEntry)>, results: &mut Vec<ParseResult>) {
+        if let Some((line, entry)) = current {
+            if entry.name.is_empty() {
+                results.push(Err((line, "OID without a description")));
+            } else {
+                results.push(Ok(entry));
+            }
+        }
+    }
+
+    let mut results = Vec::new();
+    let mut current: Option<(usize, OidEntry)> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (line, ""),
+        };
+
+        match key {
+            "OID" => {
+                finish(current.take(), &mut results);
+                match parse_dumpasn1_oid(value) {
+                    Ok(oid) => {
+                        let mut entry = entry("", "", "", "");
+                        entry.oid = Cow::Owned(oid);
+                        entry.reference = Cow::Owned(reference.to_owned());
+                        current = Some((idx + 1, entry));
+                    }
+                    Err(err) => results.push(Err((idx + 1, err))),
+                }
+            }
+            "Description" => {
+                if let Some((_, entry)) = current.as_mut() {
+                    let name = match value.rfind(" (") {
+                        Some(pos) if value.ends_with(')') => &value[..pos],
+                        _ => value,
+                    };
+                    entry.name = Cow::Owned(name.to_owned());
+                    if entry.description.is_empty() {
+                        entry.description = Cow::Owned(name.to_owned());
+                    }
+                }
+            }
+            "Comment" => {
+                if let Some((_, entry)) = current.as_mut() {
+                    entry.description = Cow::Owned(value.to_owned());
+                }
+            }
+            // Warnings only make sense for dumpasn1 itself.
+            "Warning" => (),
+            _ => {
+                if current.is_some() {
+                    results.push(Err((idx + 1, "Unknown key")));
+                }
+            }
+        }
+    }
+
+    finish(current, &mut results);
+    return results;
+}
+
+fn parse_dumpasn1_oid(value: &str) -> std::result::Result<String, &'static str> {
+    let hex: String = value.split_whitespace().collect::<Vec<_>>().concat().to_lowercase();
+    let bytes = hex::unhexlify(&hex).map_err(|_| "Invalid hexadecimal OID")?;
+    return match bytes.as_slice() {
+        [0x06, len, rest @ ..] if usize::from(*len) == rest.len() => {
+            oid::stringify(rest).map_err(|err| err.0)
+        }
+        _ => Err("Expected a DER-encoded OID"),
+    };
+}
+
+fn strip_comment(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    for (idx, _) in line.match_indices('#') {
+        let after = bytes.get(idx + 1).is_none_or(|c| c.is_ascii_whitespace());
+        let before = idx == 0 || bytes[idx - 1].is_ascii_whitespace();
+        if before && after {
+            return &line[..idx];
+        }
+    }
+    return line;
+}
+
+/// Parse the content of a tab-separated OID file.
+///
+/// Every line is `oid<TAB>name[<TAB>description[<TAB>reference]]`, once
+/// its comment is removed. A comment starts with a `#` followed by a space or
+/// the end of the line and preceded by a space, a tab or nothing, so that
+/// descriptions like "PKCS #1" are kept.
+pub fn parse_tsv(content: &str, reference: &str) -> Vec<ParseResult> {
+    let mut results = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let line = strip_comment(line);
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split('\t').map(str::trim);
+        let oid = fields.next().unwrap_or_default();
+        let name = fields.next().unwrap_or_default();
+        if name.is_empty() {
+            results.push(Err((idx + 1, "Missing name")));
+            continue;
+        }
+
+        if oid::parse_oid(oid).is_err() {
+            results.push(Err((idx + 1, "Invalid OID")));
+            continue;
+        }
+
+        let description = fields.next().filter(|s| !s.is_empty()).unwrap_or(name);
+        let source = fields.next().filter(|s| !s.is_empty()).unwrap_or(reference);
+        results.push(Ok(OidEntry {
+            oid: Cow::Owned(oid.to_owned()),
+            name: Cow::Owned(name.to_owned()),
+            description: Cow::Owned(description.to_owned()),
+            reference: Cow::Owned(source.to_owned()),
+        }));
+    }
+    return results;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+
+    #[test]
+    fn entries_are_unique() {
+        assert_eq!(builtin_map().len(), ENTRIES.len());
+    }
+
+    #[test]
+    fn entries_are_valid_oids() {
+        for entry in ENTRIES {
+            let encoded = oid::parse_oid(&entry.oid).unwrap();
+            assert_eq!(oid::stringify(&encoded).unwrap(), entry.oid);
+            assert!(!entry.name.is_empty() && !entry.description.is_empty() && !entry.reference.is_empty());
+        }
+    }
+
+    #[test]
+    fn find_known_oids() {
+        assert_eq!(find("2.5.4.3").unwrap().name, "commonName");
+        assert_eq!(find("2.5.29.19").unwrap().reference, "RFC 5280");
+        assert_eq!(name_or_oid("1.2.3.4.5".to_owned()), "1.2.3.4.5");
+        assert_eq!(find("1.2.3.4.5.6.7"), None);
+    }
+
+    #[test]
+    fn parse_dumpasn1_entries() {
+        let content = "# Comment\n\
+            OID = 06 03 55 04 03\n\
+            Comment = X.520 id-at-commonName (2 5 4 3)\n\
+            Description = commonName (2 5 4 3)\n\
+            \n\
+            OID = 06 0A 2B 06 01 04 01 82 37 3C 02 01\n\
+            Description = ourInternalThing\n\
+            Warning\n\
+            \n\
+            OID = 06 02 55\n\
+            Description = broken\n";
+        let results = parse_dumpasn1(content, "dumpasn1.cfg");
+        assert_eq!(results.len(), 3);
+
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.oid, "2.5.4.3");
+        assert_eq!(first.name, "commonName");
+        assert_eq!(first.description, "X.520 id-at-commonName (2 5 4 3)");
+
+        let second = results[1].as_ref().unwrap();
+        assert_eq!(second.oid, "1.3.6.1.4.1.311.60.2.1");
+        assert_eq!(second.description, "ourInternalThing");
+
+        assert_eq!(results[2], Err((10, "Expected a DER-encoded OID")));
+    }
+
+    #[test]
+    fn parse_tsv_entries() {
+        let content = "# oid\tname\n\
+            1.3.6.1.4.1.55555.1\tacmeRoot\n\
+            1.3.6.1.4.1.55555.1.2\tacmeDevice\tAcme device certificate\tAcme CP 1.2\n\
+            1.3.6.1.4.1.55555.1.3\tacmePkcs\tAcme PKCS #1 key # rotated yearly\n\
+            1.3.6.1.4.1.55555.1.4\tacmeTest\t# no description\n\
+            #\n\
+            1.3.x\tbroken\n\
+            1.3.6\n";
+        let results = parse_tsv(content, "oids.tsv");
+        assert_eq!(results.len(), 6);
+        assert_eq!(results[0].as_ref().unwrap().description, "acmeRoot");
+        assert_eq!(results[0].as_ref().unwrap().reference, "oids.tsv");
+        assert_eq!(results[1].as_ref().unwrap().reference, "Acme CP 1.2");
+        assert_eq!(results[2].as_ref().unwrap().description, "Acme PKCS #1 key");
+        assert_eq!(results[3].as_ref().unwrap().description, "acmeTest");
+        assert_eq!(results[4], Err((7, "Invalid OID")));
+        assert_eq!(results[5], Err((8, "Missing name")));
+    }
 }
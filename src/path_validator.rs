@@ -0,0 +1,176 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use imgui::*;
+use crate::app::Selection;
+use crate::chain::{self, Candidate};
+use crate::document::{Document, Kind};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Tool window building the chain of a certificate from the loaded ones and
+/// validating it at a chosen time.
+pub struct PathValidator {
+    pub opened: bool,
+    leaf: Option<Selection>,
+    time: String,
+    validation: Option<Validation>,
+}
+
+/// The chain of a certificate, kept until the inputs or the documents change.
+struct Validation {
+    leaf: Selection,
+    time: DateTime<Utc>,
+    generation: usize,
+    /// Every certificate of the chain, from the leaf, with its subject and
+    /// its failures.
+    chain: Vec<(Selection, String, Vec<String>)>,
+}
+
+fn now() -> String {
+    return Utc::now().format(TIME_FORMAT).to_string();
+}
+
+fn validate(documents: &[Document], leaf: &Selection, time: DateTime<Utc>, generation: usize) -> Option<Validation> {
+    let mut certificates = Vec::new();
+    let mut candidates = Vec::new();
+    for (idx, document) in documents.iter().enumerate() {
+        for structure in document.structures.iter() {
+            if let (Kind::Certificate(certificate), Some(node)) = (&structure.kind, document.node(&structure.path)) {
+                let selection = Selection { document: idx, path: structure.path.clone() };
+                certificates.push((selection, certificate.subject.clone()));
+                candidates.push(Candidate { node, bytes: &document.bytes });
+            }
+        }
+    }
+
+    let leaf_idx = certificates.iter().position(|(selection, _)| selection == leaf)?;
+    let path = chain::build(&candidates, leaf_idx);
+    let failures = chain::validate(&candidates, &path, time);
+    let chain = path
+        .iter()
+        .zip(failures)
+        .map(|(idx, failures)| (certificates[*idx].0.clone(), certificates[*idx].1.clone(), failures))
+        .collect();
+    return Some(Validation { leaf: leaf.clone(), time, generation, chain });
+}
+
+impl PathValidator {
+    pub fn new() -> Self {
+        return Self { opened: false, leaf: None, time: now(), validation: None };
+    }
+
+    /// Open the window with the chain of the given certificate.
+    pub fn show(&mut self, leaf: Selection) {
+        self.opened = true;
+        self.leaf = Some(leaf);
+    }
+
+    /// Follow the leaf when the document at `idx` is closed. The chain is
+    /// built again on the next frame.
+    pub fn close_document(&mut self, idx: usize) {
+        self.leaf = self.leaf.take().and_then(|leaf| leaf.after_close(idx));
+        self.validation = None;
+    }
+
+    /// The failures of the certificate, if it's part of the displayed chain.
+    pub fn failures(&self, selection: &Selection) -> Option<&[String]> {
+        if !self.opened {
+            return None;
+        }
+        let chain = &self.validation.as_ref()?.chain;
+        let (_, _, failures) = chain.iter().find(|(certificate, _, _)| certificate == selection)?;
+        return Some(failures);
+    }
+
+    /// Draw the window and returns the node to navigate to, if any.
+    ///
+    /// `generation` must change whenever `documents` changes.
+    pub fn draw(&mut self, ui: &Ui, documents: &[Document], generation: usize) -> Option<Selection> {
+        if !self.opened {
+            return None;
+        }
+
+        let mut opened = self.opened;
+        let mut navigate = None;
+        ui.window("Certificate path")
+            .size([640.0, 360.0], Condition::FirstUseEver)
+            .opened(&mut opened)
+            .build(|| {
+                ui.input_text("Time (UTC)", &mut self.time).hint(TIME_FORMAT).build();
+                ui.same_line();
+                if ui.small_button("Now") {
+                    self.time = now();
+                }
+                let time = match NaiveDateTime::parse_from_str(self.time.trim(), TIME_FORMAT) {
+                    Ok(time) => time.and_utc(),
+                    Err(_) => {
+                        ui.text_colored([1.0, 0.0, 0.0, 1.0], "Invalid time, expected YYYY-MM-DD HH:MM:SS");
+                        return;
+                    }
+                };
+
+                let leaf = match self.leaf.as_ref() {
+                    Some(leaf) => leaf,
+                    None => {
+                        ui.text("Right-click a certificate and choose \"Build chain\".");
+                        return;
+                    }
+                };
+                let outdated = match self.validation.as_ref() {
+                    Some(validation) => {
+                        validation.leaf != *leaf || validation.time != time || validation.generation != generation
+                    }
+                    None => true,
+                };
+                if outdated {
+                    self.validation = validate(documents, leaf, time, generation);
+                }
+                let validation = match self.validation.as_ref() {
+                    Some(validation) => validation,
+                    None => {
+                        ui.text("The certificate isn't loaded anymore.");
+                        return;
+                    }
+                };
+
+                let count: usize = validation.chain.iter().map(|(_, _, failures)| failures.len()).sum();
+                if count == 0 {
+                    ui.text_colored([0.0, 0.8, 0.0, 1.0], "The path is valid");
+                } else {
+                    ui.text_colored([1.0, 0.0, 0.0, 1.0], format!("The path is invalid: {} failure(s)", count));
+                }
+                ui.separator();
+
+                for (depth, (selection, subject, failures)) in validation.chain.iter().enumerate() {
+                    let _id = ui.push_id_usize(depth);
+                    if ui.small_button("Show") {
+                        navigate = Some(selection.clone());
+                    }
+                    ui.same_line();
+                    let name = &documents[selection.document].name;
+                    ui.text(format!("{}{} ({})", "  ".repeat(depth), subject, name));
+                    for failure in failures {
+                        ui.text_colored([1.0, 0.0, 0.0, 1.0], format!("{}    {}", "  ".repeat(depth), failure));
+                    }
+                }
+            });
+
+        self.opened = opened;
+        return navigate;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_leaf_on_close() {
+        let mut validator = PathValidator::new();
+        validator.show(Selection { document: 2, path: vec![0] });
+        validator.close_document(3);
+        validator.close_document(0);
+        assert_eq!(validator.leaf, Some(Selection { document: 1, path: vec![0] }));
+        validator.close_document(1);
+        assert_eq!(validator.leaf, None);
+    }
+}
@@ -0,0 +1,227 @@
+use cbc::cipher::{block_padding::Pkcs7, BlockCipher, BlockDecryptMut, InnerIvInit, KeyInit, KeyIvInit};
+use crate::der::Tag;
+use crate::error::{Error, Result};
+use crate::hash;
+use crate::node::Node;
+use crate::{spki, value};
+
+/// Refuse iteration counts that would freeze the interface for minutes.
+pub const MAX_ITERATIONS: u64 = 10_000_000;
+
+/// A password in the encodings used by the password-based schemes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Password {
+    /// Used by PBES2.
+    pub utf8: Vec<u8>,
+    /// Used by the PKCS#12 key derivation: big-endian UTF-16 with a trailing
+    /// zero character.
+    pub bmp: Vec<u8>,
+}
+
+impl Password {
+    pub fn new(password: &str) -> Self {
+        let mut bmp: Vec<u8> = password.encode_utf16().flat_map(|unit| unit.to_be_bytes()).collect();
+        bmp.extend_from_slice(&[0, 0]);
+        return Self { utf8: password.as_bytes().to_vec(), bmp };
+    }
+
+    /// The absent password, which some implementations use instead of the
+    /// empty one.
+    pub fn absent() -> Self {
+        return Self { utf8: Vec::new(), bmp: Vec::new() };
+    }
+}
+
+/// Derive key material as described in RFC 7292 appendix B.2. `id` is 1 for
+/// keys, 2 for IVs and 3 for MAC keys.
+pub fn pkcs12_kdf(hash: hash::Algorithm, password: &[u8], salt: &[u8], id: u8, iterations: u64, len: usize) -> Vec<u8> {
+    let u = hash.output_size();
+    let v = hash.block_size();
+    let repeat = |input: &[u8]| -> Vec<u8> {
+        let len = v * input.len().div_ceil(v);
+        return input.iter().copied().cycle().take(len).collect();
+    };
+
+    let diversifier = vec![id; v];
+    let mut input = repeat(salt);
+    input.extend(repeat(password));
+
+    let mut output = Vec::with_capacity(len + u);
+    while output.len() < len {
+        let mut a = hash.digest(&[diversifier.as_slice(), input.as_slice()].concat());
+        for _ in 1..iterations {
+            a = hash.digest(&a);
+        }
+        output.extend_from_slice(&a);
+
+        // Add B + 1 to every block of the input, modulo 2^(v * 8).
+        let b: Vec<u8> = a.iter().copied().cycle().take(v).collect();
+        for block in input.chunks_mut(v) {
+            let mut carry = 1u16;
+            for (byte, b) in block.iter_mut().zip(b.iter()).rev() {
+                let sum = u16::from(*byte) + u16::from(*b) + carry;
+                *byte = sum as u8;
+                carry = sum >> 8;
+            }
+        }
+    }
+    output.truncate(len);
+    return output;
+}
+
+fn cbc_decrypt<C: BlockCipher + BlockDecryptMut + KeyInit>(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let decryptor = cbc::Decryptor::<C>::new_from_slices(key, iv).map_err(|_| Error("Invalid key or IV length"))?;
+    return decrypt_padded(decryptor, data);
+}
+
+fn decrypt_padded<C: BlockCipher + BlockDecryptMut>(decryptor: cbc::Decryptor<C>, data: &[u8]) -> Result<Vec<u8>> {
+    return decryptor.decrypt_padded_vec_mut::<Pkcs7>(data).map_err(|_| Error("Wrong password or corrupted data"));
+}
+
+fn rc2_decrypt(key: &[u8], effective_bits: usize, iv: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let cipher = rc2::Rc2::new_with_eff_key_len(key, effective_bits);
+    let decryptor = cbc::Decryptor::inner_iv_slice_init(cipher, iv).map_err(|_| Error("Invalid IV length"))?;
+    return decrypt_padded(decryptor, data);
+}
+
+/// Read an iteration count, refusing the ones above `max`, usually
+/// `MAX_ITERATIONS`.
+pub fn iterations(node: &Node, bytes: &[u8], max: u64) -> Result<u64> {
+    let iterations = value::unsigned(node.content(bytes))?;
+    if iterations == 0 || max < iterations {
+        return Err(Error("Unsupported iteration count"));
+    }
+    return Ok(iterations);
+}
+
+/// Decrypt with one of the PBE schemes of RFC 7292 appendix C.
+fn pkcs12_pbe(oid: &str, parameters: &Node, bytes: &[u8], password: &Password, data: &[u8], max: u64) -> Result<Vec<u8>> {
+    let salt = parameters.child(0, Tag::OCTET_STRING)?.content(bytes);
+    let iterations = iterations(parameters.child(1, Tag::INTEGER)?, bytes, max)?;
+    let derive = |id, len| pkcs12_kdf(hash::Algorithm::Sha1, &password.bmp, salt, id, iterations, len);
+
+    return match oid {
+        "1.2.840.113549.1.12.1.3" => cbc_decrypt::<des::TdesEde3>(&derive(1, 24), &derive(2, 8), data),
+        "1.2.840.113549.1.12.1.4" => cbc_decrypt::<des::TdesEde2>(&derive(1, 16), &derive(2, 8), data),
+        "1.2.840.113549.1.12.1.5" => rc2_decrypt(&derive(1, 16), 128, &derive(2, 8), data),
+        "1.2.840.113549.1.12.1.6" => rc2_decrypt(&derive(1, 5), 40, &derive(2, 8), data),
+        _ => Err(Error("Unsupported encryption algorithm")),
+    };
+}
+
+/// Decrypt with one of the PBES1 schemes of RFC 8018, using PBKDF1.
+fn pbes1(oid: &str, parameters: &Node, bytes: &[u8], password: &Password, data: &[u8], max: u64) -> Result<Vec<u8>> {
+    let salt = parameters.child(0, Tag::OCTET_STRING)?.content(bytes);
+    let iterations = iterations(parameters.child(1, Tag::INTEGER)?, bytes, max)?;
+    let hash = match oid {
+        "1.2.840.113549.1.5.3" | "1.2.840.113549.1.5.6" => hash::Algorithm::Md5,
+        _ => hash::Algorithm::Sha1,
+    };
+
+    let mut derived = hash.digest(&[password.utf8.as_slice(), salt].concat());
+    for _ in 1..iterations {
+        derived = hash.digest(&derived);
+    }
+    let (key, iv) = (&derived[..8], &derived[8..16]);
+
+    return match oid {
+        "1.2.840.113549.1.5.3" | "1.2.840.113549.1.5.10" => cbc_decrypt::<des::Des>(key, iv, data),
+        _ => rc2_decrypt(key, 64, iv, data),
+    };
+}
+
+/// Decrypt with PBES2 from RFC 8018, using PBKDF2.
+fn pbes2(parameters: &Node, bytes: &[u8], password: &Password, data: &[u8], max: u64) -> Result<Vec<u8>> {
+    let (kdf, kdf_parameters) = spki::algorithm(parameters.child(0, Tag::SEQUENCE)?, bytes)?;
+    if kdf != "1.2.840.113549.1.5.12" {
+        return Err(Error("Unsupported key derivation function"));
+    }
+    let kdf_parameters = kdf_parameters.ok_or(Error("Missing PBKDF2 parameters"))?;
+    let salt = kdf_parameters.child(0, Tag::OCTET_STRING)?.content(bytes);
+    let iterations = iterations(kdf_parameters.child(1, Tag::INTEGER)?, bytes, max)?;
+
+    let mut key_len = None;
+    let mut prf = hash::Algorithm::Sha1;
+    for node in kdf_parameters.children.iter().skip(2) {
+        match node.tag {
+            Tag::INTEGER => key_len = Some(value::unsigned(node.content(bytes))? as usize),
+            Tag::SEQUENCE => {
+                let (oid, _) = spki::algorithm(node, bytes)?;
+                prf = hash::Algorithm::from_hmac_oid(&oid).ok_or(Error("Unsupported PBKDF2 pseudorandom function"))?;
+            }
+            _ => return Err(Error("Unexpected element in PBKDF2 parameters")),
+        }
+    }
+
+    let (scheme, iv) = spki::algorithm(parameters.child(1, Tag::SEQUENCE)?, bytes)?;
+    let iv = match iv {
+        Some(iv) if iv.tag == Tag::OCTET_STRING => iv.content(bytes),
+        _ => return Err(Error("Unsupported encryption scheme parameters")),
+    };
+    let scheme_key_len = match scheme.as_str() {
+        "1.3.14.3.2.7" => 8,
+        "1.2.840.113549.3.7" => 24,
+        "2.16.840.1.101.3.4.1.2" => 16,
+        "2.16.840.1.101.3.4.1.22" => 24,
+        "2.16.840.1.101.3.4.1.42" => 32,
+        _ => return Err(Error("Unsupported encryption scheme")),
+    };
+    if key_len.is_some_and(|key_len| key_len != scheme_key_len) {
+        return Err(Error("Key length doesn't match the encryption scheme"));
+    }
+
+    let mut key = vec![0; scheme_key_len];
+    prf.pbkdf2(&password.utf8, salt, iterations as u32, &mut key);
+    return match scheme_key_len {
+        8 => cbc_decrypt::<des::Des>(&key, iv, data),
+        24 if scheme == "1.2.840.113549.3.7" => cbc_decrypt::<des::TdesEde3>(&key, iv, data),
+        16 => cbc_decrypt::<aes::Aes128>(&key, iv, data),
+        24 => cbc_decrypt::<aes::Aes192>(&key, iv, data),
+        _ => cbc_decrypt::<aes::Aes256>(&key, iv, data),
+    };
+}
+
+/// Decrypt data protected by the password-based encryption scheme described
+/// by an AlgorithmIdentifier, refusing more than `max` iterations.
+pub fn decrypt(algorithm: &Node, bytes: &[u8], password: &Password, data: &[u8], max: u64) -> Result<Vec<u8>> {
+    let (oid, parameters) = spki::algorithm(algorithm, bytes)?;
+    let parameters = parameters.ok_or(Error("Missing encryption parameters"))?;
+    return match oid.as_str() {
+        "1.2.840.113549.1.5.13" => pbes2(parameters, bytes, password, data, max),
+        "1.2.840.113549.1.5.3" | "1.2.840.113549.1.5.6" | "1.2.840.113549.1.5.10" | "1.2.840.113549.1.5.11" => {
+            pbes1(&oid, parameters, bytes, password, data, max)
+        }
+        _ => pkcs12_pbe(&oid, parameters, bytes, password, data, max),
+    };
+}
+
+/// Whether the OID identifies one of the password-based encryption schemes.
+pub fn is_password_based(oid: &str) -> bool {
+    return oid == "1.2.840.113549.1.5.13"
+        || matches!(oid, "1.2.840.113549.1.5.3" | "1.2.840.113549.1.5.6" | "1.2.840.113549.1.5.10" | "1.2.840.113549.1.5.11")
+        || oid.starts_with("1.2.840.113549.1.12.1.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn encode_passwords() {
+        assert_eq!(Password::new("").bmp, b"\x00\x00");
+        assert_eq!(Password::new("ab").bmp, b"\x00a\x00b\x00\x00");
+        assert_eq!(Password::new("é").utf8, "é".as_bytes());
+    }
+
+    #[test]
+    fn pkcs12_kdf_vectors() {
+        // Test vectors from the PKCS#12 implementation of Bouncy Castle.
+        let password = Password::new("smeg");
+        let salt = hex::unhexlify("0a58cf64530d823f").unwrap();
+        let key = pkcs12_kdf(hash::Algorithm::Sha1, &password.bmp, &salt, 1, 1, 24);
+        assert_eq!(hex::hexlify(&key), "8aaae6297b6cb04642ab5b077851284eb7128f1a2a7fbca3");
+        let iv = pkcs12_kdf(hash::Algorithm::Sha1, &password.bmp, &salt, 2, 1, 8);
+        assert_eq!(hex::hexlify(&iv), "79993dfe048d3b76");
+    }
+}
@@ -0,0 +1,520 @@
+use crate::der::Tag;
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::cms::{annotate_content_info, content_type, octets, DATA, ENCRYPTED_DATA, ENVELOPED_DATA, SIGNED_DATA};
+use crate::pbe::{self, Password};
+use crate::{cms, hash, hex, node, oid, oid_names, spki, value};
+
+const KEY_BAG: &str = "1.2.840.113549.1.12.10.1.1";
+const SHROUDED_KEY_BAG: &str = "1.2.840.113549.1.12.10.1.2";
+const CERT_BAG: &str = "1.2.840.113549.1.12.10.1.3";
+const CRL_BAG: &str = "1.2.840.113549.1.12.10.1.4";
+const SECRET_BAG: &str = "1.2.840.113549.1.12.10.1.5";
+const SAFE_CONTENTS_BAG: &str = "1.2.840.113549.1.12.10.1.6";
+
+const FRIENDLY_NAME: &str = "1.2.840.113549.1.9.20";
+const LOCAL_KEY_ID: &str = "1.2.840.113549.1.9.21";
+
+/// A SafeBag found in a PFX, outside of the contents that are still encrypted.
+#[derive(Clone, Debug)]
+pub struct Bag {
+    pub kind: String,
+    pub friendly_name: Option<String>,
+    pub local_key_id: Option<Vec<u8>>,
+}
+
+/// Parameters of the MAC protecting the integrity of a PFX.
+#[derive(Clone, Debug)]
+pub struct Mac {
+    pub algorithm: String,
+    pub salt: Vec<u8>,
+    pub iterations: u64,
+}
+
+/// Summary of a PKCS#12 PFX, from RFC 7292.
+#[derive(Clone, Debug)]
+pub struct Pfx {
+    pub bags: Vec<Bag>,
+    /// Number of encrypted contents and shrouded keys.
+    pub encrypted: usize,
+    /// Number of encrypted contents and shrouded keys decrypted with a password.
+    pub decrypted: usize,
+    /// Number of contents enveloped for the public key of a recipient, which
+    /// no password decrypts.
+    pub enveloped: usize,
+    pub mac: Option<Mac>,
+    /// Whether the MAC was verified with the password used to decrypt.
+    pub mac_verified: bool,
+}
+
+/// The element encapsulated in an OCTET STRING, if it was decoded.
+fn encapsulated(node: &mut Node) -> Option<&mut Node> {
+    if node.tag != Tag::OCTET_STRING || !node.encapsulated {
+        return None;
+    }
+    return node.children.first_mut();
+}
+
+/// Replace the children of an encrypted element by the elements of its
+/// plaintext, which is appended to `bytes`.
+pub fn attach_plaintext(node: &mut Node, bytes: &mut Vec<u8>, plaintext: Vec<u8>) {
+    let start = bytes.len();
+    bytes.extend(plaintext);
+    let (children, error) = node::parse_from(bytes, start);
+    node.children = children;
+    node.encapsulated = false;
+    node.decrypted = true;
+    if let Some((_, err)) = error {
+        node.note = Some(format!("decrypted content is invalid: {}", err));
+    }
+}
+
+fn mac_data(node: &Node, bytes: &[u8]) -> Result<Mac> {
+    if node.tag != Tag::SEQUENCE || node.children.len() < 2 {
+        return Err(Error("MacData must be a SEQUENCE of two or three elements"));
+    }
+
+    let digest_info = node.child(0, Tag::SEQUENCE)?;
+    let (algorithm, _) = spki::algorithm(digest_info.child(0, Tag::SEQUENCE)?, bytes)?;
+    digest_info.child(1, Tag::OCTET_STRING)?;
+    let salt = node.child(1, Tag::OCTET_STRING)?.content(bytes).to_vec();
+    let iterations = match node.children.get(2) {
+        Some(iterations) if iterations.tag == Tag::INTEGER => value::unsigned(iterations.content(bytes))?,
+        Some(_) => return Err(Error("Unexpected element in MacData")),
+        None => 1,
+    };
+
+    return Ok(Mac { algorithm: oid_names::name_or_oid(algorithm), salt, iterations });
+}
+
+/// Check the MAC of a PFX, computed over `data` with a key derived from the
+/// password.
+fn verify_mac(node: &Node, bytes: &[u8], data: &[u8], password: &Password, max: u64) -> Result<bool> {
+    let digest_info = node.child(0, Tag::SEQUENCE)?;
+    let (algorithm, _) = spki::algorithm(digest_info.child(0, Tag::SEQUENCE)?, bytes)?;
+    let hash = hash::Algorithm::from_oid(&algorithm).ok_or(Error("Unsupported MAC algorithm"))?;
+    let digest = digest_info.child(1, Tag::OCTET_STRING)?.content(bytes);
+    let salt = node.child(1, Tag::OCTET_STRING)?.content(bytes);
+    let iterations = match node.children.get(2) {
+        Some(iterations) => pbe::iterations(iterations, bytes, max)?,
+        None => 1,
+    };
+
+    let key = pbe::pkcs12_kdf(hash, &password.bmp, salt, 3, iterations, hash.output_size());
+    return Ok(hash.hmac(&key, data) == digest);
+}
+
+fn annotate_attributes(node: &mut Node, bytes: &[u8], bag: &mut Bag) {
+    for attribute in node.children.iter_mut() {
+        attribute.set_field("attribute");
+        let id = match attribute.child(0, Tag::OID) {
+            Ok(id) => oid::stringify(id.content(bytes)).unwrap_or_default(),
+            Err(_) => continue,
+        };
+        attribute.note = oid_names::find(&id).map(|entry| entry.name.to_string());
+        attribute.set_child_field(0, "attrId");
+        let values = match attribute.set_child_field(1, "attrValues") {
+            Some(values) => values,
+            None => continue,
+        };
+
+        let value = match values.children.first() {
+            Some(value) => value,
+            None => continue,
+        };
+        match id.as_str() {
+            FRIENDLY_NAME => bag.friendly_name = value::string(value.tag, value.content(bytes)).ok(),
+            LOCAL_KEY_ID => bag.local_key_id = Some(value.content(bytes).to_vec()),
+            _ => {}
+        }
+    }
+}
+
+/// The bagId of a SafeBag.
+fn bag_id(node: &Node, bytes: &[u8]) -> String {
+    return match node.child(0, Tag::OID) {
+        Ok(id) => oid::stringify(id.content(bytes)).unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+}
+
+/// The bagValue of a SafeBag, that is the element inside the explicit tag.
+fn bag_value(node: &mut Node) -> Option<&mut Node> {
+    return node.children.get_mut(1)?.children.first_mut();
+}
+
+impl Pfx {
+    /// Decode the outer layer of a PFX, failing if the node doesn't have its
+    /// shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() < 2 || 3 < node.children.len() {
+            return Err(Error("PFX must be a SEQUENCE of two or three elements"));
+        }
+        if node.child(0, Tag::INTEGER)?.content(bytes) != [3] {
+            return Err(Error("Unsupported PFX version"));
+        }
+        match content_type(&node.children[1], bytes)?.as_str() {
+            DATA | SIGNED_DATA => {}
+            _ => return Err(Error("Unexpected content type in PFX")),
+        }
+        let mac = match node.children.get(2) {
+            Some(mac) => Some(mac_data(mac, bytes)?),
+            None => None,
+        };
+
+        return Ok(Self { bags: Vec::new(), encrypted: 0, decrypted: 0, enveloped: 0, mac, mac_verified: false });
+    }
+
+    /// Whether a password is needed to decrypt some content or to verify the
+    /// MAC.
+    pub fn needs_password(&self) -> bool {
+        return self.decrypted < self.encrypted || (self.mac.is_some() && !self.mac_verified);
+    }
+
+    fn annotate_bag(&mut self, node: &mut Node, bytes: &[u8]) {
+        node.set_field("safeBag");
+        let id = bag_id(node, bytes);
+        node.set_child_field(0, "bagId");
+        node.set_child_field(1, "bagValue");
+
+        let mut bag = Bag {
+            kind: oid_names::name_or_oid(id.clone()),
+            friendly_name: None,
+            local_key_id: None,
+        };
+        if let Some(attributes) = node.set_child_field(2, "bagAttributes") {
+            annotate_attributes(attributes, bytes, &mut bag);
+        }
+
+        let mut note = bag.kind.clone();
+        if let Some(friendly_name) = bag.friendly_name.as_ref() {
+            note.push_str(&format!(", '{}'", friendly_name));
+        }
+        if let Some(local_key_id) = bag.local_key_id.as_ref() {
+            note.push_str(&format!(", localKeyID {}", hex::colon_separated(local_key_id)));
+        }
+        node.note = Some(note);
+        self.bags.push(bag);
+
+        let value = match bag_value(node) {
+            Some(value) => value,
+            None => return,
+        };
+        match id.as_str() {
+            KEY_BAG => value.set_field("privateKeyInfo"),
+            SHROUDED_KEY_BAG => {
+                value.set_field("encryptedPrivateKeyInfo");
+                value.set_child_field(0, "encryptionAlgorithm");
+                if let Some(data) = value.set_child_field(1, "encryptedData") {
+                    self.encrypted += 1;
+                    if data.decrypted {
+                        self.decrypted += 1;
+                        data.set_child_field(0, "privateKeyInfo");
+                    }
+                }
+            }
+            CERT_BAG | CRL_BAG | SECRET_BAG => {
+                let (kind, id, content) = match id.as_str() {
+                    CERT_BAG => ("certBag", "certId", "certValue"),
+                    CRL_BAG => ("crlBag", "crlId", "crlValue"),
+                    _ => ("secretBag", "secretTypeId", "secretValue"),
+                };
+                value.set_field(kind);
+                value.set_child_field(0, id);
+                value.set_child_field(1, content);
+            }
+            SAFE_CONTENTS_BAG => self.annotate_safe_contents(value, bytes),
+            _ => {}
+        }
+    }
+
+    fn annotate_safe_contents(&mut self, node: &mut Node, bytes: &[u8]) {
+        node.set_field("safeContents");
+        for bag in node.children.iter_mut() {
+            self.annotate_bag(bag, bytes);
+        }
+    }
+
+    /// Name the fields of an EncryptedData.
+    fn annotate_encrypted_data(&mut self, node: &mut Node, bytes: &[u8]) {
+        node.set_field("encryptedData");
+        node.set_child_field(0, "version");
+        let info = match node.set_child_field(1, "encryptedContentInfo") {
+            Some(info) => info,
+            None => return,
+        };
+        cms::annotate_encrypted_content_info(info);
+        if let Some(content) = info.children.get_mut(2) {
+            self.encrypted += 1;
+            if content.decrypted {
+                self.decrypted += 1;
+                if let Some(safe_contents) = content.children.first_mut() {
+                    self.annotate_safe_contents(safe_contents, bytes);
+                }
+            }
+        }
+    }
+
+    /// Name every field of a node previously accepted by `parse` and collect
+    /// the bags that aren't encrypted.
+    pub fn annotate(&mut self, node: &mut Node, bytes: &[u8]) {
+        self.bags.clear();
+        self.encrypted = 0;
+        self.decrypted = 0;
+        self.enveloped = 0;
+
+        node.set_child_field(0, "version");
+        if let Some(mac) = node.set_child_field(2, "macData") {
+            if let Some(digest_info) = mac.set_child_field(0, "mac") {
+                digest_info.set_child_field(0, "digestAlgorithm");
+                digest_info.set_child_field(1, "digest");
+            }
+            mac.set_child_field(1, "macSalt");
+            mac.set_child_field(2, "iterations");
+        }
+
+        let auth_safe = match node.set_child_field(1, "authSafe") {
+            Some(auth_safe) => auth_safe,
+            None => return,
+        };
+        let auth_safe = match annotate_content_info(auth_safe, bytes).and_then(encapsulated) {
+            Some(content) => content,
+            None => return,
+        };
+
+        auth_safe.set_field("authenticatedSafe");
+        for info in auth_safe.children.iter_mut() {
+            info.set_field("contentInfo");
+            let content_type = content_type(info, bytes).unwrap_or_default();
+            let content = match annotate_content_info(info, bytes) {
+                Some(content) => content,
+                None => continue,
+            };
+            match content_type.as_str() {
+                DATA => {
+                    if let Some(safe_contents) = encapsulated(content) {
+                        self.annotate_safe_contents(safe_contents, bytes);
+                    }
+                }
+                ENCRYPTED_DATA => self.annotate_encrypted_data(content, bytes),
+                ENVELOPED_DATA => {
+                    content.set_field("envelopedData");
+                    content.note = Some("public-key encrypted, not decryptable".to_owned());
+                    self.enveloped += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Verify the MAC and decrypt every encrypted content and shrouded key with
+    /// the password. The plaintexts are appended to `bytes` and their elements
+    /// become the children of the encrypted elements. Key derivations of more
+    /// than `max` iterations are refused, leaving their contents encrypted.
+    pub fn decrypt(&mut self, node: &mut Node, bytes: &mut Vec<u8>, password: &str, max: u64) -> Result<()> {
+        // An empty password is encoded either as a zero character or as
+        // nothing, depending on the implementation.
+        let mut candidates = vec![Password::new(password)];
+        if password.is_empty() {
+            candidates.push(Password::absent());
+        }
+
+        let password = match node.children.get(2) {
+            Some(mac) => {
+                let auth_safe = node.children[1].children.get(1).and_then(|content| content.children.first());
+                let data = match auth_safe {
+                    Some(auth_safe) if auth_safe.tag == Tag::OCTET_STRING => octets(auth_safe, bytes),
+                    _ => return Err(Error("Only PFX protected by a MAC can be verified")),
+                };
+                let mut verified = None;
+                for candidate in candidates {
+                    if verify_mac(mac, bytes, &data, &candidate, max)? {
+                        verified = Some(candidate);
+                        break;
+                    }
+                }
+                self.mac_verified = verified.is_some();
+                verified.ok_or(Error("Wrong password, the MAC doesn't match"))?
+            }
+            None => candidates.remove(0),
+        };
+
+        let result = decrypt_authenticated_safe(node, bytes, &password, max);
+        self.annotate(node, bytes);
+        return result;
+    }
+}
+
+fn decrypt_authenticated_safe(node: &mut Node, bytes: &mut Vec<u8>, password: &Password, max: u64) -> Result<()> {
+    let auth_safe = node.children[1].children.get_mut(1).and_then(|content| content.children.first_mut());
+    let auth_safe = match auth_safe.and_then(encapsulated) {
+        Some(auth_safe) => auth_safe,
+        None => return Ok(()),
+    };
+
+    for info in auth_safe.children.iter_mut() {
+        let content_type = content_type(info, bytes).unwrap_or_default();
+        let content = match info.children.get_mut(1).and_then(|content| content.children.first_mut()) {
+            Some(content) => content,
+            None => continue,
+        };
+        match content_type.as_str() {
+            DATA => {
+                if let Some(safe_contents) = encapsulated(content) {
+                    decrypt_keys(safe_contents, bytes, password, max)?;
+                }
+            }
+            ENCRYPTED_DATA => {
+                let info = content.children.get_mut(1).ok_or(Error("Missing encryptedContentInfo"))?;
+                if info.children.len() < 3 {
+                    continue;
+                }
+                if !info.children[2].decrypted {
+                    let data = octets(&info.children[2], bytes);
+                    let plaintext = pbe::decrypt(&info.children[1], bytes, password, &data, max)?;
+                    attach_plaintext(&mut info.children[2], bytes, plaintext);
+                }
+                if let Some(safe_contents) = info.children[2].children.first_mut() {
+                    decrypt_keys(safe_contents, bytes, password, max)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    return Ok(());
+}
+
+/// Decrypt the shrouded key bags of a SafeContents.
+fn decrypt_keys(node: &mut Node, bytes: &mut Vec<u8>, password: &Password, max: u64) -> Result<()> {
+    for bag in node.children.iter_mut() {
+        let id = bag_id(bag, bytes);
+        let value = match bag_value(bag) {
+            Some(value) => value,
+            None => continue,
+        };
+        match id.as_str() {
+            SHROUDED_KEY_BAG if value.children.len() == 2 && !value.children[1].decrypted => {
+                let plaintext = pbe::decrypt(&value.children[0], bytes, password, value.children[1].content(bytes), max)?;
+                attach_plaintext(&mut value.children[1], bytes, plaintext);
+            }
+            SAFE_CONTENTS_BAG => decrypt_keys(value, bytes, password, max)?,
+            _ => {}
+        }
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_unencrypted_pfx() {
+        let bytes = include_bytes!("../tests/no-password.pfx");
+        let (mut nodes, _) = node::parse(bytes);
+        let mut pfx = Pfx::parse(&nodes[0], bytes).unwrap();
+        pfx.annotate(&mut nodes[0], bytes);
+
+        let kinds: Vec<_> = pfx.bags.iter().map(|bag| bag.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["certBag", "certBag", "certBag", "keyBag"]);
+        assert_eq!(pfx.encrypted, 0);
+        let mac = pfx.mac.unwrap();
+        assert_eq!(mac.algorithm, "sha1");
+        assert_eq!(mac.iterations, 2048);
+        assert_eq!(hex::hexlify(&mac.salt), "67b700dddd91b409");
+    }
+
+    #[test]
+    fn parse_encrypted_pfx() {
+        let bytes = include_bytes!("../tests/leaf.p12");
+        let (mut nodes, _) = node::parse(bytes);
+        let mut pfx = Pfx::parse(&nodes[0], bytes).unwrap();
+        pfx.annotate(&mut nodes[0], bytes);
+
+        assert_eq!(pfx.encrypted, 2);
+        assert_eq!(pfx.bags.len(), 1);
+        assert_eq!(pfx.bags[0].kind, "pkcs8ShroudedKeyBag");
+        assert_eq!(pfx.bags[0].friendly_name.as_deref(), Some("Test leaf"));
+        assert!(pfx.bags[0].local_key_id.is_some());
+    }
+
+    #[test]
+    fn decrypt_pfx() {
+        let mut bytes = include_bytes!("../tests/leaf.p12").to_vec();
+        let (mut nodes, _) = node::parse(&bytes);
+        let mut pfx = Pfx::parse(&nodes[0], &bytes).unwrap();
+        pfx.annotate(&mut nodes[0], &bytes);
+        assert!(pfx.needs_password());
+
+        assert!(pfx.decrypt(&mut nodes[0], &mut bytes, "wrong", pbe::MAX_ITERATIONS).is_err());
+        assert!(!pfx.mac_verified);
+        pfx.decrypt(&mut nodes[0], &mut bytes, "secret", pbe::MAX_ITERATIONS).unwrap();
+        assert!(pfx.mac_verified);
+        assert!(!pfx.needs_password());
+        assert_eq!(pfx.decrypted, 2);
+        let kinds: Vec<_> = pfx.bags.iter().map(|bag| bag.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["certBag", "certBag", "pkcs8ShroudedKeyBag"]);
+    }
+
+    #[test]
+    fn decrypt_legacy_pfx() {
+        let mut bytes = include_bytes!("../tests/legacy.p12").to_vec();
+        let (mut nodes, _) = node::parse(&bytes);
+        let mut pfx = Pfx::parse(&nodes[0], &bytes).unwrap();
+        pfx.decrypt(&mut nodes[0], &mut bytes, "secret", pbe::MAX_ITERATIONS).unwrap();
+        assert_eq!((pfx.encrypted, pfx.decrypted), (2, 2));
+        assert_eq!(pfx.bags[0].friendly_name.as_deref(), Some("Legacy leaf"));
+    }
+
+    #[test]
+    fn verify_empty_password() {
+        let mut bytes = include_bytes!("../tests/no-password.pfx").to_vec();
+        let (mut nodes, _) = node::parse(&bytes);
+        let mut pfx = Pfx::parse(&nodes[0], &bytes).unwrap();
+        pfx.decrypt(&mut nodes[0], &mut bytes, "", pbe::MAX_ITERATIONS).unwrap();
+        assert!(pfx.mac_verified);
+    }
+
+    #[test]
+    fn skip_enveloped_content() {
+        let mut bytes = include_bytes!("../tests/enveloped.pfx").to_vec();
+        let (mut nodes, _) = node::parse(&bytes);
+        let mut pfx = Pfx::parse(&nodes[0], &bytes).unwrap();
+        pfx.annotate(&mut nodes[0], &bytes);
+        assert_eq!((pfx.encrypted, pfx.enveloped), (0, 1));
+        assert!(pfx.needs_password());
+
+        pfx.decrypt(&mut nodes[0], &mut bytes, "", pbe::MAX_ITERATIONS).unwrap();
+        assert!(pfx.mac_verified);
+        assert!(!pfx.needs_password());
+        assert_eq!(pfx.enveloped, 1);
+    }
+
+    #[test]
+    fn reject_huge_mac_iterations() {
+        // Replace the 2048 MAC iterations by 2^31 - 1, which is two octets
+        // longer, and grow the lengths of the PFX and of the MacData.
+        let original = include_bytes!("../tests/no-password.pfx");
+        let (nodes, _) = node::parse(original);
+        let mac_offset = nodes[0].children[2].offset;
+        let mut bytes = original[..original.len() - 4].to_vec();
+        bytes.extend([0x02, 0x04, 0x7F, 0xFF, 0xFF, 0xFF]);
+        let len = u16::from_be_bytes([bytes[2], bytes[3]]) + 2;
+        bytes[2..4].copy_from_slice(&len.to_be_bytes());
+        bytes[mac_offset + 1] += 2;
+
+        let (mut nodes, error) = node::parse(&bytes);
+        assert!(error.is_none());
+        let mut pfx = Pfx::parse(&nodes[0], &bytes).unwrap();
+        assert_eq!(pfx.mac.as_ref().unwrap().iterations, 0x7FFF_FFFF);
+        assert_eq!(pfx.decrypt(&mut nodes[0], &mut bytes, "", pbe::MAX_ITERATIONS), Err(Error("Unsupported iteration count")));
+        assert!(!pfx.mac_verified);
+    }
+
+    #[test]
+    fn reject_certificate() {
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (nodes, _) = node::parse(bytes);
+        assert!(Pfx::parse(&nodes[0], bytes).is_err());
+    }
+}
@@ -0,0 +1,1026 @@
+use std::collections::HashMap;
+use crate::der::{Class, Tag};
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::value;
+
+/// Maximum nesting of types followed while matching, to stop on recursive
+/// types that don't consume elements.
+const MAX_DEPTH: usize = 512;
+
+/// How the tags of a module are applied when neither IMPLICIT nor EXPLICIT
+/// is written.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TagDefault {
+    Explicit,
+    Implicit,
+    Automatic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TagMode {
+    Explicit,
+    Implicit,
+}
+
+/// A type of the X.680 subset supported by the parser.
+#[derive(Clone, Debug)]
+pub enum Type {
+    /// A universal type matched on its tag only, like strings and times.
+    Universal(u32),
+    Integer(Vec<(String, i64)>),
+    Enumerated(Vec<(String, i64)>),
+    BitString(Vec<(String, i64)>),
+    Sequence(Components),
+    Set(Components),
+    Choice(Components),
+    SequenceOf(Box<Type>),
+    SetOf(Box<Type>),
+    Tagged { class: Class, number: u32, mode: Option<TagMode>, inner: Box<Type> },
+    Any,
+    /// A type defined by an assignment, possibly as `Module.Type`.
+    Reference(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Components {
+    pub list: Vec<Component>,
+    /// The type has an extension marker, so unknown elements are allowed.
+    pub extensible: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Component {
+    pub name: String,
+    pub ty: Type,
+    /// The component is OPTIONAL or has a DEFAULT value.
+    pub optional: bool,
+}
+
+/// An ASN.1 module, with its type assignments.
+#[derive(Clone, Debug)]
+pub struct Module {
+    pub name: String,
+    pub tag_default: TagDefault,
+    /// Module each imported symbol comes from.
+    pub imports: HashMap<String, String>,
+    /// Type assignments, in the order of the module.
+    pub types: Vec<(String, Type)>,
+}
+
+fn is_type_reference(token: &str) -> bool {
+    return token.starts_with(|c: char| c.is_ascii_uppercase());
+}
+
+fn is_value_reference(token: &str) -> bool {
+    return token.starts_with(|c: char| c.is_ascii_lowercase());
+}
+
+/// Split ASN.1 notation in tokens, with the line they start on, skipping
+/// comments.
+fn tokenize(text: &str) -> Result<Vec<(String, usize)>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut idx = 0;
+    let at = |idx: usize| chars.get(idx).copied().unwrap_or('\0');
+    while idx < chars.len() {
+        let c = chars[idx];
+        let start = idx;
+        if c == '\n' {
+            line += 1;
+            idx += 1;
+        } else if c.is_whitespace() {
+            idx += 1;
+        } else if c == '-' && at(idx + 1) == '-' {
+            // A comment ends with the line or with another pair of hyphens.
+            idx += 2;
+            while idx < chars.len() && chars[idx] != '\n' && !(chars[idx] == '-' && at(idx + 1) == '-') {
+                idx += 1;
+            }
+            if chars.get(idx) == Some(&'-') {
+                idx += 2;
+            }
+        } else if c == '/' && at(idx + 1) == '*' {
+            let mut depth = 0;
+            loop {
+                match (at(idx), at(idx + 1)) {
+                    ('\0', _) => return Err(Error("Unterminated comment")),
+                    ('/', '*') => (depth, idx) = (depth + 1, idx + 2),
+                    ('*', '/') => (depth, idx) = (depth - 1, idx + 2),
+                    ('\n', _) => (line, idx) = (line + 1, idx + 1),
+                    _ => idx += 1,
+                }
+                if depth == 0 {
+                    break;
+                }
+            }
+        } else if c == '"' || c == '\'' {
+            idx += 1;
+            while at(idx) != c || (c == '"' && at(idx + 1) == '"') {
+                match at(idx) {
+                    '\0' => return Err(Error("Unterminated string")),
+                    '\n' => line += 1,
+                    '"' => idx += 1,
+                    _ => {}
+                }
+                idx += 1;
+            }
+            idx += 1;
+            // The radix of binary and hexadecimal strings.
+            if c == '\'' && (at(idx) == 'B' || at(idx) == 'H') {
+                idx += 1;
+            }
+            tokens.push((chars[start..idx].iter().collect(), line));
+        } else if c.is_ascii_alphabetic() || (c == '&' && at(idx + 1).is_ascii_alphabetic()) {
+            idx += 1;
+            while at(idx).is_ascii_alphanumeric() || (at(idx) == '-' && at(idx + 1).is_ascii_alphanumeric()) {
+                idx += 1;
+            }
+            tokens.push((chars[start..idx].iter().collect(), line));
+        } else if c.is_ascii_digit() {
+            while at(idx).is_ascii_digit() {
+                idx += 1;
+            }
+            tokens.push((chars[start..idx].iter().collect(), line));
+        } else {
+            let symbols = ["::=", "...", "..", "[[", "]]"];
+            let symbol = symbols.iter().find(|symbol| symbol.chars().enumerate().all(|(i, s)| at(idx + i) == s));
+            let len = symbol.map(|symbol| symbol.len()).unwrap_or(1);
+            idx += len;
+            tokens.push((chars[start..idx].iter().collect(), line));
+        }
+    }
+    return Ok(tokens);
+}
+
+struct Parser {
+    tokens: Vec<(String, usize)>,
+    position: usize,
+    tag_default: TagDefault,
+}
+
+impl Parser {
+    fn peek_at(&self, offset: usize) -> &str {
+        return self.tokens.get(self.position + offset).map(|(token, _)| token.as_str()).unwrap_or("");
+    }
+
+    fn peek(&self) -> &str {
+        return self.peek_at(0);
+    }
+
+    fn line(&self) -> usize {
+        let last = self.tokens.last().map(|(_, line)| *line).unwrap_or(1);
+        return self.tokens.get(self.position).map(|(_, line)| *line).unwrap_or(last);
+    }
+
+    fn next(&mut self) -> Result<String> {
+        let (token, _) = self.tokens.get(self.position).ok_or(Error("Unexpected end of the module"))?;
+        self.position += 1;
+        return Ok(token.clone());
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.peek() != token {
+            return false;
+        }
+        self.position += 1;
+        return true;
+    }
+
+    fn expect(&mut self, token: &'static str, err: &'static str) -> Result<()> {
+        return if self.eat(token) { Ok(()) } else { Err(Error(err)) };
+    }
+
+    /// Skip an opening bracket and everything up to the matching one.
+    fn skip_balanced(&mut self) -> Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.next()?.as_str() {
+                "(" | "{" | "[" => depth += 1,
+                ")" | "}" | "]" => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skip a value, like the ones of DEFAULT and of value assignments.
+    fn skip_value(&mut self) -> Result<()> {
+        if self.peek() == "{" {
+            return self.skip_balanced();
+        }
+        self.eat("-");
+        self.next()?;
+        // The value of a CHOICE is written as `alternative: value`.
+        if self.eat(":") {
+            return self.skip_value();
+        }
+        return Ok(());
+    }
+
+    /// Whether the next tokens start a type or value assignment, to resume
+    /// parsing after an error.
+    fn at_assignment(&self) -> bool {
+        let (first, second) = (self.peek(), self.peek_at(1));
+        return (is_type_reference(first) && second == "::=")
+            || (is_value_reference(first) && is_type_reference(second) && self.peek_at(2) == "::=")
+            || (is_value_reference(first) && second == "OBJECT" && self.peek_at(3) == "::=");
+    }
+
+    fn module(&mut self, errors: &mut Vec<String>) -> Result<Module> {
+        let name = self.next()?;
+        if !is_type_reference(&name) {
+            return Err(Error("Expected a module name"));
+        }
+        if self.peek() == "{" {
+            self.skip_balanced()?;
+        }
+        self.expect("DEFINITIONS", "Expected DEFINITIONS")?;
+        self.tag_default = TagDefault::Explicit;
+        for (keyword, tag_default) in [
+            ("EXPLICIT", TagDefault::Explicit),
+            ("IMPLICIT", TagDefault::Implicit),
+            ("AUTOMATIC", TagDefault::Automatic),
+        ] {
+            if self.eat(keyword) {
+                self.tag_default = tag_default;
+                self.expect("TAGS", "Expected TAGS")?;
+            }
+        }
+        if self.eat("EXTENSIBILITY") {
+            self.expect("IMPLIED", "Expected IMPLIED")?;
+        }
+        self.expect("::=", "Expected ::=")?;
+        self.expect("BEGIN", "Expected BEGIN")?;
+
+        let mut module = Module { name, tag_default: self.tag_default, imports: HashMap::new(), types: Vec::new() };
+        if self.eat("EXPORTS") {
+            while self.next()? != ";" {}
+        }
+        if self.eat("IMPORTS") {
+            self.imports(&mut module)?;
+        }
+
+        while !self.eat("END") {
+            if self.peek().is_empty() {
+                return Err(Error("Missing END"));
+            }
+            let start = self.position;
+            if let Err(err) = self.assignment(&mut module) {
+                errors.push(format!("line {}: {}", self.line(), err));
+                self.position = start + 1;
+                while !self.peek().is_empty() && self.peek() != "END" && !self.at_assignment() {
+                    self.position += 1;
+                }
+            }
+        }
+        return Ok(module);
+    }
+
+    fn imports(&mut self, module: &mut Module) -> Result<()> {
+        let mut symbols = Vec::new();
+        loop {
+            let token = self.next()?;
+            match token.as_str() {
+                ";" => return Ok(()),
+                "," => {}
+                "{" => {
+                    // The empty braces of parameterized references.
+                    self.position -= 1;
+                    self.skip_balanced()?;
+                }
+                "FROM" => {
+                    let from = self.next()?;
+                    for symbol in symbols.drain(..) {
+                        module.imports.insert(symbol, from.clone());
+                    }
+                    // The object identifier, or the value reference, of the module.
+                    if self.peek() == "{" {
+                        self.skip_balanced()?;
+                    } else if is_value_reference(self.peek()) && !matches!(self.peek_at(1), "," | "FROM") {
+                        self.position += 1;
+                    }
+                }
+                _ => symbols.push(token),
+            }
+        }
+    }
+
+    fn assignment(&mut self, module: &mut Module) -> Result<()> {
+        let name = self.next()?;
+        if is_type_reference(&name) {
+            if self.peek() == "{" {
+                return Err(Error("Parameterized types aren't supported"));
+            }
+            self.expect("::=", "Expected ::=")?;
+            let ty = self.ty()?;
+            module.types.push((name, ty));
+            return Ok(());
+        }
+        if is_value_reference(&name) {
+            // Values, like object identifiers, aren't used to match elements.
+            self.ty()?;
+            self.expect("::=", "Expected ::=")?;
+            return self.skip_value();
+        }
+        return Err(Error("Expected an assignment"));
+    }
+
+    /// Named numbers or bits, and enumeration items which are numbered
+    /// automatically when their number is omitted.
+    fn named_numbers(&mut self) -> Result<Vec<(String, i64)>> {
+        let mut named: Vec<(String, Option<i64>)> = Vec::new();
+        self.expect("{", "Expected {")?;
+        loop {
+            let name = self.next()?;
+            match name.as_str() {
+                "}" => break,
+                "," => continue,
+                "..." => {
+                    if self.eat("!") {
+                        self.skip_value()?;
+                    }
+                    continue;
+                }
+                _ if !is_value_reference(&name) => return Err(Error("Expected an identifier")),
+                _ => {}
+            }
+            let mut number = None;
+            if self.eat("(") {
+                let negative = self.eat("-");
+                let token = self.next()?;
+                // Numbers defined by value references can't be resolved.
+                number = token.parse::<i64>().ok().map(|number| if negative { -number } else { number });
+                self.expect(")", "Expected )")?;
+                if number.is_none() {
+                    continue;
+                }
+            }
+            named.push((name, number));
+        }
+
+        let mut used: Vec<i64> = named.iter().filter_map(|(_, number)| *number).collect();
+        let mut next = 0;
+        let mut numbers = Vec::new();
+        for (name, number) in named {
+            let number = match number {
+                Some(number) => number,
+                None => {
+                    while used.contains(&next) {
+                        next += 1;
+                    }
+                    used.push(next);
+                    next
+                }
+            };
+            numbers.push((name, number));
+        }
+        return Ok(numbers);
+    }
+
+    fn components(&mut self) -> Result<Components> {
+        let mut components = Components { list: Vec::new(), extensible: false };
+        self.expect("{", "Expected {")?;
+        loop {
+            let token = self.next()?;
+            match token.as_str() {
+                "}" => break,
+                "," | "[[" | "]]" => {}
+                "..." => {
+                    components.extensible = true;
+                    if self.eat("!") {
+                        self.skip_value()?;
+                    }
+                }
+                // The version number of an extension addition group.
+                number if number.parse::<u32>().is_ok() && self.peek() == ":" => self.position += 1,
+                "COMPONENTS" => return Err(Error("COMPONENTS OF isn't supported")),
+                name if is_value_reference(name) => {
+                    let ty = self.ty()?;
+                    let optional = if self.eat("DEFAULT") {
+                        self.skip_value()?;
+                        true
+                    } else {
+                        self.eat("OPTIONAL")
+                    };
+                    components.list.push(Component { name: token, ty, optional });
+                }
+                _ => return Err(Error("Expected a component")),
+            }
+        }
+
+        // Automatic tagging numbers the components when none is tagged.
+        let tagged = components.list.iter().any(|component| matches!(component.ty, Type::Tagged { .. }));
+        if self.tag_default == TagDefault::Automatic && !tagged {
+            for (number, component) in components.list.iter_mut().enumerate() {
+                let inner = Box::new(std::mem::replace(&mut component.ty, Type::Any));
+                component.ty = Type::Tagged { class: Class::ContextSpecific, number: number as u32, mode: None, inner };
+            }
+        }
+        return Ok(components);
+    }
+
+    fn ty(&mut self) -> Result<Type> {
+        let token = self.next()?;
+        let ty = match token.as_str() {
+            "[" => {
+                let class = match self.peek() {
+                    "UNIVERSAL" => Class::Universal,
+                    "APPLICATION" => Class::Application,
+                    "PRIVATE" => Class::Private,
+                    _ => Class::ContextSpecific,
+                };
+                if class != Class::ContextSpecific {
+                    self.position += 1;
+                }
+                let number = self.next()?.parse::<u32>().map_err(|_| Error("Expected a tag number"))?;
+                self.expect("]", "Expected ]")?;
+                let mode = if self.eat("IMPLICIT") {
+                    Some(TagMode::Implicit)
+                } else if self.eat("EXPLICIT") {
+                    Some(TagMode::Explicit)
+                } else {
+                    None
+                };
+                Type::Tagged { class, number, mode, inner: Box::new(self.ty()?) }
+            }
+            "BOOLEAN" => Type::Universal(1),
+            "INTEGER" if self.peek() == "{" => Type::Integer(self.named_numbers()?),
+            "INTEGER" => Type::Integer(Vec::new()),
+            "ENUMERATED" => Type::Enumerated(self.named_numbers()?),
+            "BIT" => {
+                self.expect("STRING", "Expected STRING")?;
+                Type::BitString(if self.peek() == "{" { self.named_numbers()? } else { Vec::new() })
+            }
+            "OCTET" => {
+                self.expect("STRING", "Expected STRING")?;
+                Type::Universal(4)
+            }
+            "NULL" => Type::Universal(5),
+            "OBJECT" => {
+                self.expect("IDENTIFIER", "Expected IDENTIFIER")?;
+                Type::Universal(6)
+            }
+            "ObjectDescriptor" => Type::Universal(7),
+            "EXTERNAL" => Type::Universal(8),
+            "REAL" => Type::Universal(9),
+            "UTF8String" => Type::Universal(12),
+            "RELATIVE-OID" => Type::Universal(13),
+            "NumericString" => Type::Universal(18),
+            "PrintableString" => Type::Universal(19),
+            "TeletexString" | "T61String" => Type::Universal(20),
+            "VideotexString" => Type::Universal(21),
+            "IA5String" => Type::Universal(22),
+            "UTCTime" => Type::Universal(23),
+            "GeneralizedTime" => Type::Universal(24),
+            "GraphicString" => Type::Universal(25),
+            "VisibleString" | "ISO646String" => Type::Universal(26),
+            "GeneralString" => Type::Universal(27),
+            "UniversalString" => Type::Universal(28),
+            "BMPString" => Type::Universal(30),
+            "ANY" => {
+                if self.eat("DEFINED") {
+                    self.expect("BY", "Expected BY")?;
+                    self.next()?;
+                }
+                Type::Any
+            }
+            "SEQUENCE" | "SET" if self.peek() == "{" => match token.as_str() {
+                "SEQUENCE" => Type::Sequence(self.components()?),
+                _ => Type::Set(self.components()?),
+            },
+            "SEQUENCE" | "SET" => {
+                self.eat("SIZE");
+                if self.peek() == "(" {
+                    self.skip_balanced()?;
+                }
+                self.expect("OF", "Expected OF")?;
+                // The name of the items, in `SEQUENCE OF name Type`.
+                if is_value_reference(self.peek()) {
+                    self.position += 1;
+                }
+                match token.as_str() {
+                    "SEQUENCE" => Type::SequenceOf(Box::new(self.ty()?)),
+                    _ => Type::SetOf(Box::new(self.ty()?)),
+                }
+            }
+            "CHOICE" => Type::Choice(self.components()?),
+            "CLASS" | "INSTANCE" | "TYPE-IDENTIFIER" | "ABSTRACT-SYNTAX" => {
+                return Err(Error("Information object classes aren't supported"));
+            }
+            name if is_type_reference(name) => {
+                if self.peek() == "{" {
+                    return Err(Error("Parameterized types aren't supported"));
+                }
+                if self.peek() == "." && is_type_reference(self.peek_at(1)) {
+                    self.position += 1;
+                    Type::Reference(format!("{}.{}", name, self.next()?))
+                } else if self.peek() == "." {
+                    return Err(Error("Information object classes aren't supported"));
+                } else {
+                    Type::Reference(token)
+                }
+            }
+            _ => return Err(Error("Expected a type")),
+        };
+
+        // Constraints don't change the encoding.
+        while self.peek() == "(" {
+            self.skip_balanced()?;
+        }
+        return Ok(ty);
+    }
+}
+
+/// Parse the modules of an ASN.1 file. Assignments that can't be parsed are
+/// skipped, and reported with their line.
+pub fn parse(text: &str) -> (Vec<Module>, Vec<String>) {
+    let mut errors = Vec::new();
+    let tokens = match tokenize(text) {
+        Ok(tokens) => tokens,
+        Err(err) => return (Vec::new(), vec![err.to_string()]),
+    };
+
+    let mut parser = Parser { tokens, position: 0, tag_default: TagDefault::Explicit };
+    let mut modules = Vec::new();
+    while !parser.peek().is_empty() {
+        match parser.module(&mut errors) {
+            Ok(module) => modules.push(module),
+            Err(err) => {
+                errors.push(format!("line {}: {}", parser.line(), err));
+                break;
+            }
+        }
+    }
+    return (modules, errors);
+}
+
+/// The modules loaded from ASN.1 files, whose types can be applied to
+/// elements.
+#[derive(Default)]
+pub struct Schema {
+    pub modules: Vec<Module>,
+}
+
+/// The value of a small INTEGER or ENUMERATED.
+fn small_integer(content: &[u8]) -> Option<i64> {
+    if content.is_empty() || 8 < content.len() {
+        return None;
+    }
+    let first = if content[0] & 0x80 != 0 { -1 } else { 0 };
+    return Some(content.iter().fold(first, |value, byte| (value << 8) | i64::from(*byte)));
+}
+
+/// Remove the type names, CHOICE alternatives and implicit types of a
+/// previously applied schema.
+pub fn clear(node: &mut Node) {
+    node.type_name = None;
+    node.alternative = None;
+    node.implicit = None;
+    for child in node.children.iter_mut() {
+        clear(child);
+    }
+}
+
+struct Matcher<'a> {
+    schema: &'a Schema,
+    bytes: &'a [u8],
+    /// Path of the element being matched, from the element the type is
+    /// applied to.
+    path: Vec<usize>,
+    mismatches: Vec<(Vec<usize>, String)>,
+    depth: usize,
+}
+
+impl<'a> Matcher<'a> {
+    /// The type a reference designates, from the module at `module`, and the
+    /// module it's defined in.
+    fn resolve(&self, module: usize, name: &str) -> Result<(usize, &'a Type)> {
+        let schema = self.schema;
+        let mut module = module;
+        let mut name = name;
+        if let Some((module_name, type_name)) = name.split_once('.') {
+            module = schema.find(module_name).ok_or(Error("Undefined module"))?;
+            name = type_name;
+        }
+        // Follow the imports, which may be imported themselves.
+        for _ in 0..schema.modules.len() + 1 {
+            if let Some((_, ty)) = schema.modules[module].types.iter().find(|(type_name, _)| type_name == name) {
+                return Ok((module, ty));
+            }
+            let from = schema.modules[module].imports.get(name).ok_or(Error("Undefined type"))?;
+            module = schema.find(from).ok_or(Error("The module of an imported type isn't loaded"))?;
+        }
+        return Err(Error("Circular imports"));
+    }
+
+    /// Follow references until the type is built in.
+    fn dereference(&self, module: usize, ty: &'a Type) -> Option<(usize, &'a Type)> {
+        let (mut module, mut ty) = (module, ty);
+        for _ in 0..MAX_DEPTH {
+            match ty {
+                Type::Reference(name) => (module, ty) = self.resolve(module, name).ok()?,
+                _ => return Some((module, ty)),
+            }
+        }
+        return None;
+    }
+
+    /// The class and number of the tags an element of the type may have, or
+    /// None when it may have any tag.
+    fn tags(&self, module: usize, ty: &'a Type) -> Option<Vec<(Class, u32)>> {
+        return self.collect_tags(module, ty, &mut Vec::new());
+    }
+
+    /// The tags of `tags`, skipping the CHOICEs in `visited` whose tags are
+    /// already collected, so that recursive CHOICEs end.
+    fn collect_tags(&self, module: usize, ty: &'a Type, visited: &mut Vec<*const Type>) -> Option<Vec<(Class, u32)>> {
+        let (module, ty) = match self.dereference(module, ty) {
+            Some(resolved) => resolved,
+            None => return Some(Vec::new()),
+        };
+        let universal = |number| Some(vec![(Class::Universal, number)]);
+        return match ty {
+            Type::Universal(number) => universal(*number),
+            Type::Integer(_) => universal(2),
+            Type::BitString(_) => universal(3),
+            Type::Enumerated(_) => universal(10),
+            Type::Sequence(_) | Type::SequenceOf(_) => universal(16),
+            Type::Set(_) | Type::SetOf(_) => universal(17),
+            Type::Tagged { class, number, .. } => Some(vec![(*class, *number)]),
+            Type::Choice(components) => {
+                if visited.contains(&(ty as *const Type)) {
+                    return Some(Vec::new());
+                }
+                visited.push(ty);
+                let mut tags = Vec::new();
+                for component in components.list.iter() {
+                    tags.extend(self.collect_tags(module, &component.ty, visited)?);
+                }
+                Some(tags)
+            }
+            Type::Any | Type::Reference(_) => None,
+        };
+    }
+
+    /// The class and number of the tags the first child of an element of the
+    /// type may have, or None when it may have any tag or no child.
+    fn first_tags(&self, module: usize, ty: &'a Type) -> Option<Vec<(Class, u32)>> {
+        let (mut module, mut ty) = (module, ty);
+        for _ in 0..MAX_DEPTH {
+            (module, ty) = self.dereference(module, ty)?;
+            match ty {
+                Type::Tagged { mode, inner, .. } if self.is_explicit(module, *mode, inner) => {
+                    return self.tags(module, inner);
+                }
+                Type::Tagged { inner, .. } => ty = inner,
+                Type::Sequence(components) => {
+                    let mut tags = Vec::new();
+                    for component in components.list.iter() {
+                        tags.extend(self.tags(module, &component.ty)?);
+                        if !component.optional {
+                            return Some(tags);
+                        }
+                    }
+                    return None;
+                }
+                _ => return None,
+            }
+        }
+        return None;
+    }
+
+    fn matches(&self, module: usize, ty: &'a Type, node: &Node) -> bool {
+        return match self.tags(module, ty) {
+            Some(tags) => tags.contains(&(node.tag.class, node.tag.number)),
+            None => true,
+        };
+    }
+
+    /// Whether a tag without IMPLICIT or EXPLICIT is explicit. Tags on CHOICE
+    /// and ANY are always explicit.
+    fn is_explicit(&self, module: usize, mode: Option<TagMode>, inner: &'a Type) -> bool {
+        let untagged = matches!(self.dereference(module, inner), Some((_, Type::Choice(_) | Type::Any)));
+        return match mode {
+            Some(TagMode::Explicit) => true,
+            Some(TagMode::Implicit) => untagged,
+            None => self.schema.modules[module].tag_default == TagDefault::Explicit || untagged,
+        };
+    }
+
+    /// Match the child at `idx` with a component, naming it, and record its
+    /// mismatch if it doesn't match.
+    fn component(&mut self, module: usize, component: &'a Component, node: &mut Node, idx: usize) {
+        let child = &mut node.children[idx];
+        child.set_field(&component.name);
+        self.path.push(idx);
+        if let Err(err) = self.apply(module, &component.ty, child, false) {
+            self.mismatches.push((self.path.clone(), format!("{}: {}", component.name, err)));
+        }
+        self.path.pop();
+    }
+
+    fn sequence(&mut self, module: usize, components: &'a Components, node: &mut Node) -> Result<()> {
+        let mut idx = 0;
+        for component in components.list.iter() {
+            match node.children.get(idx) {
+                Some(child) if self.matches(module, &component.ty, child) => {
+                    self.component(module, component, node, idx);
+                    idx += 1;
+                }
+                _ if component.optional => {}
+                Some(_) => return Err(Error("Unexpected element in place of a mandatory component")),
+                None => return Err(Error("Missing mandatory component")),
+            }
+        }
+        if idx < node.children.len() && !components.extensible {
+            return Err(Error("Unexpected element after the last component"));
+        }
+        return Ok(());
+    }
+
+    fn set(&mut self, module: usize, components: &'a Components, node: &mut Node) -> Result<()> {
+        let mut found = vec![false; components.list.len()];
+        for idx in 0..node.children.len() {
+            let position = components
+                .list
+                .iter()
+                .enumerate()
+                .position(|(position, component)| !found[position] && self.matches(module, &component.ty, &node.children[idx]));
+            match position {
+                Some(position) => {
+                    found[position] = true;
+                    self.component(module, &components.list[position], node, idx);
+                }
+                None if components.extensible => {}
+                None => return Err(Error("Unexpected element in a SET")),
+            }
+        }
+        if components.list.iter().zip(found).any(|(component, found)| !component.optional && !found) {
+            return Err(Error("Missing mandatory component"));
+        }
+        return Ok(());
+    }
+
+    /// Match the element with the type, naming its descendants. `implicit`
+    /// tells whether the tag of the type was replaced by an implicit tag.
+    fn apply(&mut self, module: usize, ty: &'a Type, node: &mut Node, implicit: bool) -> Result<()> {
+        if MAX_DEPTH <= self.depth {
+            return Err(Error("Types are nested too deeply"));
+        }
+        self.depth += 1;
+        let result = self.apply_type(module, ty, node, implicit);
+        self.depth -= 1;
+        return result;
+    }
+
+    fn apply_type(&mut self, module: usize, ty: &'a Type, node: &mut Node, implicit: bool) -> Result<()> {
+        let tag = (node.tag.class, node.tag.number);
+        match ty {
+            Type::Reference(name) => {
+                let (target, resolved) = self.resolve(module, name)?;
+                if node.type_name.is_none() {
+                    node.type_name = Some(name.clone());
+                }
+                return self.apply(target, resolved, node, implicit);
+            }
+            Type::Tagged { class, number, mode, inner } => {
+                if !implicit && tag != (*class, *number) {
+                    return Err(Error("Unexpected tag"));
+                }
+                if !self.is_explicit(module, *mode, inner) {
+                    return self.apply(module, inner, node, true);
+                }
+                if !node.tag.constructed || node.children.len() != 1 {
+                    return Err(Error("An explicit tag must hold a single element"));
+                }
+                self.path.push(0);
+                let result = self.apply(module, inner, &mut node.children[0], false);
+                self.path.pop();
+                return result;
+            }
+            Type::Any => return Ok(()),
+            Type::Choice(components) => {
+                let alternative = components.list.iter().find(|component| self.matches(module, &component.ty, node));
+                return match alternative {
+                    Some(alternative) => {
+                        match node.field.is_some() {
+                            true => node.note = node.note.take().or_else(|| Some(alternative.name.clone())),
+                            false => node.set_field(&alternative.name),
+                        }
+                        node.alternative = node.alternative.take().or_else(|| Some(alternative.name.clone()));
+                        self.apply(module, &alternative.ty, node, false)
+                    }
+                    None if components.extensible => Ok(()),
+                    None => Err(Error("No alternative of the CHOICE matches")),
+                };
+            }
+            _ => {}
+        }
+
+        let expected = match self.tags(module, ty) {
+            Some(tags) => tags,
+            None => return Ok(()),
+        };
+        if !implicit && !expected.contains(&tag) {
+            return Err(Error("Unexpected tag"));
+        }
+        if implicit && node.tag.class != Class::Universal {
+            if let [(Class::Universal, number)] = expected.as_slice() {
+                node.implicit = Some(Tag { class: Class::Universal, constructed: node.tag.constructed, number: *number });
+            }
+        }
+        let constructed = matches!(ty, Type::Sequence(_) | Type::Set(_) | Type::SequenceOf(_) | Type::SetOf(_));
+        if constructed && !node.tag.constructed {
+            return Err(Error("Expected a constructed element"));
+        }
+
+        match ty {
+            Type::Integer(names) | Type::Enumerated(names) => {
+                let number = small_integer(node.content(self.bytes));
+                if let Some((name, _)) = names.iter().find(|(_, value)| Some(*value) == number) {
+                    node.note = node.note.take().or_else(|| Some(name.clone()));
+                }
+            }
+            Type::BitString(names) if !names.is_empty() => {
+                let (_, bits) = value::bit_string(node.content(self.bytes))?;
+                let mut set = Vec::new();
+                for bit in 0..bits.len() * 8 {
+                    if bits[bit / 8] & (0x80 >> (bit % 8)) != 0 {
+                        match names.iter().find(|(_, number)| *number == bit as i64) {
+                            Some((name, _)) => set.push(name.clone()),
+                            None => set.push(bit.to_string()),
+                        }
+                    }
+                }
+                node.note = node.note.take().or_else(|| Some(set.join(", ")));
+            }
+            Type::Sequence(components) => return self.sequence(module, components, node),
+            Type::Set(components) => return self.set(module, components, node),
+            Type::SequenceOf(item) | Type::SetOf(item) => {
+                for (idx, child) in node.children.iter_mut().enumerate() {
+                    self.path.push(idx);
+                    if let Err(err) = self.apply(module, item, child, false) {
+                        self.mismatches.push((self.path.clone(), err.to_string()));
+                    }
+                    self.path.pop();
+                }
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+}
+
+impl Schema {
+    /// Add the modules of a file, replacing the loaded ones with the same name.
+    pub fn add(&mut self, modules: Vec<Module>) {
+        for module in modules {
+            match self.find(&module.name) {
+                Some(idx) => self.modules[idx] = module,
+                None => self.modules.push(module),
+            }
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<usize> {
+        return self.modules.iter().position(|module| module.name == name);
+    }
+
+    /// Every type of every module, as `Module.Type`.
+    pub fn type_names(&self) -> Vec<String> {
+        return self
+            .modules
+            .iter()
+            .flat_map(|module| module.types.iter().map(move |(name, _)| format!("{}.{}", module.name, name)))
+            .collect();
+    }
+
+    /// Whether the tags of the element and of its first child fit the type
+    /// `Module.Type`, a quick check before applying it.
+    pub fn fits(&self, type_name: &str, node: &Node) -> bool {
+        let matcher = Matcher { schema: self, bytes: &[], path: Vec::new(), mismatches: Vec::new(), depth: 0 };
+        let ty = Type::Reference(type_name.to_owned());
+        if !matcher.matches(0, &ty, node) {
+            return false;
+        }
+        return match (matcher.first_tags(0, &ty), node.children.first()) {
+            (Some(tags), Some(child)) => tags.contains(&(child.tag.class, child.tag.number)),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+    }
+
+    /// Name the element and its descendants after the type `Module.Type`,
+    /// returning the path from `node` and the reason of every element which
+    /// doesn't match. Those keep their previous names.
+    pub fn apply(&self, type_name: &str, node: &mut Node, bytes: &[u8]) -> Vec<(Vec<usize>, String)> {
+        clear(node);
+        let mut matcher = Matcher { schema: self, bytes, path: Vec::new(), mismatches: Vec::new(), depth: 0 };
+        let ty = Type::Reference(type_name.to_owned());
+        if let Err(err) = matcher.apply(0, &ty, node, false) {
+            matcher.mismatches.insert(0, (Vec::new(), format!("{}: {}", type_name, err)));
+        }
+        return matcher.mismatches;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    fn schema() -> Schema {
+        let (modules, errors) = parse(include_str!("../tests/certificate.asn1"));
+        assert_eq!(errors, vec!["line 72: Information object classes aren't supported"]);
+        let mut schema = Schema::default();
+        schema.add(modules);
+        return schema;
+    }
+
+    #[test]
+    fn parse_modules() {
+        let schema = schema();
+        assert_eq!(schema.modules.len(), 2);
+        assert_eq!(schema.modules[0].tag_default, TagDefault::Explicit);
+        assert_eq!(schema.modules[1].imports.get("Name").map(String::as_str), Some("Names"));
+        assert!(schema.type_names().contains(&"Certificates.TBSCertificate".to_owned()));
+
+        let (modules, errors) = parse("M DEFINITIONS AUTOMATIC TAGS ::= BEGIN E ::= ENUMERATED { a, b(0), c } END");
+        assert!(errors.is_empty());
+        match &modules[0].types[0].1 {
+            Type::Enumerated(names) => assert_eq!(names, &[("a".to_owned(), 1), ("b".to_owned(), 0), ("c".to_owned(), 2)]),
+            ty => panic!("unexpected type {:?}", ty),
+        }
+        let (_, errors) = parse("M DEFINITIONS ::= BEGIN T ::= SEQUENCE { a INTEGER");
+        assert_eq!(errors, vec!["line 1: Unexpected end of the module", "line 1: Missing END"]);
+    }
+
+    #[test]
+    fn apply_to_certificate() {
+        let schema = schema();
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (mut nodes, _) = node::parse(bytes);
+        let mismatches = schema.apply("Certificates.Certificate", &mut nodes[0], bytes);
+        assert_eq!(mismatches, vec![]);
+        assert_eq!(nodes[0].type_name.as_deref(), Some("Certificates.Certificate"));
+        let tbs = &nodes[0].children[0];
+        assert_eq!(tbs.field.as_deref(), Some("tbsCertificate"));
+        assert_eq!(tbs.type_name.as_deref(), Some("TBSCertificate"));
+        assert_eq!(tbs.children[0].children[0].note.as_deref(), Some("v3"));
+        assert_eq!(tbs.children[3].type_name.as_deref(), Some("Name"));
+        assert_eq!(tbs.children[3].note.as_deref(), Some("rdnSequence"));
+        let extension = &tbs.children[7].children[0].children[0];
+        assert_eq!(extension.children[0].field.as_deref(), Some("extnID"));
+    }
+
+    #[test]
+    fn report_mismatches() {
+        let schema = schema();
+        let bytes = include_bytes!("../tests/leaf.csr");
+        let (mut nodes, _) = node::parse(bytes);
+        let mismatches = schema.apply("Certificates.Certificate", &mut nodes[0], bytes);
+        let tbs = mismatches.iter().find(|(path, _)| *path == [0]).map(|(_, reason)| reason.as_str());
+        assert_eq!(tbs, Some("tbsCertificate: Unexpected element in place of a mandatory component"));
+        assert!(mismatches.iter().any(|(path, reason)| *path == [0, 1] && reason.starts_with("signature: ")));
+
+        let (modules, _) = parse(
+            "Auto DEFINITIONS AUTOMATIC TAGS ::= BEGIN \
+             Pair ::= SEQUENCE { first INTEGER OPTIONAL, second Choice } \
+             Choice ::= CHOICE { number INTEGER, flag BOOLEAN } END",
+        );
+        let mut schema = Schema::default();
+        schema.add(modules);
+        // SEQUENCE { [1] { [1] TRUE } }, the CHOICE being explicitly tagged.
+        let bytes = [0x30, 0x05, 0xA1, 0x03, 0x81, 0x01, 0xFF];
+        let (mut nodes, _) = node::parse(&bytes);
+        assert!(schema.fits("Auto.Pair", &nodes[0]) && !schema.fits("Auto.Choice", &nodes[0]));
+        assert_eq!(schema.apply("Auto.Pair", &mut nodes[0], &bytes), vec![]);
+        assert_eq!(nodes[0].children[0].field.as_deref(), Some("second"));
+        assert_eq!(nodes[0].children[0].children[0].field.as_deref(), Some("flag"));
+        assert_eq!(nodes[0].children[0].children[0].alternative.as_deref(), Some("flag"));
+        assert_eq!(nodes[0].children[0].children[0].implicit, Some(Tag::BOOLEAN));
+        assert!(!schema.apply("Auto.Choice", &mut nodes[0], &bytes).is_empty());
+    }
+
+    #[test]
+    fn recursive_choice() {
+        let (modules, errors) = parse(
+            "Loop DEFINITIONS ::= BEGIN \
+             A ::= CHOICE { a A, b INTEGER } \
+             B ::= CHOICE { a C, b C } \
+             C ::= CHOICE { a B, b BOOLEAN } END",
+        );
+        assert!(errors.is_empty());
+        let mut schema = Schema::default();
+        schema.add(modules);
+        let bytes = [0x02, 0x01, 0x05];
+        let (mut nodes, _) = node::parse(&bytes);
+        assert!(schema.fits("Loop.A", &nodes[0]) && !schema.fits("Loop.B", &nodes[0]));
+        assert_eq!(schema.apply("Loop.A", &mut nodes[0], &bytes).len(), 1);
+        assert_eq!(schema.apply("Loop.B", &mut nodes[0], &bytes).len(), 1);
+    }
+}
@@ -0,0 +1,183 @@
+use std::path::Path;
+use imgui::*;
+use crate::app::Selection;
+use crate::document::Document;
+use crate::schema::{self, Schema};
+
+/// Elements which don't match a type: the element the type was applied to,
+/// the type, and each mismatching element with the reason.
+type Report = (Selection, String, Vec<(Selection, String)>);
+
+/// Tool window loading ASN.1 modules and applying one of their types to the
+/// selected element.
+pub struct SchemaWindow {
+    pub opened: bool,
+    pub schema: Schema,
+    /// Path typed by the user, for the files which aren't dropped.
+    path: String,
+    files: Vec<String>,
+    errors: Vec<String>,
+    type_names: Vec<String>,
+    type_idx: usize,
+    filter: String,
+    /// Element the type was last applied to, with the type and the
+    /// mismatching elements, kept until the documents change.
+    applied: Option<Report>,
+    generation: usize,
+}
+
+/// What the user asked to do from the window.
+pub enum Request {
+    /// Apply the type to the element.
+    Apply(Selection, String),
+    Show(Selection),
+}
+
+impl SchemaWindow {
+    pub fn new() -> Self {
+        return Self {
+            opened: false,
+            schema: Schema::default(),
+            path: String::new(),
+            files: Vec::new(),
+            errors: Vec::new(),
+            type_names: Vec::new(),
+            type_idx: 0,
+            filter: String::new(),
+            applied: None,
+            generation: 0,
+        };
+    }
+
+    /// Load the modules of an ASN.1 file, reporting its errors in the window.
+    pub fn load(&mut self, path: &Path) {
+        self.opened = true;
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                self.errors.push(format!("{}: {}", path.display(), err));
+                return;
+            }
+        };
+
+        let (modules, errors) = schema::parse(&text);
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        self.errors.extend(errors.into_iter().map(|err| format!("{}: {}", name, err)));
+        let names: Vec<&str> = modules.iter().map(|module| module.name.as_str()).collect();
+        self.files.push(format!("{} ({})", name, names.join(", ")));
+        self.schema.add(modules);
+        self.type_names = self.schema.type_names();
+        if self.type_names.len() <= self.type_idx {
+            self.type_idx = 0;
+        }
+    }
+
+    /// Keep the elements which don't match the applied type, to list them.
+    pub fn report(&mut self, selection: Selection, type_name: String, mismatches: Vec<(Vec<usize>, String)>, generation: usize) {
+        let mismatches = mismatches
+            .into_iter()
+            .map(|(path, reason)| {
+                let mut element = selection.clone();
+                element.path.extend(path);
+                return (element, reason);
+            })
+            .collect();
+        self.applied = Some((selection, type_name, mismatches));
+        self.generation = generation;
+    }
+
+    /// Draw the window and returns what the user asked for, if anything.
+    ///
+    /// `generation` must change whenever `documents` changes.
+    pub fn draw(&mut self, ui: &Ui, documents: &[Document], selection: Option<&Selection>, generation: usize) -> Option<Request> {
+        if !self.opened {
+            return None;
+        }
+        if self.generation != generation {
+            self.applied = None;
+        }
+
+        let mut opened = self.opened;
+        let mut request = None;
+        ui.window("ASN.1 schemas")
+            .size([640.0, 420.0], Condition::FirstUseEver)
+            .opened(&mut opened)
+            .build(|| {
+                ui.text("Drop .asn1 module files on the window, or load one by path.");
+                ui.input_text("##path", &mut self.path).hint("path to an ASN.1 module").build();
+                ui.same_line();
+                if ui.button("Load") && !self.path.trim().is_empty() {
+                    let path = self.path.trim().to_owned();
+                    self.load(Path::new(&path));
+                }
+                for file in self.files.iter() {
+                    ui.text(format!("Loaded '{}'", file));
+                }
+                for err in self.errors.iter() {
+                    ui.text_colored([1.0, 0.0, 0.0, 1.0], err);
+                }
+                if !self.errors.is_empty() && ui.small_button("Dismiss") {
+                    self.errors.clear();
+                }
+                ui.separator();
+
+                if self.type_names.is_empty() {
+                    ui.text("No type loaded.");
+                    return;
+                }
+                ui.input_text("Filter", &mut self.filter).build();
+                let filter = self.filter.to_ascii_lowercase();
+                let shown: Vec<usize> = (0..self.type_names.len())
+                    .filter(|idx| self.type_names[*idx].to_ascii_lowercase().contains(&filter))
+                    .collect();
+                let preview = self.type_names.get(self.type_idx).map(String::as_str).unwrap_or("");
+                if let Some(_) = ui.begin_combo("Type", preview) {
+                    for idx in shown {
+                        if ui.selectable_config(&self.type_names[idx]).selected(idx == self.type_idx).build() {
+                            self.type_idx = idx;
+                        }
+                    }
+                }
+
+                let target = selection.and_then(|selection| {
+                    let document = documents.get(selection.document)?;
+                    return Some((selection, document, document.node(&selection.path)?));
+                });
+                match target {
+                    Some((selection, document, node)) => {
+                        ui.text(format!("{:?} at offset {} in '{}'", node.tag, node.offset, document.name));
+                        if ui.button("Apply to the selected element") {
+                            request = Some(Request::Apply(selection.clone(), self.type_names[self.type_idx].clone()));
+                        }
+                    }
+                    None => ui.text("Select an element in a document to apply the type to it."),
+                }
+
+                let (applied, type_name, mismatches) = match self.applied.as_ref() {
+                    Some(applied) => applied,
+                    None => return,
+                };
+                ui.separator();
+                let name = documents.get(applied.document).map(|document| document.name.as_str()).unwrap_or("");
+                if mismatches.is_empty() {
+                    ui.text_colored([0.0, 0.8, 0.0, 1.0], format!("Every element of '{}' matches {}", name, type_name));
+                    return;
+                }
+                ui.text_colored(
+                    [1.0, 0.0, 0.0, 1.0],
+                    format!("{} element(s) of '{}' don't match {}:", mismatches.len(), name, type_name),
+                );
+                for (idx, (element, reason)) in mismatches.iter().enumerate() {
+                    let _id = ui.push_id_usize(idx);
+                    if ui.small_button("Show") {
+                        request = Some(Request::Show(element.clone()));
+                    }
+                    ui.same_line();
+                    ui.text(reason);
+                }
+            });
+
+        self.opened = opened;
+        return request;
+    }
+}
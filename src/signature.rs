@@ -0,0 +1,312 @@
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use rsa::{BigUint, Pkcs1v15Sign, Pss, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use sha3::Sha3_256;
+use crate::der::{Class, Tag};
+use crate::error::{Error, Result};
+use crate::hash;
+use crate::node::Node;
+use crate::{oid, spki, value, x509};
+
+/// Larger RSA keys would make the verification too slow.
+const MAX_RSA_BITS: usize = 16384;
+
+const INVALID: Error = Error("Invalid signature");
+const MALFORMED: Error = Error("Malformed signature");
+
+/// A public key that signatures can be verified with.
+pub enum PublicKey {
+    Rsa(RsaPublicKey),
+    P256(p256::ecdsa::VerifyingKey),
+    P384(p384::ecdsa::VerifyingKey),
+    P521(p521::ecdsa::VerifyingKey),
+    Ed25519(ed25519_dalek::VerifyingKey),
+    Ed448(ed448_goldilocks_plus::VerifyingKey),
+}
+
+/// How a signature is computed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scheme {
+    Pkcs1(hash::Algorithm),
+    /// The hash, also used by MGF1, and the salt length.
+    Pss(hash::Algorithm, usize),
+    Ecdsa(hash::Algorithm),
+    Ed25519,
+    Ed448,
+}
+
+/// The content of a BIT STRING without unused bits.
+fn bit_string<'a>(node: &Node, bytes: &'a [u8]) -> Result<&'a [u8]> {
+    return match node.content(bytes) {
+        [0, content @ ..] => Ok(content),
+        _ => Err(Error("The BIT STRING must be a whole number of bytes")),
+    };
+}
+
+impl PublicKey {
+    /// Decode the key of a SubjectPublicKeyInfo.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() != 2 {
+            return Err(Error("SubjectPublicKeyInfo must be a SEQUENCE of two elements"));
+        }
+        let (algorithm, parameters) = spki::algorithm(&node.children[0], bytes)?;
+        let public_key = node.child(1, Tag::BIT_STRING)?;
+
+        return match algorithm.as_str() {
+            "1.2.840.113549.1.1.1" | "1.2.840.113549.1.1.10" => {
+                let rsa_key = public_key.child(0, Tag::SEQUENCE)?;
+                let (_, modulus) = value::integer_magnitude(rsa_key.child(0, Tag::INTEGER)?.content(bytes))?;
+                let (_, exponent) = value::integer_magnitude(rsa_key.child(1, Tag::INTEGER)?.content(bytes))?;
+                let key = RsaPublicKey::new_with_max_size(
+                    BigUint::from_bytes_be(modulus),
+                    BigUint::from_bytes_be(exponent),
+                    MAX_RSA_BITS,
+                );
+                Ok(Self::Rsa(key.map_err(|_| Error("Invalid RSA public key"))?))
+            }
+            "1.2.840.10045.2.1" => {
+                let curve = match parameters {
+                    Some(node) if node.tag == Tag::OID => oid::stringify(node.content(bytes))?,
+                    _ => return Err(Error("Unsupported EC parameters")),
+                };
+                let point = bit_string(public_key, bytes)?;
+                let invalid = Error("Invalid EC public key");
+                match curve.as_str() {
+                    "1.2.840.10045.3.1.7" => p256::ecdsa::VerifyingKey::from_sec1_bytes(point).map(Self::P256).map_err(|_| invalid),
+                    "1.3.132.0.34" => p384::ecdsa::VerifyingKey::from_sec1_bytes(point).map(Self::P384).map_err(|_| invalid),
+                    "1.3.132.0.35" => p521::ecdsa::VerifyingKey::from_sec1_bytes(point).map(Self::P521).map_err(|_| invalid),
+                    _ => Err(Error("Unsupported elliptic curve")),
+                }
+            }
+            "1.3.101.112" => {
+                let point = bit_string(public_key, bytes)?.try_into().map_err(|_| Error("Invalid Ed25519 public key"))?;
+                let key = ed25519_dalek::VerifyingKey::from_bytes(point).map_err(|_| Error("Invalid Ed25519 public key"))?;
+                Ok(Self::Ed25519(key))
+            }
+            "1.3.101.113" => {
+                let point = bit_string(public_key, bytes)?.try_into().map_err(|_| Error("Invalid Ed448 public key"))?;
+                let key = ed448_goldilocks_plus::VerifyingKey::from_bytes(point).map_err(|_| Error("Invalid Ed448 public key"))?;
+                Ok(Self::Ed448(key))
+            }
+            _ => Err(Error("Unsupported public key algorithm")),
+        };
+    }
+}
+
+fn hash_of(node: &Node, bytes: &[u8]) -> Result<hash::Algorithm> {
+    let (oid, _) = spki::algorithm(node, bytes)?;
+    return hash::Algorithm::from_oid(&oid).ok_or(Error("Unsupported hash algorithm"));
+}
+
+/// Decode RSASSA-PSS-params, where every field has a default value.
+fn pss_parameters(node: Option<&Node>, bytes: &[u8]) -> Result<Scheme> {
+    let mut hash = hash::Algorithm::Sha1;
+    let mut mgf_hash = hash::Algorithm::Sha1;
+    let mut salt_len = 20;
+
+    let fields = match node {
+        Some(node) if node.tag == Tag::SEQUENCE => node.children.as_slice(),
+        Some(node) if node.tag == Tag::NULL => &[],
+        None => &[],
+        _ => return Err(Error("RSASSA-PSS-params must be a SEQUENCE")),
+    };
+    for field in fields {
+        let inner = field.children.first().ok_or(Error("Empty RSASSA-PSS parameter"))?;
+        match (field.tag.class, field.tag.number) {
+            (Class::ContextSpecific, 0) => hash = hash_of(inner, bytes)?,
+            (Class::ContextSpecific, 1) => {
+                let (mgf, parameters) = spki::algorithm(inner, bytes)?;
+                if mgf != "1.2.840.113549.1.1.8" {
+                    return Err(Error("Unsupported mask generation function"));
+                }
+                mgf_hash = hash_of(parameters.ok_or(Error("Missing MGF1 hash"))?, bytes)?;
+            }
+            (Class::ContextSpecific, 2) => salt_len = value::unsigned(inner.content(bytes))? as usize,
+            (Class::ContextSpecific, 3) => {
+                if value::unsigned(inner.content(bytes))? != 1 {
+                    return Err(Error("Unsupported trailer field"));
+                }
+            }
+            _ => return Err(Error("Unexpected element in RSASSA-PSS-params")),
+        }
+    }
+
+    if mgf_hash != hash {
+        return Err(Error("MGF1 with a different hash is not supported"));
+    }
+    return Ok(Scheme::Pss(hash, salt_len));
+}
+
+/// The scheme of a signature AlgorithmIdentifier. CMS identifies some
+/// signatures by the key algorithm alone, with the hash given by `digest`.
+fn scheme(node: &Node, bytes: &[u8], digest: Option<hash::Algorithm>) -> Result<Scheme> {
+    let (oid, parameters) = spki::algorithm(node, bytes)?;
+    let digest = || digest.ok_or(Error("The signature algorithm doesn't name a hash"));
+    return match oid.as_str() {
+        "1.2.840.113549.1.1.1" => Ok(Scheme::Pkcs1(digest()?)),
+        "1.2.840.113549.1.1.4" => Ok(Scheme::Pkcs1(hash::Algorithm::Md5)),
+        "1.2.840.113549.1.1.5" => Ok(Scheme::Pkcs1(hash::Algorithm::Sha1)),
+        "1.2.840.113549.1.1.14" => Ok(Scheme::Pkcs1(hash::Algorithm::Sha224)),
+        "1.2.840.113549.1.1.11" => Ok(Scheme::Pkcs1(hash::Algorithm::Sha256)),
+        "1.2.840.113549.1.1.12" => Ok(Scheme::Pkcs1(hash::Algorithm::Sha384)),
+        "1.2.840.113549.1.1.13" => Ok(Scheme::Pkcs1(hash::Algorithm::Sha512)),
+        "1.2.840.113549.1.1.10" => pss_parameters(parameters, bytes),
+        "1.2.840.10045.2.1" => Ok(Scheme::Ecdsa(digest()?)),
+        "1.2.840.10045.4.1" => Ok(Scheme::Ecdsa(hash::Algorithm::Sha1)),
+        "1.2.840.10045.4.3.1" => Ok(Scheme::Ecdsa(hash::Algorithm::Sha224)),
+        "1.2.840.10045.4.3.2" => Ok(Scheme::Ecdsa(hash::Algorithm::Sha256)),
+        "1.2.840.10045.4.3.3" => Ok(Scheme::Ecdsa(hash::Algorithm::Sha384)),
+        "1.2.840.10045.4.3.4" => Ok(Scheme::Ecdsa(hash::Algorithm::Sha512)),
+        "1.3.101.112" => Ok(Scheme::Ed25519),
+        "1.3.101.113" => Ok(Scheme::Ed448),
+        _ => Err(Error("Unsupported signature algorithm")),
+    };
+}
+
+fn pkcs1(hash: hash::Algorithm) -> Pkcs1v15Sign {
+    return match hash {
+        // The md-5 crate doesn't provide the OID needed to build the DigestInfo.
+        hash::Algorithm::Md5 => Pkcs1v15Sign {
+            hash_len: Some(16),
+            prefix: Box::new(*b"\x30\x20\x30\x0c\x06\x08\x2a\x86\x48\x86\xf7\x0d\x02\x05\x05\x00\x04\x10"),
+        },
+        hash::Algorithm::Sha1 => Pkcs1v15Sign::new::<Sha1>(),
+        hash::Algorithm::Sha224 => Pkcs1v15Sign::new::<Sha224>(),
+        hash::Algorithm::Sha256 => Pkcs1v15Sign::new::<Sha256>(),
+        hash::Algorithm::Sha384 => Pkcs1v15Sign::new::<Sha384>(),
+        hash::Algorithm::Sha512 => Pkcs1v15Sign::new::<Sha512>(),
+        hash::Algorithm::Sha3_256 => Pkcs1v15Sign::new::<Sha3_256>(),
+    };
+}
+
+fn pss(hash: hash::Algorithm, salt_len: usize) -> Pss {
+    return match hash {
+        hash::Algorithm::Md5 => Pss::new_with_salt::<md5::Md5>(salt_len),
+        hash::Algorithm::Sha1 => Pss::new_with_salt::<Sha1>(salt_len),
+        hash::Algorithm::Sha224 => Pss::new_with_salt::<Sha224>(salt_len),
+        hash::Algorithm::Sha256 => Pss::new_with_salt::<Sha256>(salt_len),
+        hash::Algorithm::Sha384 => Pss::new_with_salt::<Sha384>(salt_len),
+        hash::Algorithm::Sha512 => Pss::new_with_salt::<Sha512>(salt_len),
+        hash::Algorithm::Sha3_256 => Pss::new_with_salt::<Sha3_256>(salt_len),
+    };
+}
+
+/// Verify a signature over `message` made with the algorithm described by
+/// the AlgorithmIdentifier `algorithm`.
+pub fn verify(
+    key: &PublicKey,
+    algorithm: &Node,
+    bytes: &[u8],
+    digest: Option<hash::Algorithm>,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    return match (key, scheme(algorithm, bytes, digest)?) {
+        (PublicKey::Rsa(key), Scheme::Pkcs1(hash)) => key.verify(pkcs1(hash), &hash.digest(message), signature).map_err(|_| INVALID),
+        (PublicKey::Rsa(key), Scheme::Pss(hash, salt_len)) => {
+            key.verify(pss(hash, salt_len), &hash.digest(message), signature).map_err(|_| INVALID)
+        }
+        (PublicKey::P256(key), Scheme::Ecdsa(hash)) => {
+            let signature = p256::ecdsa::Signature::from_der(signature).map_err(|_| MALFORMED)?;
+            key.verify_prehash(&hash.digest(message), &signature).map_err(|_| INVALID)
+        }
+        (PublicKey::P384(key), Scheme::Ecdsa(hash)) => {
+            let signature = p384::ecdsa::Signature::from_der(signature).map_err(|_| MALFORMED)?;
+            key.verify_prehash(&hash.digest(message), &signature).map_err(|_| INVALID)
+        }
+        (PublicKey::P521(key), Scheme::Ecdsa(hash)) => {
+            let signature = p521::ecdsa::Signature::from_der(signature).map_err(|_| MALFORMED)?;
+            key.verify_prehash(&hash.digest(message), &signature).map_err(|_| INVALID)
+        }
+        (PublicKey::Ed25519(key), Scheme::Ed25519) => {
+            let signature = ed25519_dalek::Signature::from_slice(signature).map_err(|_| MALFORMED)?;
+            key.verify_strict(message, &signature).map_err(|_| INVALID)
+        }
+        (PublicKey::Ed448(key), Scheme::Ed448) => {
+            let signature = ed448_goldilocks_plus::Signature::from_slice(signature).map_err(|_| MALFORMED)?;
+            key.verify_raw(&signature, message).map_err(|_| INVALID)
+        }
+        _ => Err(Error("The key doesn't match the signature algorithm")),
+    };
+}
+
+/// Whether the node looks like a Certificate, CertificateList or
+/// CertificationRequest: the signed data, the AlgorithmIdentifier and the
+/// signature.
+pub fn is_signed(node: &Node) -> bool {
+    let tags: Vec<Tag> = node.children.iter().map(|child| child.tag).collect();
+    return node.tag == Tag::SEQUENCE && tags == [Tag::SEQUENCE, Tag::SEQUENCE, Tag::BIT_STRING];
+}
+
+/// The SubjectPublicKeyInfo of a Certificate or CertificationRequest, the
+/// key a self-signed structure is verified with.
+pub fn own_key(node: &Node) -> Option<&Node> {
+    if let Ok(fields) = x509::Fields::new(node) {
+        return Some(fields.spki);
+    }
+
+    // CertificationRequestInfo: version, subject, subjectPKInfo and attributes.
+    let info = node.children.first().filter(|info| info.tag == Tag::SEQUENCE && info.children.len() == 4)?;
+    info.child(0, Tag::INTEGER).ok()?;
+    if info.children[3].tag != Tag::context_specific(0).into_constructed() {
+        return None;
+    }
+    return info.child(2, Tag::SEQUENCE).ok();
+}
+
+/// Verify the signature of a structure recognized by `is_signed` over the
+/// exact bytes of its signed data.
+pub fn verify_signed(node: &Node, bytes: &[u8], key: &PublicKey) -> Result<()> {
+    if !is_signed(node) {
+        return Err(Error("Expected a SEQUENCE of the signed data, the algorithm and the signature"));
+    }
+    let signature = bit_string(&node.children[2], bytes)?;
+    return verify(key, &node.children[1], bytes, None, node.children[0].raw(bytes), signature);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    fn parse(bytes: &[u8]) -> Vec<Node> {
+        let (nodes, error) = node::parse(bytes);
+        assert!(error.is_none());
+        return nodes;
+    }
+
+    fn self_signed(bytes: &[u8]) -> Result<()> {
+        let nodes = parse(bytes);
+        let key = PublicKey::parse(own_key(&nodes[0]).unwrap(), bytes)?;
+        return verify_signed(&nodes[0], bytes, &key);
+    }
+
+    #[test]
+    fn verify_self_signed() {
+        assert_eq!(self_signed(include_bytes!("../tests/ed25519.der")), Ok(()));
+        assert_eq!(self_signed(include_bytes!("../tests/ed448.der")), Ok(()));
+
+        let mut tampered = include_bytes!("../tests/ed448.der").to_vec();
+        let name = tampered.windows(5).position(|window| window == b"Ed448").unwrap();
+        tampered[name] = b'e';
+        assert_eq!(self_signed(&tampered), Err(INVALID));
+    }
+
+    #[test]
+    fn verify_with_issuer() {
+        let issuer_bytes = include_bytes!("../tests/inter.der");
+        let issuer = parse(issuer_bytes);
+        let key = PublicKey::parse(own_key(&issuer[0]).unwrap(), issuer_bytes).unwrap();
+        let bytes = include_bytes!("../tests/leaf.der");
+        assert_eq!(verify_signed(&parse(bytes)[0], bytes, &key), Ok(()));
+
+        let bytes = include_bytes!("../tests/ed25519.der");
+        assert_eq!(verify_signed(&parse(bytes)[0], bytes, &key), Err(Error("The key doesn't match the signature algorithm")));
+    }
+
+    #[test]
+    fn verify_certification_request() {
+        assert_eq!(self_signed(include_bytes!("../tests/leaf.csr")), Ok(()));
+    }
+}
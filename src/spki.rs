@@ -0,0 +1,248 @@
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use crate::der::Tag;
+use crate::error::{Error, Result};
+use crate::hash;
+use crate::node::Node;
+use crate::{hex, oid, oid_names, value, x509};
+
+/// Details of a SubjectPublicKeyInfo, and the values derived from it to pin
+/// or identify the key.
+#[derive(Clone, Debug)]
+pub struct Spki {
+    pub description: String,
+    /// Decoded parameters and components, as labels and values.
+    pub details: Vec<(&'static str, String)>,
+    /// Base64 of the SHA-256 of the whole SubjectPublicKeyInfo, as used by
+    /// HPKP and most pinning configurations.
+    pub sha256_pin: String,
+    /// Key identifiers of RFC 5280 section 4.2.1.2, methods 1 and 2.
+    pub key_id: Vec<u8>,
+    pub short_key_id: Vec<u8>,
+    /// RFC 7638 thumbprint of the key as a JWK, for the key types JWK supports.
+    pub jwk_thumbprint: Option<String>,
+}
+
+/// Size in bits of the named elliptic curves.
+pub fn curve_bits(curve: &str) -> Option<usize> {
+    return match curve {
+        "1.2.840.10045.3.1.1" => Some(192),
+        "1.3.132.0.33" => Some(224),
+        "1.2.840.10045.3.1.7" | "1.3.132.0.10" | "1.3.36.3.3.2.8.1.1.7" => Some(256),
+        "1.3.36.3.3.2.8.1.1.9" => Some(320),
+        "1.3.132.0.34" | "1.3.36.3.3.2.8.1.1.11" => Some(384),
+        "1.3.36.3.3.2.8.1.1.13" => Some(512),
+        "1.3.132.0.35" => Some(521),
+        _ => None,
+    };
+}
+
+/// Number of significant bits of an unsigned big-endian integer.
+pub fn bit_length(magnitude: &[u8]) -> usize {
+    return match magnitude.iter().position(|&byte| byte != 0) {
+        Some(idx) => ((magnitude.len() - idx) * 8) - (magnitude[idx].leading_zeros() as usize),
+        None => 0,
+    };
+}
+
+/// Returns the OID of an AlgorithmIdentifier and its parameters, if any.
+pub fn algorithm<'a>(node: &'a Node, bytes: &[u8]) -> Result<(String, Option<&'a Node>)> {
+    if node.tag != Tag::SEQUENCE || node.children.is_empty() || 2 < node.children.len() {
+        return Err(Error("AlgorithmIdentifier must be a SEQUENCE of one or two elements"));
+    }
+    let oid = oid::stringify(node.child(0, Tag::OID)?.content(bytes))?;
+    return Ok((oid, node.children.get(1)));
+}
+
+/// Describe an elliptic curve key from its ECParameters.
+pub fn describe_curve(parameters: Option<&Node>, bytes: &[u8]) -> Result<String> {
+    let curve = match parameters {
+        Some(node) if node.tag == Tag::OID => oid::stringify(node.content(bytes))?,
+        _ => return Ok("EC with explicit parameters".to_owned()),
+    };
+    let name = oid_names::name_or_oid(curve.clone());
+    return match curve_bits(&curve) {
+        Some(bits) => Ok(format!("EC {} ({} bits)", name, bits)),
+        None => Ok(format!("EC {}", name)),
+    };
+}
+
+/// Describe the type and size of the key in a SubjectPublicKeyInfo.
+pub fn describe(node: &Node, bytes: &[u8]) -> Result<String> {
+    if node.tag != Tag::SEQUENCE || node.children.len() != 2 {
+        return Err(Error("SubjectPublicKeyInfo must be a SEQUENCE of two elements"));
+    }
+
+    let (algorithm, parameters) = algorithm(&node.children[0], bytes)?;
+    let public_key = node.child(1, Tag::BIT_STRING)?;
+
+    return match algorithm.as_str() {
+        "1.2.840.113549.1.1.1" | "1.2.840.113549.1.1.10" => {
+            let rsa_key = public_key.child(0, Tag::SEQUENCE)?;
+            let modulus = rsa_key.child(0, Tag::INTEGER)?;
+            let (_, magnitude) = value::integer_magnitude(modulus.content(bytes))?;
+            Ok(format!("RSA {} bits", bit_length(magnitude)))
+        }
+        "1.2.840.10045.2.1" => describe_curve(parameters, bytes),
+        "1.2.840.10040.4.1" => {
+            let prime = parameters.ok_or(Error("Missing DSA parameters"))?.child(0, Tag::INTEGER)?;
+            let (_, magnitude) = value::integer_magnitude(prime.content(bytes))?;
+            Ok(format!("DSA {} bits", bit_length(magnitude)))
+        }
+        "1.3.101.110" | "1.3.101.111" | "1.3.101.112" | "1.3.101.113" => {
+            Ok(oid_names::find(&algorithm).map(|entry| entry.name.to_string()).unwrap_or_default())
+        }
+        _ => Ok(oid_names::name_or_oid(algorithm)),
+    };
+}
+
+/// The content of the subjectPublicKey BIT STRING, without the unused bits.
+fn key_bits<'a>(node: &Node, bytes: &'a [u8]) -> Result<&'a [u8]> {
+    return match node.content(bytes) {
+        [0, key @ ..] => Ok(key),
+        _ => Err(Error("The public key must be a whole number of bytes")),
+    };
+}
+
+fn point_format(point: &[u8]) -> &'static str {
+    return match point.first() {
+        Some(0) => "point at infinity",
+        Some(2 | 3) => "compressed",
+        Some(4) => "uncompressed",
+        Some(6 | 7) => "hybrid",
+        _ => "invalid",
+    };
+}
+
+/// The SHA-256 of the canonical JSON of the required members of a JWK.
+fn jwk_thumbprint(members: &[(&str, String)]) -> String {
+    let members: Vec<String> = members.iter().map(|(name, value)| format!("\"{}\":\"{}\"", name, value)).collect();
+    let json = format!("{{{}}}", members.join(","));
+    return URL_SAFE_NO_PAD.encode(hash::Algorithm::Sha256.digest(json.as_bytes()));
+}
+
+impl Spki {
+    /// Decode a SubjectPublicKeyInfo, failing if the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        let description = describe(node, bytes)?;
+        let (algorithm, parameters) = algorithm(&node.children[0], bytes)?;
+        let key = key_bits(&node.children[1], bytes)?;
+
+        let mut details = vec![("Algorithm", oid_names::name_or_oid(algorithm.clone()))];
+        let mut jwk = None;
+        match algorithm.as_str() {
+            "1.2.840.113549.1.1.1" | "1.2.840.113549.1.1.10" => {
+                let rsa_key = node.children[1].child(0, Tag::SEQUENCE)?;
+                let (_, modulus) = value::integer_magnitude(rsa_key.child(0, Tag::INTEGER)?.content(bytes))?;
+                let (_, exponent) = value::integer_magnitude(rsa_key.child(1, Tag::INTEGER)?.content(bytes))?;
+                details.push(("Modulus", format!("{} bits", bit_length(modulus))));
+                details.push(("Exponent", value::describe(Tag::INTEGER, rsa_key.children[1].content(bytes))?));
+                let (n, e) = (URL_SAFE_NO_PAD.encode(modulus), URL_SAFE_NO_PAD.encode(exponent));
+                jwk = Some(jwk_thumbprint(&[("e", e), ("kty", "RSA".to_owned()), ("n", n)]));
+            }
+            "1.2.840.10045.2.1" => {
+                details.push(("Curve", describe_curve(parameters, bytes)?));
+                details.push(("Point format", point_format(key).to_owned()));
+                let curve = parameters.and_then(|node| oid::stringify(node.content(bytes)).ok()).unwrap_or_default();
+                let crv = match curve.as_str() {
+                    "1.2.840.10045.3.1.7" => Some("P-256"),
+                    "1.3.132.0.34" => Some("P-384"),
+                    "1.3.132.0.35" => Some("P-521"),
+                    _ => None,
+                };
+                // JWK only represents uncompressed points.
+                if let (Some(crv), Some(4)) = (crv, key.first()) {
+                    let (x, y) = key[1..].split_at((key.len() - 1) / 2);
+                    let (x, y) = (URL_SAFE_NO_PAD.encode(x), URL_SAFE_NO_PAD.encode(y));
+                    jwk = Some(jwk_thumbprint(&[("crv", crv.to_owned()), ("kty", "EC".to_owned()), ("x", x), ("y", y)]));
+                }
+            }
+            "1.3.101.110" | "1.3.101.111" | "1.3.101.112" | "1.3.101.113" => {
+                details.push(("Raw key", hex::hexlify(key)));
+                let crv = match algorithm.as_str() {
+                    "1.3.101.110" => "X25519",
+                    "1.3.101.111" => "X448",
+                    "1.3.101.112" => "Ed25519",
+                    _ => "Ed448",
+                };
+                let x = URL_SAFE_NO_PAD.encode(key);
+                jwk = Some(jwk_thumbprint(&[("crv", crv.to_owned()), ("kty", "OKP".to_owned()), ("x", x)]));
+            }
+            _ => {}
+        }
+
+        let key_id = hash::Algorithm::Sha1.digest(key);
+        let mut short_key_id = vec![0x40 | (key_id[12] & 0x0F)];
+        short_key_id.extend_from_slice(&key_id[13..]);
+
+        return Ok(Self {
+            description,
+            details,
+            sha256_pin: STANDARD.encode(hash::Algorithm::Sha256.digest(node.raw(bytes))),
+            key_id,
+            short_key_id,
+            jwk_thumbprint: jwk,
+        });
+    }
+
+    /// Name the fields of a SubjectPublicKeyInfo found outside of a certificate.
+    pub fn annotate(node: &mut Node) {
+        if let Some(algorithm) = node.set_child_field(0, "algorithm") {
+            x509::annotate_algorithm(algorithm);
+        }
+        node.set_child_field(1, "subjectPublicKey");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node;
+
+    #[test]
+    fn bit_lengths() {
+        assert_eq!(bit_length(b""), 0);
+        assert_eq!(bit_length(b"\x00\x01"), 1);
+        assert_eq!(bit_length(b"\x80\x00"), 16);
+        assert_eq!(bit_length(b"\x01\x00\x01"), 17);
+    }
+
+    #[test]
+    fn describe_keys() {
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (nodes, _) = node::parse(bytes);
+        let spki = &nodes[0].children[0].children[6];
+        assert_eq!(describe(spki, bytes).unwrap(), "EC secp384r1 (384 bits)");
+
+        let bytes = include_bytes!("../tests/ed25519.der");
+        let (nodes, _) = node::parse(bytes);
+        let spki = &nodes[0].children[0].children[6];
+        assert_eq!(describe(spki, bytes).unwrap(), "Ed25519");
+    }
+
+    #[test]
+    fn fingerprints() {
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (nodes, _) = node::parse(bytes);
+        let spki = Spki::parse(&nodes[0].children[0].children[6], bytes).unwrap();
+        assert_eq!(spki.details[2], ("Point format", "uncompressed".to_owned()));
+        assert_eq!(spki.sha256_pin, "aJiFsHA1Hz0oBUV+shDCHMe+JtYOvyqgMHg+PZSJNrc=");
+        assert_eq!(hex::hexlify(&spki.key_id), "bcff2e116b5db23af70d84be20dd9293a9b4e166");
+        assert_eq!(spki.jwk_thumbprint.as_deref(), Some("2ONwBIUG8cM-6F0VqnVQ7CM54Rwy9VBPpISYFfPZy8U"));
+
+        let bytes = include_bytes!("../tests/ed25519.der");
+        let (nodes, _) = node::parse(bytes);
+        let spki = Spki::parse(&nodes[0].children[0].children[6], bytes).unwrap();
+        assert_eq!(hex::hexlify(&spki.short_key_id), "4f350e1c5a0d3aa1");
+        assert_eq!(spki.jwk_thumbprint.as_deref(), Some("aJbirpFuJr42Aq4Yl9XiHb7Ogt-TvhBt_a4W-tpVXSQ"));
+
+        // The intermediate CA certificate embedded in the SignedData has an RSA key.
+        let bytes = include_bytes!("../tests/signed.p7s");
+        let (nodes, _) = node::parse(bytes);
+        let certificate = &nodes[0].children[1].children[0].children[3].children[0];
+        let spki = Spki::parse(&certificate.children[0].children[6], bytes).unwrap();
+        assert_eq!(spki.details[1], ("Modulus", "2048 bits".to_owned()));
+        assert_eq!(spki.details[2], ("Exponent", "65537".to_owned()));
+        assert_eq!(spki.jwk_thumbprint.as_deref(), Some("bnNDgAaDGz8y2HBNY8xaiUrDId6dyG2EtOoWutrcX40"));
+    }
+}
@@ -0,0 +1,462 @@
+use chrono::{DateTime, Utc};
+use crate::cms;
+use crate::der::{Class, Tag};
+use crate::error::{Error, Result};
+use crate::hash;
+use crate::node::Node;
+use crate::{extension, hex, oid, oid_names, spki, value, x509};
+
+const STATUSES: &[&str] =
+    &["granted", "grantedWithMods", "rejection", "waiting", "revocationWarning", "revocationNotification"];
+
+const FAILURES: &[&str] = &[
+    "badAlg",
+    "unknown",
+    "badRequest",
+    "unknown",
+    "unknown",
+    "badDataFormat",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "timeNotAvailable",
+    "unacceptedPolicy",
+    "unacceptedExtension",
+    "addInfoNotAvailable",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "unknown",
+    "systemFailure",
+];
+
+/// The hash of the time-stamped data.
+#[derive(Clone, Debug)]
+pub struct MessageImprint {
+    pub hash_algorithm: String,
+    pub hashed_message: Vec<u8>,
+    /// Result of the last comparison with the hash of a file.
+    pub check: Option<Result<()>>,
+}
+
+/// Summary of a RFC 3161 TimeStampReq.
+#[derive(Clone, Debug)]
+pub struct TimeStampReq {
+    pub version: u64,
+    pub message_imprint: MessageImprint,
+    pub policy: Option<String>,
+    pub nonce: Option<String>,
+    pub cert_req: bool,
+    /// Names of the extensions, with their criticality.
+    pub extensions: Vec<String>,
+}
+
+/// Summary of a RFC 3161 TimeStampResp, whose token is described as CMS.
+#[derive(Clone, Debug)]
+pub struct TimeStampResp {
+    pub status: &'static str,
+    pub status_string: Vec<String>,
+    pub failure_info: Vec<&'static str>,
+    pub token: bool,
+}
+
+/// Summary of a RFC 3161 TSTInfo.
+#[derive(Clone, Debug)]
+pub struct TstInfo {
+    pub version: u64,
+    pub policy: String,
+    pub message_imprint: MessageImprint,
+    pub serial: Vec<u8>,
+    pub gen_time: DateTime<Utc>,
+    pub accuracy: Option<String>,
+    pub ordering: bool,
+    pub nonce: Option<String>,
+    pub tsa: Option<String>,
+    /// Names of the extensions, with their criticality.
+    pub extensions: Vec<String>,
+}
+
+fn oid_name(node: &Node, bytes: &[u8]) -> Result<String> {
+    let id = oid::stringify(node.content(bytes))?;
+    return Ok(oid_names::name_or_oid(id));
+}
+
+/// Names of the Extensions of a SEQUENCE, with their criticality.
+fn extensions(node: &Node, bytes: &[u8]) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for extension in node.children.iter() {
+        let name = oid_name(extension.child(0, Tag::OID)?, bytes)?;
+        let critical = extension.children.len() == 3 && value::boolean(extension.children[1].content(bytes)) == Ok(true);
+        names.push(if critical { format!("{}, critical", name) } else { name });
+    }
+    return Ok(names);
+}
+
+fn annotate_extensions(node: &mut Node, bytes: &[u8]) {
+    node.set_field("extensions");
+    for extension in node.children.iter_mut() {
+        extension::annotate(extension, bytes);
+    }
+}
+
+/// Describe an Accuracy, as its total in seconds.
+fn accuracy(node: &Node, bytes: &[u8]) -> Result<String> {
+    let mut micros: u64 = 0;
+    for child in node.children.iter() {
+        let (unit, max) = match (child.tag.class, child.tag.number) {
+            (Class::Universal, _) if child.tag == Tag::INTEGER => (1_000_000, u64::MAX),
+            (Class::ContextSpecific, 0) => (1_000, 999),
+            (Class::ContextSpecific, 1) => (1, 999),
+            _ => return Err(Error("Unexpected element in Accuracy")),
+        };
+        let count = value::unsigned(child.content(bytes))?;
+        if count == 0 || max < count {
+            return Err(Error("Accuracy out of range"));
+        }
+        let total = count.checked_mul(unit).and_then(|count| micros.checked_add(count));
+        micros = total.ok_or(Error("Accuracy out of range"))?;
+    }
+    let (seconds, fraction) = (micros / 1_000_000, micros % 1_000_000);
+    if fraction == 0 {
+        return Ok(format!("±{} s", seconds));
+    }
+    return Ok(format!("±{}.{} s", seconds, format!("{:06}", fraction).trim_end_matches('0')));
+}
+
+impl MessageImprint {
+    fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() != 2 {
+            return Err(Error("MessageImprint must be a SEQUENCE of two elements"));
+        }
+        let (hash_algorithm, _) = spki::algorithm(node.child(0, Tag::SEQUENCE)?, bytes)?;
+        return Ok(Self {
+            hash_algorithm: oid_names::name_or_oid(hash_algorithm),
+            hashed_message: node.child(1, Tag::OCTET_STRING)?.content(bytes).to_vec(),
+            check: None,
+        });
+    }
+
+    fn annotate(node: &mut Node, bytes: &[u8]) {
+        node.set_field("messageImprint");
+        if let Some(algorithm) = node.set_child_field(0, "hashAlgorithm") {
+            x509::annotate_algorithm(algorithm);
+        }
+        if let Some(hashed_message) = node.set_child_field(1, "hashedMessage") {
+            hashed_message.note = Some(hex::colon_separated(hashed_message.content(bytes)));
+        }
+    }
+
+    /// Compare the MessageImprint `node` with the hash of `content`.
+    fn check(&mut self, node: &Node, bytes: &[u8], content: &[u8]) -> Result<()> {
+        let (algorithm, _) = spki::algorithm(node.child(0, Tag::SEQUENCE)?, bytes)?;
+        let algorithm = hash::Algorithm::from_oid(&algorithm).ok_or(Error("Unsupported hash algorithm"))?;
+        self.check = Some(match algorithm.digest(content) == self.hashed_message {
+            true => Ok(()),
+            false => Err(Error("The messageImprint doesn't match the hash of the file")),
+        });
+        return Ok(());
+    }
+}
+
+impl TimeStampReq {
+    /// Decode a TimeStampReq, failing if the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() < 2 {
+            return Err(Error("TimeStampReq must be a SEQUENCE of at least two elements"));
+        }
+        let version = value::unsigned(node.child(0, Tag::INTEGER)?.content(bytes))?;
+        if version != 1 {
+            return Err(Error("Unsupported TimeStampReq version"));
+        }
+
+        let mut request = Self {
+            version,
+            message_imprint: MessageImprint::parse(&node.children[1], bytes)?,
+            policy: None,
+            nonce: None,
+            cert_req: false,
+            extensions: Vec::new(),
+        };
+        let mut children = node.children[2..].iter().peekable();
+        if let Some(policy) = children.next_if(|child| child.tag == Tag::OID) {
+            request.policy = Some(oid_name(policy, bytes)?);
+        }
+        if let Some(nonce) = children.next_if(|child| child.tag == Tag::INTEGER) {
+            request.nonce = Some(value::integer(nonce.content(bytes))?);
+        }
+        if let Some(cert_req) = children.next_if(|child| child.tag == Tag::BOOLEAN) {
+            request.cert_req = value::boolean(cert_req.content(bytes))?;
+        }
+        if let Some(extensions) = children.next_if(|child| child.tag == Tag::context_specific(0).into_constructed()) {
+            request.extensions = self::extensions(extensions, bytes)?;
+        }
+        if children.next().is_some() {
+            return Err(Error("Unexpected element in TimeStampReq"));
+        }
+        return Ok(request);
+    }
+
+    /// Name every field of a node previously accepted by `parse`.
+    pub fn annotate(node: &mut Node, bytes: &[u8]) {
+        node.set_child_field(0, "version");
+        if let Some(message_imprint) = node.children.get_mut(1) {
+            MessageImprint::annotate(message_imprint, bytes);
+        }
+        for child in node.children.iter_mut().skip(2) {
+            match child.tag {
+                Tag::OID => child.set_field("reqPolicy"),
+                Tag::INTEGER => child.set_field("nonce"),
+                Tag::BOOLEAN => child.set_field("certReq"),
+                _ => annotate_extensions(child, bytes),
+            }
+        }
+    }
+
+    /// Compare the messageImprint with the hash of `content`.
+    pub fn check(&mut self, node: &Node, bytes: &[u8], content: &[u8]) -> Result<()> {
+        return self.message_imprint.check(node.child(1, Tag::SEQUENCE)?, bytes, content);
+    }
+}
+
+impl TimeStampResp {
+    /// Decode a TimeStampResp, failing if the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.is_empty() || 2 < node.children.len() {
+            return Err(Error("TimeStampResp must be a SEQUENCE of one or two elements"));
+        }
+        let status_info = node.child(0, Tag::SEQUENCE)?;
+        let status = value::unsigned(status_info.child(0, Tag::INTEGER)?.content(bytes))? as usize;
+        let status = STATUSES.get(status).copied().ok_or(Error("Invalid PKIStatus"))?;
+
+        let mut response = Self { status, status_string: Vec::new(), failure_info: Vec::new(), token: false };
+        let mut children = status_info.children[1..].iter().peekable();
+        if let Some(free_text) = children.next_if(|child| child.tag == Tag::SEQUENCE) {
+            for text in free_text.children.iter() {
+                if text.tag != Tag::UTF8_STRING {
+                    return Err(Error("PKIFreeText must hold UTF8String"));
+                }
+                response.status_string.push(value::string(text.tag, text.content(bytes))?);
+            }
+        }
+        if let Some(failure_info) = children.next_if(|child| child.tag == Tag::BIT_STRING) {
+            response.failure_info = extension::named_bits(failure_info.content(bytes), FAILURES)?;
+        }
+        if children.next().is_some() {
+            return Err(Error("Unexpected element in PKIStatusInfo"));
+        }
+
+        // The token is present exactly when the request was granted.
+        let granted = matches!(status, "granted" | "grantedWithMods");
+        if let Some(token) = node.children.get(1) {
+            if cms::content_type(token, bytes)? != cms::SIGNED_DATA {
+                return Err(Error("The timeStampToken must be a SignedData"));
+            }
+            response.token = true;
+        }
+        if response.token != granted {
+            return Err(Error("The timeStampToken must be present when granted"));
+        }
+        return Ok(response);
+    }
+
+    /// Name every field of a node previously accepted by `parse`. The token
+    /// is recognized and annotated as CMS.
+    pub fn annotate(node: &mut Node) {
+        node.set_child_field(1, "timeStampToken");
+        let status_info = match node.set_child_field(0, "status") {
+            Some(status_info) => status_info,
+            None => return,
+        };
+        status_info.set_child_field(0, "status");
+        for child in status_info.children.iter_mut().skip(1) {
+            match child.tag {
+                Tag::SEQUENCE => child.set_field("statusString"),
+                _ => child.set_field("failInfo"),
+            }
+        }
+    }
+}
+
+impl TstInfo {
+    /// Decode a TSTInfo, failing if the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() < 5 {
+            return Err(Error("TSTInfo must be a SEQUENCE of at least five elements"));
+        }
+        let version = value::unsigned(node.child(0, Tag::INTEGER)?.content(bytes))?;
+        if version != 1 {
+            return Err(Error("Unsupported TSTInfo version"));
+        }
+        let (_, serial) = value::integer_magnitude(node.child(3, Tag::INTEGER)?.content(bytes))?;
+        let gen_time = node.child(4, Tag::GENERALIZED_TIME)?;
+
+        let mut info = Self {
+            version,
+            policy: oid_name(node.child(1, Tag::OID)?, bytes)?,
+            message_imprint: MessageImprint::parse(&node.children[2], bytes)?,
+            serial: serial.to_vec(),
+            gen_time: value::time(gen_time.tag, gen_time.content(bytes))?,
+            accuracy: None,
+            ordering: false,
+            nonce: None,
+            tsa: None,
+            extensions: Vec::new(),
+        };
+        let mut children = node.children[5..].iter().peekable();
+        if let Some(accuracy) = children.next_if(|child| child.tag == Tag::SEQUENCE) {
+            info.accuracy = Some(self::accuracy(accuracy, bytes)?);
+        }
+        if let Some(ordering) = children.next_if(|child| child.tag == Tag::BOOLEAN) {
+            info.ordering = value::boolean(ordering.content(bytes))?;
+        }
+        if let Some(nonce) = children.next_if(|child| child.tag == Tag::INTEGER) {
+            info.nonce = Some(value::integer(nonce.content(bytes))?);
+        }
+        if let Some(tsa) = children.next_if(|child| child.tag == Tag::context_specific(0).into_constructed()) {
+            let mut general_name = tsa.children.first().ok_or(Error("Missing tsa"))?.clone();
+            info.tsa = Some(extension::general_name(&mut general_name, bytes)?);
+        }
+        if let Some(extensions) = children.next_if(|child| child.tag == Tag::context_specific(1).into_constructed()) {
+            info.extensions = self::extensions(extensions, bytes)?;
+        }
+        if children.next().is_some() {
+            return Err(Error("Unexpected element in TSTInfo"));
+        }
+        return Ok(info);
+    }
+
+    /// Name every field of a node previously accepted by `parse`.
+    pub fn annotate(node: &mut Node, bytes: &[u8]) {
+        node.set_field("TSTInfo");
+        node.set_child_field(0, "version");
+        node.set_child_field(1, "policy");
+        if let Some(message_imprint) = node.children.get_mut(2) {
+            MessageImprint::annotate(message_imprint, bytes);
+        }
+        node.set_child_field(3, "serialNumber");
+        node.set_child_field(4, "genTime");
+        for child in node.children.iter_mut().skip(5) {
+            match (child.tag.class, child.tag.number) {
+                (Class::Universal, _) if child.tag == Tag::SEQUENCE => {
+                    child.set_field("accuracy");
+                    child.note = accuracy(child, bytes).ok();
+                    for unit in child.children.iter_mut() {
+                        unit.set_field(match unit.tag.class {
+                            Class::ContextSpecific if unit.tag.number == 0 => "millis",
+                            Class::ContextSpecific => "micros",
+                            _ => "seconds",
+                        });
+                    }
+                }
+                (Class::Universal, _) if child.tag == Tag::BOOLEAN => child.set_field("ordering"),
+                (Class::Universal, _) => child.set_field("nonce"),
+                (Class::ContextSpecific, 0) => {
+                    child.set_field("tsa");
+                    if let Some(general_name) = child.children.first_mut() {
+                        let _ = extension::general_name(general_name, bytes);
+                    }
+                }
+                _ => annotate_extensions(child, bytes),
+            }
+        }
+    }
+
+    /// Compare the messageImprint with the hash of `content`.
+    pub fn check(&mut self, node: &Node, bytes: &[u8], content: &[u8]) -> Result<()> {
+        return self.message_imprint.check(node.child(2, Tag::SEQUENCE)?, bytes, content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+    use crate::node;
+
+    /// The TSTInfo encapsulated in the token of a TimeStampResp.
+    fn tst_info(nodes: &[Node]) -> &Node {
+        let signed_data = &nodes[0].children[1].children[1].children[0];
+        return &signed_data.children[2].children[1].children[0].children[0];
+    }
+
+    #[test]
+    fn parse_and_check_request() {
+        let bytes = include_bytes!("../tests/timestamp.tsq");
+        let (mut nodes, _) = node::parse(bytes);
+        let mut request = TimeStampReq::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(request.message_imprint.hash_algorithm, "sha256");
+        assert!(request.policy.is_none());
+        assert!(request.nonce.is_some());
+        assert!(request.cert_req);
+        assert!(TstInfo::parse(&nodes[0], bytes).is_err());
+        assert!(TimeStampResp::parse(&nodes[0], bytes).is_err());
+
+        request.check(&nodes[0], bytes, include_bytes!("../tests/timestamp.txt")).unwrap();
+        assert_eq!(request.message_imprint.check, Some(Ok(())));
+        request.check(&nodes[0], bytes, b"Hello, timestamp?\n").unwrap();
+        assert!(request.message_imprint.check.as_ref().is_some_and(|check| check.is_err()));
+
+        TimeStampReq::annotate(&mut nodes[0], bytes);
+        assert_eq!(nodes[0].children[1].children[1].field.as_deref(), Some("hashedMessage"));
+        assert_eq!(nodes[0].children[3].field.as_deref(), Some("certReq"));
+    }
+
+    #[test]
+    fn parse_response_and_token() {
+        let bytes = include_bytes!("../tests/timestamp.tsr");
+        let (mut nodes, _) = node::parse(bytes);
+        let response = TimeStampResp::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(response.status, "granted");
+        assert!(response.token);
+        assert!(response.failure_info.is_empty());
+
+        let mut info = TstInfo::parse(tst_info(&nodes), bytes).unwrap();
+        assert_eq!(info.policy, "1.3.6.1.4.1.99999.1.1");
+        assert_eq!(hex::hexlify(&info.serial), "2b");
+        assert_eq!(info.gen_time.timestamp_subsec_millis(), 836);
+        assert_eq!(info.accuracy.as_deref(), Some("±1.5001 s"));
+        assert!(info.ordering);
+        assert_eq!(info.nonce.as_deref(), Some("1316374658096631188"));
+        assert_eq!(info.tsa.as_deref(), Some("CN=Explo-DER Test TSA,O=Explo-DER Test,C=US"));
+        info.check(tst_info(&nodes), bytes, include_bytes!("../tests/timestamp.txt")).unwrap();
+        assert_eq!(info.message_imprint.check, Some(Ok(())));
+
+        TimeStampResp::annotate(&mut nodes[0]);
+        assert_eq!(nodes[0].children[1].field.as_deref(), Some("timeStampToken"));
+
+        let document = Document::from_bytes("timestamp.tsr".to_owned(), bytes.to_vec());
+        assert_eq!(tst_info(&document.nodes).children[4].field.as_deref(), Some("genTime"));
+    }
+
+    #[test]
+    fn reject_huge_accuracy() {
+        // Accuracy { seconds 2^64 - 1, millis 1 }
+        let bytes = b"\x30\x0E\x02\x09\x00\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x80\x01\x01";
+        let (nodes, _) = node::parse(bytes);
+        assert_eq!(accuracy(&nodes[0], bytes), Err(Error("Accuracy out of range")));
+
+        // Accuracy { seconds 2, millis 1 }
+        let bytes = b"\x30\x06\x02\x01\x02\x80\x01\x01";
+        let (nodes, _) = node::parse(bytes);
+        assert_eq!(accuracy(&nodes[0], bytes).as_deref(), Ok("±2.001 s"));
+    }
+
+    #[test]
+    fn parse_rejection() {
+        let bytes = include_bytes!("../tests/timestamp-rejected.tsr");
+        let (nodes, _) = node::parse(bytes);
+        let response = TimeStampResp::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(response.status, "rejection");
+        assert_eq!(response.status_string, vec!["Error during serial number generation."]);
+        assert_eq!(response.failure_info, vec!["addInfoNotAvailable"]);
+        assert!(!response.token);
+    }
+}
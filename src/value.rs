@@ -0,0 +1,318 @@
+use chrono::{offset::LocalResult, DateTime, TimeZone, Utc};
+use crate::der::{self, Class, Tag};
+use crate::error::{Error, Result};
+use crate::{hex, oid, oid_names};
+
+/// Decode a DER BOOLEAN.
+pub fn boolean(content: &[u8]) -> Result<bool> {
+    return match content {
+        [0] => Ok(false),
+        [255] => Ok(true),
+        _ => Err(Error("Invalid boolean")),
+    };
+}
+
+/// Decode a DER INTEGER as a decimal string if it fits in 128 bits and as an
+/// hexadecimal string otherwise.
+pub fn integer(content: &[u8]) -> Result<String> {
+    let (negative, magnitude) = integer_magnitude(content)?;
+    if content.len() <= 16 {
+        let mut bytes = if negative { [0xFF; 16] } else { [0; 16] };
+        bytes[16 - content.len()..].copy_from_slice(content);
+        return Ok(format!("{}", i128::from_be_bytes(bytes)));
+    }
+
+    let sign = if negative { "-" } else { "" };
+    return Ok(format!("{}0x{}", sign, hex::hexlify(magnitude)));
+}
+
+/// Returns whether a DER INTEGER is negative and its big-endian bytes without
+/// the sign byte. For negative numbers, the bytes are the two's complement.
+pub fn integer_magnitude(content: &[u8]) -> Result<(bool, &[u8])> {
+    return match content {
+        [] => Err(Error("Integer cannot have the length zero")),
+        [0x00, next, ..] if (next & 0x80) == 0 => Err(Error("Not the canonical encoding.")),
+        [0xFF, next, ..] if (next & 0x80) != 0 => Err(Error("Not the canonical encoding.")),
+        [0x00, rest @ ..] if !rest.is_empty() => Ok((false, rest)),
+        [first, ..] => Ok(((first & 0x80) != 0, content)),
+    };
+}
+
+/// Decode a non-negative DER INTEGER that fits in 64 bits.
+pub fn unsigned(content: &[u8]) -> Result<u64> {
+    let (negative, magnitude) = integer_magnitude(content)?;
+    if negative {
+        return Err(Error("Integer must not be negative"));
+    }
+    if 8 < magnitude.len() {
+        return Err(Error("Integer is too large"));
+    }
+    return Ok(magnitude.iter().fold(0, |value, &byte| (value << 8) | u64::from(byte)));
+}
+
+/// Decode a BIT STRING, returning the number of unused bits and the bytes.
+pub fn bit_string(content: &[u8]) -> Result<(u8, &[u8])> {
+    return match content {
+        [] => Err(Error("BitString cannot have the length zero")),
+        [0] => Ok((0, &[])),
+        [unused, ..] if 7 < *unused => Err(Error("Invalid number of unused bits")),
+        [_] => Err(Error("Unused bits in an empty BitString")),
+        [unused, bytes @ ..] => Ok((*unused, bytes)),
+    };
+}
+
+pub fn printable_string(content: &[u8]) -> Result<String> {
+    let mut builder = String::with_capacity(content.len());
+    for &byte in content {
+        match byte {
+            b'A'..=b'Z' => builder.push(byte as char),
+            b'a'..=b'z' => builder.push(byte as char),
+            b'0'..=b'9' => builder.push(byte as char),
+            b' ' | b'\'' | b'(' | b')' | b'+' | b',' | b'-' | b'.' | b'/' | b':' | b'=' | b'?' => builder.push(byte as char),
+            _ => return Err(Error("Invalid PrintableString")),
+        }
+    }
+    return Ok(builder);
+}
+
+pub fn utf8_string(content: &[u8]) -> Result<String> {
+    let value = std::str::from_utf8(content).map_err(|_| Error("Invalid UTF8 string"))?;
+    return Ok(value.to_owned());
+}
+
+pub fn ascii_string(content: &[u8]) -> Result<String> {
+    if !content.is_ascii() {
+        return Err(Error("Invalid IA5String"));
+    }
+    return Ok(content.iter().map(|&byte| byte as char).collect());
+}
+
+/// T61String is mostly used to store Latin-1 in practice.
+pub fn latin1_string(content: &[u8]) -> String {
+    return content.iter().map(|&byte| byte as char).collect();
+}
+
+pub fn bmp_string(content: &[u8]) -> Result<String> {
+    if (content.len() % 2) != 0 {
+        return Err(Error("Invalid BMPString"));
+    }
+    let codepoints = content
+        .chunks_exact(2)
+        .map(|bytes| ((bytes[0] as u16) << 8) | bytes[1] as u16)
+        .collect::<Vec<u16>>();
+    return String::from_utf16(codepoints.as_slice()).map_err(|_| Error("Invalid BMPString"));
+}
+
+pub fn universal_string(content: &[u8]) -> Result<String> {
+    if (content.len() % 4) != 0 {
+        return Err(Error("Invalid UniversalString"));
+    }
+    return content
+        .chunks_exact(4)
+        .map(|bytes| char::from_u32(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])))
+        .collect::<Option<String>>()
+        .ok_or(Error("Invalid UniversalString"));
+}
+
+/// Decode any of the string types.
+pub fn string(tag: Tag, content: &[u8]) -> Result<String> {
+    return match tag {
+        Tag::PRINTABLE_STRING => printable_string(content),
+        Tag::UTF8_STRING => utf8_string(content),
+        Tag::IA5_STRING | Tag::VISIBLE_STRING | Tag::NUMERIC_STRING => ascii_string(content),
+        Tag::T61_STRING | Tag::VIDEOTEX_STRING | Tag::GRAPHIC_STRING | Tag::GENERAL_STRING => Ok(latin1_string(content)),
+        Tag::BMP_STRING => bmp_string(content),
+        Tag::UNIVERSAL_STRING => universal_string(content),
+        _ => Err(Error("Not a string type")),
+    };
+}
+
+pub fn is_string(tag: Tag) -> bool {
+    return string(tag, b"").is_ok();
+}
+
+/// Decode a UTCTime or GeneralizedTime.
+pub fn time(tag: Tag, content: &[u8]) -> Result<DateTime<Utc>> {
+    fn read_digit(inner: &mut der::Reader) -> Result<u32> {
+        let byte = inner.read_byte()?;
+        return match byte {
+            b'0'..=b'9' => Ok(u32::from(byte - b'0')),
+            _ => Err(Error("Invalid digit found")),
+        };
+    }
+
+    fn read_two_digits(inner: &mut der::Reader, min: u32, max: u32) -> Result<u32> {
+        let hi = read_digit(inner)?;
+        let lo = read_digit(inner)?;
+        let value = (hi * 10) + lo;
+        if value < min || value > max {
+            return Err(Error("Digit outside expected range"));
+        }
+        return Ok(value);
+    }
+
+    fn get_days_in_month(year: i32, month: u32) -> Result<u32> {
+        let next_month = if month == 12 {
+            chrono::NaiveDate::from_ymd_opt(year + 1, 1, 1)
+        } else {
+            chrono::NaiveDate::from_ymd_opt(year, month + 1, 1)
+        }.ok_or(Error("Can't initialize a 'chrono::NativeDate'"))?;
+
+        let duration_since = chrono::NaiveDate::from_ymd_opt(year, month, 1)
+            .ok_or(Error("Invalid year or month"))?;
+        let number_of_days = next_month
+            .signed_duration_since(duration_since)
+            .num_days();
+        assert!(number_of_days <= (u32::MAX as i64));
+        return Ok(number_of_days as u32);
+    }
+
+    let mut reader = der::Reader::new(content);
+
+    let (year_hi, year_lo) = if tag == Tag::UTC_TIME {
+        let lo = read_two_digits(&mut reader, 0, 99)?;
+        let hi = if lo >= 50 { 19 } else { 20 };
+        (hi, lo)
+    } else if tag == Tag::GENERALIZED_TIME {
+        let hi = read_two_digits(&mut reader, 0, 99)?;
+        let lo = read_two_digits(&mut reader, 0, 99)?;
+        (hi, lo)
+    } else {
+        return Err(Error("Not a time type"));
+    };
+
+    let year = {
+        let y = (year_hi * 100) + year_lo;
+        assert!(y <= (i32::MAX as u32));
+        y as i32
+    };
+
+    let month = read_two_digits(&mut reader, 1, 12)?;
+    let days_in_month = get_days_in_month(year, month)?;
+    let day_of_month = read_two_digits(&mut reader, 1, days_in_month)?;
+    let hours = read_two_digits(&mut reader, 0, 23)?;
+    let minutes = read_two_digits(&mut reader, 0, 59)?;
+    let seconds = read_two_digits(&mut reader, 0, 59)?;
+
+    // DER allows fractions of seconds in GeneralizedTime, without trailing zeros.
+    let mut nanoseconds = 0;
+    let mut byte = reader.read_byte()?;
+    if tag == Tag::GENERALIZED_TIME && byte == b'.' {
+        let mut digits = 0;
+        let mut last = 0;
+        byte = reader.read_byte()?;
+        while byte.is_ascii_digit() {
+            digits += 1;
+            last = byte - b'0';
+            if digits <= 9 {
+                nanoseconds += u32::from(last) * 10u32.pow(9 - digits);
+            }
+            byte = reader.read_byte()?;
+        }
+        if digits == 0 || last == 0 {
+            return Err(Error("Invalid fraction of a second"));
+        }
+    }
+
+    if byte != b'Z' {
+        return Err(Error("Invalid or unsupported timezone"));
+    }
+
+    if !reader.at_end() {
+        return Err(Error("Unexpected bytes after the timezone"));
+    }
+
+    return match Utc.with_ymd_and_hms(year, month, day_of_month, hours, minutes, seconds) {
+        LocalResult::None => Err(Error("Can't initialize a Utc time")),
+        LocalResult::Single(dt) => Ok(dt + chrono::Duration::nanoseconds(i64::from(nanoseconds))),
+        LocalResult::Ambiguous(_dt1, _dt2) => Err(Error("Ambiguous time")),
+    };
+}
+
+/// Decode an OBJECT IDENTIFIER as its dotted form followed by its name, if known.
+pub fn oid_with_name(content: &[u8]) -> Result<String> {
+    let oid = oid::stringify(content)?;
+    return Ok(match oid_names::find(&oid) {
+        Some(entry) => format!("{} {}", oid, entry.name),
+        None => oid,
+    });
+}
+
+/// Hexadecimal representation of some bytes, truncated to `limit` bytes.
+pub fn truncated_hex(content: &[u8], limit: usize) -> String {
+    if content.len() <= limit {
+        return hex::hexlify(content);
+    }
+    return format!("{}... ({} bytes)", hex::hexlify(&content[..limit]), content.len());
+}
+
+/// Human readable representation of the content of a primitive element.
+pub fn describe(tag: Tag, content: &[u8]) -> Result<String> {
+    if tag.class != Class::Universal {
+        return Ok(truncated_hex(content, 32));
+    }
+
+    return match tag {
+        Tag::BOOLEAN => boolean(content).map(|value| value.to_string()),
+        Tag::INTEGER | Tag::ENUMERATED => integer(content),
+        Tag::BIT_STRING => {
+            let (unused, bytes) = bit_string(content)?;
+            if unused == 0 {
+                Ok(truncated_hex(bytes, 32))
+            } else {
+                Ok(format!("{} ({} unused bits)", truncated_hex(bytes, 32), unused))
+            }
+        }
+        Tag::NULL if content.is_empty() => Ok(String::new()),
+        Tag::NULL => Err(Error("Null must be empty")),
+        Tag::OID => oid_with_name(content),
+        Tag::UTC_TIME | Tag::GENERALIZED_TIME => time(tag, content).map(|time| time.to_string()),
+        tag if is_string(tag) => string(tag, content).map(|value| format!("'{}'", value)),
+        _ => Ok(truncated_hex(content, 32)),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_integers() {
+        assert_eq!(integer(b"\x00").unwrap(), "0");
+        assert_eq!(integer(b"\x7F").unwrap(), "127");
+        assert_eq!(integer(b"\x00\x80").unwrap(), "128");
+        assert_eq!(integer(b"\xFF").unwrap(), "-1");
+        assert_eq!(integer(b"\xFF\x7F").unwrap(), "-129");
+        assert_eq!(
+            integer(b"\x00\x80\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01").unwrap(),
+            "0x80000000000000000000000000000001"
+        );
+        assert!(integer(b"").is_err());
+        assert!(integer(b"\x00\x01").is_err());
+        assert!(integer(b"\xFF\x80").is_err());
+        assert_eq!(unsigned(b"\x00\xFF").unwrap(), 255);
+        assert!(unsigned(b"\xFF").is_err());
+        assert!(unsigned(b"\x01\x00\x00\x00\x00\x00\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn decode_strings() {
+        assert!(boolean(b"\xFF").unwrap());
+        assert_eq!(printable_string(b"Acme Ltd.").unwrap(), "Acme Ltd.");
+        assert!(printable_string(b"a@b").is_err());
+        assert_eq!(bmp_string(b"\x00h\x00i").unwrap(), "hi");
+        assert_eq!(universal_string(b"\x00\x00\x00h").unwrap(), "h");
+        assert_eq!(string(Tag::IA5_STRING, b"a@b").unwrap(), "a@b");
+    }
+
+    #[test]
+    fn decode_times() {
+        assert_eq!(time(Tag::UTC_TIME, b"491231235959Z").unwrap().to_string(), "2049-12-31 23:59:59 UTC");
+        assert_eq!(time(Tag::UTC_TIME, b"500101000000Z").unwrap().to_string(), "1950-01-01 00:00:00 UTC");
+        assert_eq!(time(Tag::GENERALIZED_TIME, b"20240229120000Z").unwrap().to_string(), "2024-02-29 12:00:00 UTC");
+        assert!(time(Tag::GENERALIZED_TIME, b"20230229120000Z").is_err());
+        assert_eq!(time(Tag::GENERALIZED_TIME, b"20261018120000.25Z").unwrap().to_string(), "2026-10-18 12:00:00.250 UTC");
+        assert!(time(Tag::GENERALIZED_TIME, b"20261018120000.250Z").is_err());
+        assert!(time(Tag::UTC_TIME, b"261018120000.25Z").is_err());
+    }
+}
@@ -0,0 +1,302 @@
+use chrono::{DateTime, Utc};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use crate::der::{Class, Tag};
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::{extension, name, oid, oid_names, spki, value};
+
+/// Summary of a RFC 5280 Certificate.
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    pub version: u64,
+    pub serial: Vec<u8>,
+    pub signature_algorithm: String,
+    pub issuer: String,
+    pub subject: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    pub public_key: String,
+    pub sha1: Vec<u8>,
+    pub sha256: Vec<u8>,
+}
+
+/// Index of every field in a TBSCertificate.
+struct Layout {
+    version: Option<usize>,
+    serial: usize,
+    signature: usize,
+    issuer: usize,
+    validity: usize,
+    subject: usize,
+    spki: usize,
+    issuer_unique_id: Option<usize>,
+    subject_unique_id: Option<usize>,
+    extensions: Option<usize>,
+}
+
+fn layout(tbs: &Node) -> Result<Layout> {
+    if tbs.tag != Tag::SEQUENCE {
+        return Err(Error("TBSCertificate must be a SEQUENCE"));
+    }
+
+    let has_version = tbs.children.first().map(|node| node.tag) == Some(Tag::context_specific(0).into_constructed());
+    let first = if has_version { 1 } else { 0 };
+    let expected = [Tag::INTEGER, Tag::SEQUENCE, Tag::SEQUENCE, Tag::SEQUENCE, Tag::SEQUENCE, Tag::SEQUENCE];
+    for (idx, tag) in expected.iter().enumerate() {
+        tbs.child(first + idx, *tag)?;
+    }
+
+    let mut layout = Layout {
+        version: if has_version { Some(0) } else { None },
+        serial: first,
+        signature: first + 1,
+        issuer: first + 2,
+        validity: first + 3,
+        subject: first + 4,
+        spki: first + 5,
+        issuer_unique_id: None,
+        subject_unique_id: None,
+        extensions: None,
+    };
+
+    // The optional fields must appear in order and each at most once.
+    let mut next_number = 1;
+    for (idx, node) in tbs.children.iter().enumerate().skip(first + 6) {
+        if node.tag.class != Class::ContextSpecific || node.tag.number < next_number {
+            return Err(Error("Unexpected element in TBSCertificate"));
+        }
+        match (node.tag.number, node.tag.constructed) {
+            (1, false) => layout.issuer_unique_id = Some(idx),
+            (2, false) => layout.subject_unique_id = Some(idx),
+            (3, true) => layout.extensions = Some(idx),
+            _ => return Err(Error("Unexpected element in TBSCertificate")),
+        }
+        next_number = node.tag.number + 1;
+    }
+
+    return Ok(layout);
+}
+
+fn validity(node: &Node, bytes: &[u8]) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    if node.children.len() != 2 {
+        return Err(Error("Validity must be a SEQUENCE of two elements"));
+    }
+    let not_before = value::time(node.children[0].tag, node.children[0].content(bytes))?;
+    let not_after = value::time(node.children[1].tag, node.children[1].content(bytes))?;
+    return Ok((not_before, not_after));
+}
+
+/// The elements of a Certificate that other structures refer to it by.
+pub struct Fields<'a> {
+    pub serial: &'a Node,
+    pub issuer: &'a Node,
+    pub validity: &'a Node,
+    pub subject: &'a Node,
+    pub spki: &'a Node,
+    /// The SEQUENCE of Extension.
+    pub extensions: Option<&'a Node>,
+}
+
+impl<'a> Fields<'a> {
+    pub fn new(node: &'a Node) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() != 3 {
+            return Err(Error("Certificate must be a SEQUENCE of three elements"));
+        }
+        let tbs = &node.children[0];
+        let layout = layout(tbs)?;
+        return Ok(Self {
+            serial: &tbs.children[layout.serial],
+            issuer: &tbs.children[layout.issuer],
+            validity: &tbs.children[layout.validity],
+            subject: &tbs.children[layout.subject],
+            spki: &tbs.children[layout.spki],
+            extensions: layout.extensions.and_then(|idx| tbs.children[idx].children.first()),
+        });
+    }
+
+    /// The notBefore and notAfter times.
+    pub fn validity(&self, bytes: &[u8]) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        return validity(self.validity, bytes);
+    }
+
+    /// The OID, criticality and extnValue of every well-formed extension.
+    pub fn extension_list(&self, bytes: &[u8]) -> Vec<(String, bool, &'a Node)> {
+        let mut list = Vec::new();
+        for extension in self.extensions.map(|node| node.children.as_slice()).unwrap_or_default() {
+            let extn_id = match extension.children.first().filter(|node| node.tag == Tag::OID) {
+                Some(extn_id) => extn_id,
+                None => continue,
+            };
+            let (id, extn_value) = match (oid::stringify(extn_id.content(bytes)), extension.children.last()) {
+                (Ok(id), Some(extn_value)) => (id, extn_value),
+                _ => continue,
+            };
+            let critical = extension.children.len() == 3 && value::boolean(extension.children[1].content(bytes)) == Ok(true);
+            list.push((id, critical, extn_value));
+        }
+        return list;
+    }
+
+    /// The extnValue of the extension with the given OID, and whether the
+    /// extension is critical.
+    pub fn extension(&self, bytes: &[u8], id: &str) -> Option<(&'a Node, bool)> {
+        return self
+            .extension_list(bytes)
+            .into_iter()
+            .find(|(extn_id, _, _)| extn_id == id)
+            .map(|(_, critical, extn_value)| (extn_value, critical));
+    }
+
+    pub fn subject_key_identifier<'b>(&self, bytes: &'b [u8]) -> Option<&'b [u8]> {
+        let (extn_value, _) = self.extension(bytes, "2.5.29.14")?;
+        let key_id = extn_value.children.first().filter(|node| node.tag == Tag::OCTET_STRING)?;
+        return Some(key_id.content(bytes));
+    }
+
+    /// The keyIdentifier of the authorityKeyIdentifier extension.
+    pub fn authority_key_identifier<'b>(&self, bytes: &'b [u8]) -> Option<&'b [u8]> {
+        let (extn_value, _) = self.extension(bytes, "2.5.29.35")?;
+        let aki = extn_value.children.first().filter(|node| node.tag == Tag::SEQUENCE)?;
+        let key_id = aki.children.first().filter(|node| node.tag == Tag::context_specific(0))?;
+        return Some(key_id.content(bytes));
+    }
+}
+
+/// Name the children of an AlgorithmIdentifier.
+pub fn annotate_algorithm(node: &mut Node) {
+    node.set_child_field(0, "algorithm");
+    node.set_child_field(1, "parameters");
+}
+
+impl Certificate {
+    /// Decode a Certificate, failing if the node doesn't have its shape.
+    pub fn parse(node: &Node, bytes: &[u8]) -> Result<Self> {
+        if node.tag != Tag::SEQUENCE || node.children.len() != 3 {
+            return Err(Error("Certificate must be a SEQUENCE of three elements"));
+        }
+
+        let tbs = &node.children[0];
+        let layout = layout(tbs)?;
+        let (signature_algorithm, _) = spki::algorithm(node.child(1, Tag::SEQUENCE)?, bytes)?;
+        node.child(2, Tag::BIT_STRING)?;
+
+        let version = match layout.version {
+            Some(idx) => {
+                let version = tbs.children[idx].child(0, Tag::INTEGER)?;
+                match version.content(bytes) {
+                    [version @ 0..=2] => u64::from(*version) + 1,
+                    _ => return Err(Error("Invalid certificate version")),
+                }
+            }
+            None => 1,
+        };
+
+        let (_, serial) = value::integer_magnitude(tbs.children[layout.serial].content(bytes))?;
+        let (not_before, not_after) = validity(&tbs.children[layout.validity], bytes)?;
+        let raw = node.raw(bytes);
+
+        return Ok(Self {
+            version,
+            serial: serial.to_vec(),
+            signature_algorithm: oid_names::name_or_oid(signature_algorithm),
+            issuer: name::format(&tbs.children[layout.issuer], bytes)?,
+            subject: name::format(&tbs.children[layout.subject], bytes)?,
+            not_before,
+            not_after,
+            public_key: spki::describe(&tbs.children[layout.spki], bytes)?,
+            sha1: Sha1::digest(raw).to_vec(),
+            sha256: Sha256::digest(raw).to_vec(),
+        });
+    }
+
+    /// Name every field of a node previously accepted by `parse`.
+    pub fn annotate(node: &mut Node, bytes: &[u8], version: u64) {
+        if let Some(algorithm) = node.set_child_field(1, "signatureAlgorithm") {
+            annotate_algorithm(algorithm);
+        }
+        node.set_child_field(2, "signatureValue");
+
+        let tbs = match node.set_child_field(0, "tbsCertificate") {
+            Some(tbs) => tbs,
+            None => return,
+        };
+
+        let layout = match layout(tbs) {
+            Ok(layout) => layout,
+            Err(_) => return,
+        };
+
+        if let Some(idx) = layout.version {
+            if let Some(node) = tbs.set_child_field(idx, "version") {
+                node.note = Some(format!("v{}", version));
+            }
+        }
+
+        tbs.set_child_field(layout.serial, "serialNumber");
+        if let Some(algorithm) = tbs.set_child_field(layout.signature, "signature") {
+            annotate_algorithm(algorithm);
+        }
+        tbs.set_child_field(layout.issuer, "issuer");
+        if let Some(validity) = tbs.set_child_field(layout.validity, "validity") {
+            validity.set_child_field(0, "notBefore");
+            validity.set_child_field(1, "notAfter");
+        }
+        tbs.set_child_field(layout.subject, "subject");
+        if let Some(spki) = tbs.set_child_field(layout.spki, "subjectPublicKeyInfo") {
+            if let Some(algorithm) = spki.set_child_field(0, "algorithm") {
+                annotate_algorithm(algorithm);
+            }
+            spki.set_child_field(1, "subjectPublicKey");
+        }
+        if let Some(idx) = layout.issuer_unique_id {
+            tbs.set_child_field(idx, "issuerUniqueID");
+        }
+        if let Some(idx) = layout.subject_unique_id {
+            tbs.set_child_field(idx, "subjectUniqueID");
+        }
+        if let Some(idx) = layout.extensions {
+            if let Some(extensions) = tbs.set_child_field(idx, "extensions") {
+                for extensions in extensions.children.iter_mut() {
+                    for extension in extensions.children.iter_mut() {
+                        extension::annotate(extension, bytes);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hex, node};
+
+    #[test]
+    fn parse_and_annotate_certificate() {
+        let bytes = include_bytes!("../tests/leaf.der");
+        let (mut nodes, _) = node::parse(bytes);
+        let certificate = Certificate::parse(&nodes[0], bytes).unwrap();
+        assert_eq!(certificate.version, 3);
+        assert_eq!(hex::hexlify(&certificate.serial), "0123456789abcdef0123");
+        assert_eq!(certificate.signature_algorithm, "sha256WithRSAEncryption");
+        assert_eq!(certificate.issuer, "CN=Explo-DER Test Intermediate CA,OU=Issuing,O=Explo-DER Test,C=US");
+        assert_eq!(certificate.public_key, "EC secp384r1 (384 bits)");
+        assert!(certificate.not_before < certificate.not_after);
+
+        Certificate::annotate(&mut nodes[0], bytes, certificate.version);
+        let tbs = &nodes[0].children[0];
+        assert_eq!(tbs.field.as_deref(), Some("tbsCertificate"));
+        assert_eq!(tbs.children[0].note.as_deref(), Some("v3"));
+        assert_eq!(tbs.children[6].children[0].children[0].field.as_deref(), Some("algorithm"));
+        assert_eq!(tbs.children[7].field.as_deref(), Some("extensions"));
+        assert_eq!(tbs.children[7].children[0].children[0].children[0].field.as_deref(), Some("extnID"));
+    }
+
+    #[test]
+    fn reject_other_structures() {
+        let bytes = include_bytes!("../tests/no-password.pfx");
+        let (nodes, _) = node::parse(bytes);
+        assert!(Certificate::parse(&nodes[0], bytes).is_err());
+    }
+}